@@ -0,0 +1,643 @@
+pub mod blip;
+pub mod dmc;
+pub mod expansion;
+pub mod filter;
+pub mod noise;
+pub mod pulse;
+pub mod triangle;
+
+use self::{blip::BlipBuffer, dmc::Dmc, expansion::ExpansionAudio, filter::OutputFilter, noise::Noise, pulse::Pulse, triangle::Triangle};
+
+/// NTSC CPU clock, the rate the APU is run at
+pub const CPU_CLOCK_RATE: f64 = 1_789_773.0;
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Values loaded into a length counter, indexed by the top five bits of the channel's fourth register
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+/// Silences a channel after a set number of half frames
+#[derive(Debug, Default)]
+pub struct LengthCounter {
+    pub enabled: bool,
+    pub halt: bool,
+    pub counter: u8,
+}
+
+impl LengthCounter {
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(index >> 3) as usize];
+        }
+    }
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+    pub fn clock(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+    pub fn active(&self) -> bool {
+        self.counter > 0
+    }
+}
+
+/// Volume envelope shared by the pulse and noise channels
+#[derive(Debug, Default)]
+pub struct Envelope {
+    pub start: bool,
+    /// Also the length counter halt flag
+    pub looping: bool,
+    pub constant_volume: bool,
+    /// The constant volume, or the reload value of the divider
+    pub period: u8,
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    pub fn write(&mut self, data: u8) {
+        self.looping = data & 0b0010_0000 != 0;
+        self.constant_volume = data & 0b0001_0000 != 0;
+        self.period = data & 0b0000_1111;
+    }
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.period;
+        } else if self.divider == 0 {
+            self.divider = self.period;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+    pub fn volume(&self) -> u8 {
+        if self.constant_volume {
+            self.period
+        } else {
+            self.decay
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameCounterMode {
+    FourStep,
+    FiveStep,
+}
+
+/// A single source of sound, for exporting each one on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Pulse1,
+    Pulse2,
+    Triangle,
+    Noise,
+    Dmc,
+    /// Sound chips on the cartridge
+    Expansion,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 6] = [
+        Channel::Pulse1,
+        Channel::Pulse2,
+        Channel::Triangle,
+        Channel::Noise,
+        Channel::Dmc,
+        Channel::Expansion,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Channel::Pulse1 => "pulse1",
+            Channel::Pulse2 => "pulse2",
+            Channel::Triangle => "triangle",
+            Channel::Noise => "noise",
+            Channel::Dmc => "dmc",
+            Channel::Expansion => "expansion",
+        }
+    }
+}
+
+/// A resampler of its own for one channel
+struct Stem {
+    last_output: f32,
+    blip: BlipBuffer,
+    filter: Option<OutputFilter>,
+}
+
+impl Stem {
+    /// Starts at `level` from `clock` on, with no samples before it
+    fn new(sample_rate: u32, filtered: bool, level: f32, clock: u64) -> Self {
+        let mut blip = BlipBuffer::new(CPU_CLOCK_RATE, sample_rate);
+        blip.skip_to(clock);
+        blip.add_delta(clock, level);
+        Self {
+            last_output: level,
+            blip,
+            filter: filtered.then(|| OutputFilter::new(sample_rate)),
+        }
+    }
+}
+
+/// What a frame counter step clocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameEvent {
+    None,
+    /// Envelopes and the triangle's linear counter
+    Quarter,
+    /// Quarter plus the length counters and sweep units
+    Half,
+}
+
+/// The 2A03's audio processing unit
+pub struct Apu {
+    pub pulse1: Pulse,
+    pub pulse2: Pulse,
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: Dmc,
+    /// Sound chip on the cartridge, if it has one
+    pub expansion: Option<Box<dyn ExpansionAudio>>,
+
+    frame_counter_mode: FrameCounterMode,
+    frame_irq_inhibit: bool,
+    pub frame_irq: bool,
+    /// CPU cycles into the current frame counter sequence
+    frame_cycle: u32,
+    /// CPU cycles until a $4017 write resets the sequence
+    frame_reset_delay: u8,
+    /// The pulse and noise timers only run on every other CPU cycle
+    odd_cycle: bool,
+
+    /// CPU cycles since the resampler was created
+    cycle: u64,
+    /// Mixer output as of the last cycle, the resampler only hears about changes
+    last_output: f32,
+    sample_rate: u32,
+    blip: BlipBuffer,
+    /// The console's analog output stage, None to hand out the raw DAC output
+    filter: Option<OutputFilter>,
+    /// One resampler per entry of `Channel::ALL` while stems are enabled
+    stems: Option<Vec<Stem>>,
+}
+
+impl Apu {
+    pub fn new() -> Self {
+        Self {
+            pulse1: Pulse::new(1),
+            pulse2: Pulse::new(2),
+            triangle: Triangle::default(),
+            noise: Noise::new(),
+            dmc: Dmc::new(),
+            expansion: None,
+            frame_counter_mode: FrameCounterMode::FourStep,
+            frame_irq_inhibit: false,
+            frame_irq: false,
+            frame_cycle: 0,
+            frame_reset_delay: 0,
+            odd_cycle: false,
+            cycle: 0,
+            last_output: 0.0,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            blip: BlipBuffer::new(CPU_CLOCK_RATE, DEFAULT_SAMPLE_RATE),
+            filter: Some(OutputFilter::new(DEFAULT_SAMPLE_RATE)),
+            stems: None,
+        }
+    }
+
+    /// DMA reads can only happen on every other CPU cycle, the get half of an APU cycle
+    pub fn get_cycle(&self) -> bool {
+        self.odd_cycle
+    }
+
+    /// Level of the APU's /IRQ output
+    pub fn irq_line(&self) -> bool {
+        self.frame_irq || self.dmc.irq
+    }
+
+    /// Runs the APU for a single CPU cycle
+    pub fn tick(&mut self) {
+        self.triangle.clock_timer();
+        self.dmc.clock_timer();
+        if self.odd_cycle {
+            self.pulse1.clock_timer();
+            self.pulse2.clock_timer();
+            self.noise.clock_timer();
+        }
+        self.odd_cycle = !self.odd_cycle;
+        if let Some(expansion) = &mut self.expansion {
+            expansion.tick();
+        }
+
+        self.clock_frame_counter();
+
+        self.cycle += 1;
+        let output = self.output();
+        if output != self.last_output {
+            self.blip.add_delta(self.cycle, output - self.last_output);
+            self.last_output = output;
+        }
+        self.update_stems();
+    }
+
+    fn update_stems(&mut self) {
+        let Some(mut stems) = self.stems.take() else {
+            return;
+        };
+        for (stem, channel) in stems.iter_mut().zip(Channel::ALL) {
+            let output = self.channel_output(channel);
+            if output != stem.last_output {
+                stem.blip.add_delta(self.cycle, output - stem.last_output);
+                stem.last_output = output;
+            }
+        }
+        self.stems = Some(stems);
+    }
+
+    fn clock_frame_counter(&mut self) {
+        if self.frame_reset_delay > 0 {
+            self.frame_reset_delay -= 1;
+            if self.frame_reset_delay == 0 {
+                self.frame_cycle = 0;
+                if self.frame_counter_mode == FrameCounterMode::FiveStep {
+                    self.clock_frame_event(FrameEvent::Half);
+                }
+                return;
+            }
+        }
+
+        self.frame_cycle += 1;
+        let event = match (self.frame_counter_mode, self.frame_cycle) {
+            (_, 7457) => FrameEvent::Quarter,
+            (_, 14913) => FrameEvent::Half,
+            (_, 22371) => FrameEvent::Quarter,
+            (FrameCounterMode::FourStep, 29828) => {
+                self.raise_frame_irq();
+                FrameEvent::None
+            }
+            (FrameCounterMode::FourStep, 29829) => {
+                self.raise_frame_irq();
+                FrameEvent::Half
+            }
+            (FrameCounterMode::FourStep, 29830) => {
+                self.raise_frame_irq();
+                self.frame_cycle = 0;
+                FrameEvent::None
+            }
+            (FrameCounterMode::FiveStep, 37281) => FrameEvent::Half,
+            (FrameCounterMode::FiveStep, 37282) => {
+                self.frame_cycle = 0;
+                FrameEvent::None
+            }
+            _ => FrameEvent::None,
+        };
+        self.clock_frame_event(event);
+    }
+
+    fn raise_frame_irq(&mut self) {
+        if !self.frame_irq_inhibit {
+            self.frame_irq = true;
+        }
+    }
+
+    fn clock_frame_event(&mut self, event: FrameEvent) {
+        if event == FrameEvent::None {
+            return;
+        }
+
+        self.pulse1.envelope.clock();
+        self.pulse2.envelope.clock();
+        self.noise.envelope.clock();
+        self.triangle.clock_linear_counter();
+
+        if event == FrameEvent::Half {
+            self.pulse1.length_counter.clock();
+            self.pulse2.length_counter.clock();
+            self.triangle.length_counter.clock();
+            self.noise.length_counter.clock();
+            self.pulse1.clock_sweep();
+            self.pulse2.clock_sweep();
+        }
+    }
+
+    /// Mixes the channels with the nonlinear DAC curves of the real hardware, the result is in 0.0..=1.0
+    pub fn output(&self) -> f32 {
+        let pulse = (self.pulse1.output() + self.pulse2.output()) as f32;
+        let tnd = self.triangle.output() as f32 / 8227.0
+            + self.noise.output() as f32 / 12241.0
+            + self.dmc.output() as f32 / 22638.0;
+
+        pulse_mix(pulse) + tnd_mix(tnd) + self.channel_output(Channel::Expansion)
+    }
+
+    /// What the mixer would put out if `channel` were the only one playing
+    pub fn channel_output(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Pulse1 => pulse_mix(self.pulse1.output() as f32),
+            Channel::Pulse2 => pulse_mix(self.pulse2.output() as f32),
+            Channel::Triangle => tnd_mix(self.triangle.output() as f32 / 8227.0),
+            Channel::Noise => tnd_mix(self.noise.output() as f32 / 12241.0),
+            Channel::Dmc => tnd_mix(self.dmc.output() as f32 / 22638.0),
+            Channel::Expansion => self.expansion.as_ref().map_or(0.0, |expansion| expansion.output()),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Changes the output rate, dropping any samples that have not been drained yet
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.cycle = 0;
+        self.blip = BlipBuffer::new(CPU_CLOCK_RATE, sample_rate);
+        // The new buffer starts from silence, step it up to where the mixer is now
+        self.blip.add_delta(0, self.last_output);
+        if self.filter.is_some() {
+            self.filter = Some(OutputFilter::new(sample_rate));
+        }
+        if self.stems.is_some() {
+            self.set_stems_enabled(true);
+        }
+    }
+
+    pub fn filters_enabled(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    /// Whether samples go through the high and low pass filters of the console's audio output
+    pub fn set_filters_enabled(&mut self, enabled: bool) {
+        self.filter = enabled.then(|| OutputFilter::new(self.sample_rate));
+        if let Some(stems) = &mut self.stems {
+            for stem in stems {
+                stem.filter = enabled.then(|| OutputFilter::new(self.sample_rate));
+            }
+        }
+    }
+
+    pub fn stems_enabled(&self) -> bool {
+        self.stems.is_some()
+    }
+
+    /// Starts or stops resampling every channel separately as well as the mix. Stems begin at the current cycle,
+    /// so drain the mix first for the two to line up sample for sample.
+    pub fn set_stems_enabled(&mut self, enabled: bool) {
+        self.stems = enabled.then(|| {
+            Channel::ALL
+                .iter()
+                .map(|&channel| {
+                    Stem::new(self.sample_rate, self.filter.is_some(), self.channel_output(channel), self.cycle)
+                })
+                .collect()
+        });
+    }
+
+    pub fn samples_available(&self) -> usize {
+        self.blip.samples_available(self.cycle)
+    }
+
+    /// Copies as many resampled samples as are ready and fit into `out`, returning how many that was
+    pub fn drain_samples(&mut self, out: &mut [f32]) -> usize {
+        let count = self.blip.read_samples(self.cycle, out);
+        if let Some(filter) = &mut self.filter {
+            for sample in &mut out[..count] {
+                *sample = filter.process(*sample);
+            }
+        }
+        count
+    }
+
+    /// Like `drain_samples` for a single channel, returns 0 when stems are not enabled
+    pub fn drain_stem(&mut self, channel: Channel, out: &mut [f32]) -> usize {
+        let Some(stems) = &mut self.stems else {
+            return 0;
+        };
+        let stem = &mut stems[channel as usize];
+        let count = stem.blip.read_samples(self.cycle, out);
+        if let Some(filter) = &mut stem.filter {
+            for sample in &mut out[..count] {
+                *sample = filter.process(*sample);
+            }
+        }
+        count
+    }
+
+    /// What the reset button does: every channel is silenced as if $4015 was written with 0 and the frame
+    /// counter starts over in its current mode
+    pub fn reset(&mut self) {
+        self.write_register(0x4015, 0);
+        self.frame_irq = false;
+        self.frame_cycle = 0;
+    }
+
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        match address {
+            0x4000..=0x4003 => self.pulse1.write_register(address, data),
+            0x4004..=0x4007 => self.pulse2.write_register(address, data),
+            0x4008..=0x400B => self.triangle.write_register(address, data),
+            0x400C..=0x400F => self.noise.write_register(address, data),
+            0x4010..=0x4013 => self.dmc.write_register(address, data),
+            0x4015 => {
+                self.pulse1.length_counter.set_enabled(data & 0b0000_0001 != 0);
+                self.pulse2.length_counter.set_enabled(data & 0b0000_0010 != 0);
+                self.triangle.length_counter.set_enabled(data & 0b0000_0100 != 0);
+                self.noise.length_counter.set_enabled(data & 0b0000_1000 != 0);
+                self.dmc.set_enabled(data & 0b0001_0000 != 0);
+                self.dmc.irq = false;
+            }
+            0x4017 => {
+                self.frame_counter_mode = if data & 0b1000_0000 != 0 {
+                    FrameCounterMode::FiveStep
+                } else {
+                    FrameCounterMode::FourStep
+                };
+                self.frame_irq_inhibit = data & 0b0100_0000 != 0;
+                if self.frame_irq_inhibit {
+                    self.frame_irq = false;
+                }
+                // The sequence restarts 3 or 4 CPU cycles later depending on where in the APU cycle the write lands
+                self.frame_reset_delay = if self.odd_cycle { 4 } else { 3 };
+            }
+            _ => {}
+        }
+    }
+
+    /// Passes a write to cartridge space on to the expansion audio chip
+    pub fn write_expansion(&mut self, address: u16, data: u8) {
+        if let Some(expansion) = &mut self.expansion {
+            expansion.write(address, data);
+        }
+    }
+
+    pub fn read_expansion(&mut self, address: u16) -> Option<u8> {
+        self.expansion.as_mut().and_then(|expansion| expansion.read(address))
+    }
+
+    /// Reads $4015, which acknowledges the frame interrupt
+    pub fn read_status(&mut self) -> u8 {
+        let status = (self.dmc.irq as u8) << 7
+            | (self.frame_irq as u8) << 6
+            | (self.dmc.active() as u8) << 4
+            | (self.noise.length_counter.active() as u8) << 3
+            | (self.triangle.length_counter.active() as u8) << 2
+            | (self.pulse2.length_counter.active() as u8) << 1
+            | self.pulse1.length_counter.active() as u8;
+        self.frame_irq = false;
+        status
+    }
+}
+
+/// The DAC curve shared by both pulse channels, from the sum of their outputs
+fn pulse_mix(pulse: f32) -> f32 {
+    if pulse == 0.0 {
+        0.0
+    } else {
+        95.88 / (8128.0 / pulse + 100.0)
+    }
+}
+
+/// The DAC curve shared by the triangle, noise and DMC, from the sum of their weighted outputs
+fn tnd_mix(tnd: f32) -> f32 {
+    if tnd == 0.0 {
+        0.0
+    } else {
+        159.79 / (1.0 / tnd + 100.0)
+    }
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_length_counter_status() {
+        let mut apu = Apu::new();
+        // Loading while disabled is ignored
+        apu.write_register(0x4003, 0b0000_1000);
+        assert_eq!(apu.read_status() & 0b1, 0);
+
+        apu.write_register(0x4015, 0b0000_0001);
+        apu.write_register(0x4003, 0b0000_1000);
+        assert_eq!(apu.read_status() & 0b1, 1);
+
+        apu.write_register(0x4015, 0);
+        assert_eq!(apu.read_status() & 0b1, 0);
+    }
+
+    #[test]
+    fn test_length_counter_runs_out() {
+        let mut apu = Apu::new();
+        apu.write_register(0x4015, 0b0000_0001);
+        // Index 3 loads a length of 2, so two half frames silence the channel
+        apu.write_register(0x4003, 3 << 3);
+        for _ in 0..14913 {
+            apu.tick();
+        }
+        assert_eq!(apu.pulse1.length_counter.counter, 1);
+        for _ in 14913..29830 {
+            apu.tick();
+        }
+        assert_eq!(apu.read_status() & 0b1, 0);
+    }
+
+    #[test]
+    fn test_frame_irq() {
+        let mut apu = Apu::new();
+        for _ in 0..29827 {
+            apu.tick();
+        }
+        assert!(!apu.irq_line());
+        apu.tick();
+        assert!(apu.irq_line());
+
+        assert_ne!(apu.read_status() & 0b0100_0000, 0);
+        assert!(!apu.irq_line());
+
+        // Five step mode never raises it
+        apu.write_register(0x4017, 0b1000_0000);
+        for _ in 0..100_000 {
+            apu.tick();
+        }
+        assert!(!apu.irq_line());
+    }
+
+    #[test]
+    fn test_mixer_is_nonlinear() {
+        let mut apu = Apu::new();
+        // The triangle idles on its first step, which outputs 15
+        let triangle_only = apu.output();
+
+        apu.dmc.write_register(0x4011, 0x7F);
+        let both = apu.output();
+        let dmc_only = 159.79 / (22638.0 / 127.0 + 100.0);
+
+        assert!(both > triangle_only);
+        assert!(both < triangle_only + dmc_only);
+    }
+
+    #[test]
+    fn test_expansion_is_mixed_in() {
+        let mut apu = Apu::new();
+        let without = apu.output();
+        apu.expansion = expansion::for_mapper(24);
+        // VRC6 pulse 1 at full volume with the duty ignored
+        apu.write_expansion(0x9000, 0b1000_1111);
+        apu.write_expansion(0x9002, 0b1000_0000);
+
+        let level = apu.channel_output(Channel::Expansion);
+        assert!((level - expansion::APU_PULSE_FULL).abs() < 1e-6);
+        assert!((apu.output() - without - level).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_drain_samples_at_output_rate() {
+        let mut apu = Apu::new();
+        apu.set_sample_rate(48_000);
+        apu.set_filters_enabled(false);
+        apu.write_register(0x4015, 0b0000_0001);
+        // 50% duty, constant volume 15, period of $FD is 440Hz
+        apu.write_register(0x4000, 0b1011_1111);
+        apu.write_register(0x4001, 0b0000_1000);
+        apu.write_register(0x4002, 0xFD);
+        apu.write_register(0x4003, 0b0000_1000);
+
+        // A second of frames, read after each one the way a frontend would
+        let mut samples = vec![0.0; 48_000];
+        let mut count = 0;
+        for cycle in 0..CPU_CLOCK_RATE as usize {
+            apu.tick();
+            if cycle % 29_780 == 0 || cycle + 1 == CPU_CLOCK_RATE as usize {
+                count += apu.drain_samples(&mut samples[count..]);
+            }
+        }
+        assert!(count >= 47_999);
+
+        // Count rising edges through the middle of the square wave, which sits on top of the idle triangle
+        let samples = &samples[100..count];
+        let low = samples.iter().copied().fold(f32::MAX, f32::min);
+        let high = samples.iter().copied().fold(f32::MIN, f32::max);
+        let middle = (low + high) / 2.0;
+        let rising = samples.windows(2).filter(|pair| pair[0] < middle && pair[1] >= middle).count();
+        assert!((438..=441).contains(&rising));
+    }
+}
@@ -0,0 +1,186 @@
+use std::f64::consts::PI;
+
+/// Length of the band-limited step in output samples, also the resampler's latency
+const TAPS: usize = 16;
+/// How finely the position of a step between two output samples is resolved
+const PHASES: usize = 32;
+/// Cutoff of the band-limiting as a fraction of the output rate, a little under Nyquist
+const CUTOFF: f64 = 0.45;
+
+/// Band-limited step synthesis in the style of blip_buf.
+///
+/// Instead of taking a sample every CPU cycle and filtering, callers report each change in level with
+/// `add_delta`, which lays a band-limited step into a buffer of differences at the exact (sub-sample) time it
+/// happened. Reading integrates the differences back into levels at the output rate, free of the aliasing a
+/// naive decimation would cause.
+///
+/// The buffer holds a PAL frame's worth of samples, the longer of the two, plus the length of a step. When
+/// nobody reads for longer than that the oldest samples are dropped, so a frontend that ignores audio doesn't
+/// use more and more memory.
+pub struct BlipBuffer {
+    samples_per_clock: f64,
+    kernel: Vec<[f32; TAPS]>,
+    /// Ring of differences for the samples from `read_index` on
+    buffer: Box<[f32]>,
+    /// Where the sample at `read_index` is in `buffer`
+    head: usize,
+    /// Absolute index of the next output sample to read
+    read_index: u64,
+    integrator: f32,
+}
+
+impl BlipBuffer {
+    pub fn new(clock_rate: f64, sample_rate: u32) -> Self {
+        Self {
+            samples_per_clock: sample_rate as f64 / clock_rate,
+            kernel: build_kernel(),
+            buffer: vec![0.0; sample_rate as usize / 50 + TAPS].into_boxed_slice(),
+            head: 0,
+            read_index: 0,
+            integrator: 0.0,
+        }
+    }
+
+    /// Adds a change in level of `delta` at `clock`, counted from when the buffer was created. Deltas have to be
+    /// added in order and not before samples that were already read.
+    pub fn add_delta(&mut self, clock: u64, delta: f32) {
+        let position = clock as f64 * self.samples_per_clock;
+        let index = position as u64;
+        let phase = ((position - index as f64) * PHASES as f64) as usize;
+
+        let end = index + TAPS as u64;
+        let capacity = self.buffer.len();
+        if end - self.read_index > capacity as u64 {
+            self.discard(end - self.read_index - capacity as u64);
+        }
+        let start = self.head + (index - self.read_index) as usize;
+        for (offset, weight) in self.kernel[phase].iter().enumerate() {
+            self.buffer[(start + offset) % capacity] += delta * weight;
+        }
+    }
+
+    /// Drops the oldest `count` samples without reading them, keeping the change in level they carried
+    fn discard(&mut self, count: u64) {
+        for _ in 0..count.min(self.buffer.len() as u64) {
+            self.integrator += std::mem::take(&mut self.buffer[self.head]);
+            self.head = (self.head + 1) % self.buffer.len();
+        }
+        self.read_index += count;
+    }
+
+    /// Drops every sample before `clock` unread, however far ahead it is, keeping the level they ended at
+    pub fn skip_to(&mut self, clock: u64) {
+        self.discard(self.samples_available(clock) as u64);
+    }
+
+    /// Output samples that no delta at or after `clock` can change any more
+    pub fn samples_available(&self, clock: u64) -> usize {
+        let end = (clock as f64 * self.samples_per_clock) as u64;
+        end.saturating_sub(self.read_index) as usize
+    }
+
+    /// Fills `out` with as many finished samples as there are up to `clock`, returning how many were written
+    pub fn read_samples(&mut self, clock: u64, out: &mut [f32]) -> usize {
+        let count = self.samples_available(clock).min(out.len());
+        for sample in &mut out[..count] {
+            self.integrator += std::mem::take(&mut self.buffer[self.head]);
+            self.head = (self.head + 1) % self.buffer.len();
+            *sample = self.integrator;
+        }
+        self.read_index += count as u64;
+        count
+    }
+}
+
+/// Windowed sinc impulses for every phase, each one the derivative of a band-limited step
+fn build_kernel() -> Vec<[f32; TAPS]> {
+    let center = (TAPS / 2) as f64;
+    (0..PHASES)
+        .map(|phase| {
+            let offset = phase as f64 / PHASES as f64;
+            let mut taps = [0.0; TAPS];
+            for (k, tap) in taps.iter_mut().enumerate() {
+                let x = k as f64 - center - offset;
+                let sinc = if x == 0.0 { 1.0 } else { (2.0 * PI * CUTOFF * x).sin() / (2.0 * PI * CUTOFF * x) };
+                // Blackman window over the width of the kernel
+                let w = (x + center) / TAPS as f64;
+                let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+                *tap = sinc * window;
+            }
+            // Every step has to add up to exactly the delta
+            let sum: f64 = taps.iter().sum();
+            taps.map(|tap| (tap / sum) as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_step_settles_to_delta() {
+        let mut blip = BlipBuffer::new(1_789_773.0, 44_100);
+        blip.add_delta(1000, 0.5);
+        blip.add_delta(20_000, -0.25);
+
+        let mut out = [0.0; 1024];
+        let count = blip.read_samples(40_000, &mut out);
+        assert_eq!(count, (40_000.0 * 44_100.0 / 1_789_773.0) as usize);
+
+        // Before, between and after the steps, far enough from them to be past the ringing
+        assert!(out[5].abs() < 1e-6);
+        assert!((out[300] - 0.5).abs() < 1e-3);
+        assert!((out[count - 1] - 0.25).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_reads_continue_where_they_left_off() {
+        let mut blip = BlipBuffer::new(1_000_000.0, 100_000);
+        blip.add_delta(100, 1.0);
+
+        let mut out = [0.0; 10];
+        assert_eq!(blip.read_samples(100, &mut out), 10);
+        assert_eq!(blip.read_samples(100, &mut out), 0);
+        assert_eq!(blip.read_samples(1000, &mut out), 10);
+        assert_eq!(blip.read_samples(1000, &mut out), 10);
+        assert!((out[9] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_unread_samples_are_dropped() {
+        let mut blip = BlipBuffer::new(1_789_773.0, 48_000);
+        let capacity = blip.buffer.len();
+        blip.add_delta(1000, 0.5);
+        // A second of pulses with nothing reading them
+        for frame in 0..60 {
+            for cycle in (0..29_780).step_by(100) {
+                let clock = frame * 29_780 + cycle + 2000;
+                blip.add_delta(clock, 0.25);
+                blip.add_delta(clock + 50, -0.25);
+            }
+        }
+        assert_eq!(blip.buffer.len(), capacity);
+
+        // The level is still right once reading starts again
+        let clock = 61 * 29_780;
+        let mut out = vec![0.0; blip.samples_available(clock)];
+        let count = blip.read_samples(clock, &mut out);
+        assert!((out[count - 1] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_skip_to() {
+        let mut blip = BlipBuffer::new(1_789_773.0, 48_000);
+        blip.add_delta(1000, 0.5);
+        // An hour in, the same as a stem enabled late in a session
+        let clock = 3600 * 1_789_773;
+        blip.skip_to(clock);
+        assert_eq!(blip.samples_available(clock), 0);
+
+        blip.add_delta(clock, 0.25);
+        let mut out = [0.0; 100];
+        assert_eq!(blip.read_samples(clock + 3000, &mut out), 80);
+        assert!((out[79] - 0.75).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,174 @@
+/// Timer periods in CPU cycles
+const RATE_TABLE: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+
+/// The delta modulation channel, $4010-$4013. It plays 1 bit delta encoded samples that it fetches from CPU
+/// memory by itself, see `dma_request`.
+#[derive(Debug)]
+pub struct Dmc {
+    pub irq_enabled: bool,
+    pub irq: bool,
+    pub looping: bool,
+    timer_period: u16,
+    timer: u16,
+
+    /// 7 bit DAC level
+    output_level: u8,
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+
+    sample_buffer: Option<u8>,
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+}
+
+impl Dmc {
+    pub fn new() -> Self {
+        Self {
+            irq_enabled: false,
+            irq: false,
+            looping: false,
+            timer_period: RATE_TABLE[0],
+            timer: 0,
+            output_level: 0,
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+            sample_buffer: None,
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        match address & 0b11 {
+            0 => {
+                self.irq_enabled = data & 0b1000_0000 != 0;
+                if !self.irq_enabled {
+                    self.irq = false;
+                }
+                self.looping = data & 0b0100_0000 != 0;
+                self.timer_period = RATE_TABLE[(data & 0b1111) as usize];
+            }
+            1 => self.output_level = data & 0b0111_1111,
+            2 => self.sample_address = 0xC000 | (data as u16) << 6,
+            _ => self.sample_length = ((data as u16) << 4) | 1,
+        }
+    }
+
+    /// Bit 4 of $4015
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    /// Whether a sample is still being fetched, bit 4 of $4015 when read
+    pub fn active(&self) -> bool {
+        self.bytes_remaining > 0
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    /// The address of the next sample byte when the reader wants one. Whoever owns the bus has to read it and
+    /// hand it back through `load_sample_byte`.
+    pub fn dma_request(&self) -> Option<u16> {
+        if self.sample_buffer.is_none() && self.bytes_remaining > 0 {
+            Some(self.current_address)
+        } else {
+            None
+        }
+    }
+
+    pub fn load_sample_byte(&mut self, data: u8) {
+        self.sample_buffer = Some(data);
+        // The address wraps from $FFFF to $8000, not $0000
+        self.current_address = self.current_address.checked_add(1).unwrap_or(0x8000);
+        self.bytes_remaining -= 1;
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.timer_period - 1;
+
+        if !self.silence {
+            if self.shift_register & 1 != 0 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(data) => {
+                    self.silence = false;
+                    self.shift_register = data;
+                }
+                None => self.silence = true,
+            }
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        self.output_level
+    }
+}
+
+impl Default for Dmc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_fetch_and_irq() {
+        let mut dmc = Dmc::new();
+        dmc.write_register(0x4010, 0b1000_1111);
+        dmc.write_register(0x4012, 0xFF);
+        dmc.write_register(0x4013, 0x00);
+        dmc.set_enabled(true);
+
+        // A single byte sample at $FFC0
+        assert_eq!(dmc.dma_request(), Some(0xFFC0));
+        dmc.load_sample_byte(0xFF);
+        assert_eq!(dmc.dma_request(), None);
+        assert!(!dmc.active());
+        assert!(dmc.irq);
+
+        // Every bit of $FF ramps the level up by 2 once the output unit picks the byte up
+        for _ in 0..54 * 16 {
+            dmc.clock_timer();
+        }
+        assert_eq!(dmc.output(), 16);
+    }
+}
@@ -0,0 +1,55 @@
+pub mod fds;
+pub mod mmc5;
+pub mod n163;
+pub mod sunsoft5b;
+pub mod vrc6;
+pub mod vrc7;
+
+use self::{mmc5::Mmc5Audio, n163::N163, sunsoft5b::Sunsoft5b, vrc6::Vrc6, vrc7::Vrc7};
+
+/// An APU pulse channel playing alone at full volume, the yardstick for every chip's level below
+pub const APU_PULSE_FULL: f32 = 95.88 / (8128.0 / 15.0 + 100.0);
+
+// Peak output of each chip relative to `APU_PULSE_FULL`, as measured on Famicom carts. Boards differ by a few
+// dB, the Namco 163 most of all.
+
+/// A VRC6 pulse at volume 15
+pub const VRC6_LEVEL: f32 = 1.0;
+/// A VRC7 channel at volume 0 (no attenuation) with an undamped carrier
+pub const VRC7_LEVEL: f32 = 2.0;
+/// A single Namco 163 channel at volume 15 playing its loudest sample
+pub const N163_LEVEL: f32 = 3.0;
+/// A Sunsoft 5B channel at volume 15
+pub const SUNSOFT_5B_LEVEL: f32 = 2.0;
+/// The FDS wave at full gain and master volume
+pub const FDS_LEVEL: f32 = 2.4;
+
+/// Sound hardware on a cartridge, mixed in with the APU through the Famicom's expansion audio pins.
+///
+/// The chip sees the bus the way a mapper does: every CPU write to cartridge space, $4020-$FFFF, and reads from
+/// $4020-$5FFF, which nothing inside the console answers.
+pub trait ExpansionAudio {
+    fn write(&mut self, address: u16, data: u8);
+    /// The value of a readable register, None for addresses the chip doesn't drive
+    fn read(&mut self, _address: u16) -> Option<u8> {
+        None
+    }
+    /// Runs the chip for a single CPU cycle
+    fn tick(&mut self);
+    /// Current level in the same units as `Apu::output`
+    fn output(&self) -> f32;
+}
+
+/// The sound chip on boards that use iNES mapper `mapper`, if any
+pub fn for_mapper(mapper: u8) -> Option<Box<dyn ExpansionAudio>> {
+    match mapper {
+        5 => Some(Box::new(Mmc5Audio::new())),
+        19 => Some(Box::new(N163::new())),
+        24 => Some(Box::new(Vrc6::new(false))),
+        26 => Some(Box::new(Vrc6::new(true))),
+        69 => Some(Box::new(Sunsoft5b::new())),
+        85 => Some(Box::new(Vrc7::new())),
+        // The FDS has no mapper number, its disk images aren't iNES files
+        _ => None,
+    }
+}
@@ -0,0 +1,258 @@
+use super::{ExpansionAudio, APU_PULSE_FULL, FDS_LEVEL};
+
+/// Steps the modulation table adds to the modulation counter, 4 means reset it to 0
+const MODULATION_STEPS: [i8; 8] = [0, 1, 2, 4, 0, -4, -2, -1];
+/// Master volume from $4089, as fractions of full
+const MASTER_VOLUMES: [f32; 4] = [1.0, 2.0 / 3.0, 2.0 / 4.0, 2.0 / 5.0];
+
+/// The volume or the modulation depth envelope, $4080 and $4084
+#[derive(Debug, Default)]
+struct FdsEnvelope {
+    disabled: bool,
+    increase: bool,
+    speed: u8,
+    /// 0-63, anything above 32 is clamped when used
+    gain: u8,
+    counter: u32,
+}
+
+impl FdsEnvelope {
+    fn write(&mut self, data: u8) {
+        self.disabled = data & 0b1000_0000 != 0;
+        self.increase = data & 0b0100_0000 != 0;
+        self.speed = data & 0b11_1111;
+        if self.disabled {
+            self.gain = self.speed;
+        }
+        self.counter = 0;
+    }
+
+    fn clock(&mut self, master_speed: u8) {
+        if self.disabled || master_speed == 0 {
+            return;
+        }
+        self.counter += 1;
+        if self.counter < 8 * (master_speed as u32 + 1) * (self.speed as u32 + 1) {
+            return;
+        }
+        self.counter = 0;
+        if self.increase && self.gain < 32 {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+    }
+}
+
+/// The Famicom Disk System's sound: a 64 step wavetable whose pitch can be bent by a second, modulation table
+pub struct Fds {
+    /// $4023 bit 1
+    sound_enabled: bool,
+    wave: [u8; 64],
+    wave_writable: bool,
+    wave_halted: bool,
+    envelopes_halted: bool,
+    master_volume: u8,
+    /// $408A, scales both envelopes
+    envelope_speed: u8,
+    volume: FdsEnvelope,
+    sweep: FdsEnvelope,
+
+    pitch: u16,
+    /// 6.16 fixed point position in the wave
+    wave_accumulator: u32,
+    /// The volume is only latched at the start of each wave cycle
+    latched_volume: u8,
+
+    modulation_table: [u8; 64],
+    modulation_position: u8,
+    modulation_halted: bool,
+    modulation_pitch: u16,
+    modulation_accumulator: u16,
+    /// Signed 7 bit
+    modulation_counter: i8,
+}
+
+impl Fds {
+    pub fn new() -> Self {
+        Self {
+            sound_enabled: false,
+            wave: [0; 64],
+            wave_writable: false,
+            wave_halted: true,
+            envelopes_halted: false,
+            master_volume: 0,
+            envelope_speed: 0xE8,
+            volume: FdsEnvelope::default(),
+            sweep: FdsEnvelope::default(),
+            pitch: 0,
+            wave_accumulator: 0,
+            latched_volume: 0,
+            modulation_table: [0; 64],
+            modulation_position: 0,
+            modulation_halted: true,
+            modulation_pitch: 0,
+            modulation_accumulator: 0,
+            modulation_counter: 0,
+        }
+    }
+
+    /// The wave pitch after modulation, following the hardware's integer math
+    fn modulated_pitch(&self) -> u32 {
+        let mut temp = self.modulation_counter as i32 * self.sweep.gain as i32;
+        let remainder = temp & 0xF;
+        temp >>= 4;
+        if remainder > 0 && temp & 0x80 == 0 {
+            temp += if self.modulation_counter < 0 { -1 } else { 2 };
+        }
+        if temp >= 192 {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+
+        temp *= self.pitch as i32;
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if remainder >= 32 {
+            temp += 1;
+        }
+        (self.pitch as i32 + temp).max(0) as u32
+    }
+
+    fn clock_modulation(&mut self) {
+        if self.modulation_halted || self.modulation_pitch == 0 {
+            return;
+        }
+        let (accumulator, overflow) = self.modulation_accumulator.overflowing_add(self.modulation_pitch);
+        self.modulation_accumulator = accumulator;
+        if !overflow {
+            return;
+        }
+
+        let step = self.modulation_table[self.modulation_position as usize];
+        self.modulation_counter = if step == 4 {
+            0
+        } else {
+            // Wraps within 7 bits
+            ((self.modulation_counter + MODULATION_STEPS[step as usize]) << 1) >> 1
+        };
+        self.modulation_position = (self.modulation_position + 1) & 0x3F;
+    }
+}
+
+impl Default for Fds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpansionAudio for Fds {
+    fn write(&mut self, address: u16, data: u8) {
+        if address == 0x4023 {
+            self.sound_enabled = data & 0b10 != 0;
+            return;
+        }
+        if !self.sound_enabled {
+            return;
+        }
+        match address {
+            0x4040..=0x407F if self.wave_writable => self.wave[(address - 0x4040) as usize] = data & 0b11_1111,
+            0x4080 => self.volume.write(data),
+            0x4082 => self.pitch = (self.pitch & 0x0F00) | data as u16,
+            0x4083 => {
+                self.pitch = (self.pitch & 0x00FF) | ((data as u16 & 0b1111) << 8);
+                self.wave_halted = data & 0b1000_0000 != 0;
+                self.envelopes_halted = data & 0b0100_0000 != 0;
+                if self.wave_halted {
+                    self.wave_accumulator = 0;
+                }
+            }
+            0x4084 => self.sweep.write(data),
+            0x4085 => self.modulation_counter = ((data << 1) as i8) >> 1,
+            0x4086 => self.modulation_pitch = (self.modulation_pitch & 0x0F00) | data as u16,
+            0x4087 => {
+                self.modulation_pitch = (self.modulation_pitch & 0x00FF) | ((data as u16 & 0b1111) << 8);
+                self.modulation_halted = data & 0b1000_0000 != 0;
+                if self.modulation_halted {
+                    self.modulation_accumulator = 0;
+                }
+            }
+            // Only takes writes while modulation is halted, each one fills two entries
+            0x4088 if self.modulation_halted => {
+                for _ in 0..2 {
+                    self.modulation_table[self.modulation_position as usize] = data & 0b111;
+                    self.modulation_position = (self.modulation_position + 1) & 0x3F;
+                }
+            }
+            0x4089 => {
+                self.wave_writable = data & 0b1000_0000 != 0;
+                self.master_volume = data & 0b11;
+            }
+            0x408A => self.envelope_speed = data,
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, address: u16) -> Option<u8> {
+        match address {
+            0x4040..=0x407F => Some(self.wave[(address - 0x4040) as usize]),
+            0x4090 => Some(self.volume.gain),
+            0x4092 => Some(self.sweep.gain),
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self) {
+        if !self.envelopes_halted && !self.wave_halted {
+            self.volume.clock(self.envelope_speed);
+            self.sweep.clock(self.envelope_speed);
+        }
+        self.clock_modulation();
+
+        if self.wave_halted {
+            return;
+        }
+        let previous = self.wave_accumulator;
+        self.wave_accumulator = (self.wave_accumulator + self.modulated_pitch()) & 0x3F_FFFF;
+        if self.wave_accumulator < previous {
+            self.latched_volume = self.volume.gain.min(32);
+        }
+    }
+
+    fn output(&self) -> f32 {
+        let index = (self.wave_accumulator >> 16) as usize;
+        let sample = self.wave[index] as f32 / 63.0;
+        let gain = self.latched_volume as f32 / 32.0;
+        sample * gain * MASTER_VOLUMES[self.master_volume as usize] * FDS_LEVEL * APU_PULSE_FULL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unmodulated_pitch() {
+        let mut fds = Fds::new();
+        fds.write(0x4023, 0b10);
+        fds.write(0x4082, 0x34);
+        fds.write(0x4083, 0x02);
+        assert_eq!(fds.modulated_pitch(), 0x234);
+
+        // A full depth sweep with the counter at -32 bends the pitch all the way down
+        fds.write(0x4084, 0b1010_0000);
+        fds.write(0x4085, 0x60);
+        assert_eq!(fds.modulated_pitch(), 0);
+    }
+
+    #[test]
+    fn test_modulation_table_writes_pairs() {
+        let mut fds = Fds::new();
+        fds.write(0x4023, 0b10);
+        fds.write(0x4087, 0b1000_0000);
+        fds.write(0x4088, 3);
+        fds.write(0x4088, 5);
+        assert_eq!(fds.modulation_table[..4], [3, 3, 5, 5]);
+    }
+}
@@ -0,0 +1,106 @@
+use super::ExpansionAudio;
+use crate::apu::{pulse::Pulse, pulse_mix, tnd_mix};
+
+/// CPU cycles between clocks of the envelopes and length counters, which run at a fixed 240Hz instead of
+/// following a frame counter
+const FRAME_PERIOD: u16 = 7457;
+
+/// The MMC5's two extra APU pulse channels and its 8 bit PCM channel
+pub struct Mmc5Audio {
+    pub pulse1: Pulse,
+    pub pulse2: Pulse,
+    frame_timer: u16,
+    odd_cycle: bool,
+    pcm_level: u8,
+}
+
+impl Mmc5Audio {
+    pub fn new() -> Self {
+        Self {
+            pulse1: Pulse::without_sweep(),
+            pulse2: Pulse::without_sweep(),
+            frame_timer: 0,
+            odd_cycle: false,
+            pcm_level: 0,
+        }
+    }
+}
+
+impl Default for Mmc5Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpansionAudio for Mmc5Audio {
+    fn write(&mut self, address: u16, data: u8) {
+        match address {
+            0x5000..=0x5003 => self.pulse1.write_register(address, data),
+            0x5004..=0x5007 => self.pulse2.write_register(address, data),
+            // Only write mode is supported, read mode and its IRQ would have to watch CPU reads from $8000-$BFFF.
+            // Writing 0 is ignored, in read mode it's what raises the IRQ
+            0x5011 if data != 0 => self.pcm_level = data,
+            0x5015 => {
+                self.pulse1.length_counter.set_enabled(data & 0b01 != 0);
+                self.pulse2.length_counter.set_enabled(data & 0b10 != 0);
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, address: u16) -> Option<u8> {
+        match address {
+            0x5010 => Some(0),
+            0x5015 => Some(
+                (self.pulse2.length_counter.active() as u8) << 1 | self.pulse1.length_counter.active() as u8,
+            ),
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.odd_cycle {
+            self.pulse1.clock_timer();
+            self.pulse2.clock_timer();
+        }
+        self.odd_cycle = !self.odd_cycle;
+
+        self.frame_timer += 1;
+        if self.frame_timer == FRAME_PERIOD {
+            self.frame_timer = 0;
+            for pulse in [&mut self.pulse1, &mut self.pulse2] {
+                pulse.envelope.clock();
+                pulse.length_counter.clock();
+            }
+        }
+    }
+
+    fn output(&self) -> f32 {
+        // Same DACs as the 2A03's own channels, the PCM level lands about where a DMC level of half would
+        let pulse = (self.pulse1.output() + self.pulse2.output()) as f32;
+        pulse_mix(pulse) + tnd_mix(self.pcm_level as f32 / 2.0 / 22638.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_low_periods_are_not_muted() {
+        let mut mmc5 = Mmc5Audio::new();
+        mmc5.write(0x5015, 0b01);
+        mmc5.write(0x5000, 0b0011_1111);
+        mmc5.write(0x5002, 0x04);
+        mmc5.write(0x5003, 0b0000_1000);
+        assert_eq!(mmc5.read(0x5015), Some(0b01));
+
+        let levels: Vec<u8> = (0..200)
+            .map(|_| {
+                mmc5.tick();
+                mmc5.pulse1.output()
+            })
+            .collect();
+        assert!(levels.contains(&15) && levels.contains(&0));
+    }
+}
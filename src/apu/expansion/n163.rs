@@ -0,0 +1,173 @@
+use super::{ExpansionAudio, APU_PULSE_FULL, N163_LEVEL};
+
+/// CPU cycles spent on each channel's update, the chip handles one channel at a time
+const CYCLES_PER_CHANNEL: u8 = 15;
+/// Channel registers occupy the top of the sound RAM, 8 bytes each, channel 7 last
+const CHANNEL_REGISTERS: usize = 0x40;
+
+/// Namco 163: up to 8 wavetable channels reading 4 bit samples out of 128 bytes of RAM shared with their
+/// registers
+pub struct N163 {
+    ram: [u8; 0x80],
+    /// $F800 address port, bit 7 increments it after every data port access
+    address: u8,
+    auto_increment: bool,
+    sound_disabled: bool,
+    cycle: u8,
+    /// Which channel is updated next, counting down from 7
+    current_channel: usize,
+    /// Last level each channel was updated to, -120..=105
+    levels: [i16; 8],
+}
+
+impl N163 {
+    pub fn new() -> Self {
+        Self {
+            ram: [0; 0x80],
+            address: 0,
+            auto_increment: false,
+            sound_disabled: false,
+            cycle: 0,
+            current_channel: 7,
+            levels: [0; 8],
+        }
+    }
+
+    /// 1 to 8, set by bits 4-6 of $7F
+    fn active_channels(&self) -> usize {
+        ((self.ram[0x7F] >> 4) & 0b111) as usize + 1
+    }
+
+    fn sample(&self, index: u8) -> u8 {
+        let byte = self.ram[(index >> 1) as usize & 0x7F];
+        if index & 1 == 0 {
+            byte & 0x0F
+        } else {
+            byte >> 4
+        }
+    }
+
+    fn update_channel(&mut self, channel: usize) {
+        let base = CHANNEL_REGISTERS + channel * 8;
+        let registers = &self.ram[base..base + 8];
+        let frequency = u32::from_le_bytes([registers[0], registers[2], registers[4] & 0b11, 0]);
+        let mut phase = u32::from_le_bytes([registers[1], registers[3], registers[5], 0]);
+        let length = 256 - (registers[4] & 0b1111_1100) as u32;
+        let wave_address = registers[6];
+        let volume = (registers[7] & 0b1111) as i16;
+
+        phase = (phase + frequency) % (length << 16);
+        let sample = self.sample(((phase >> 16) as u8).wrapping_add(wave_address));
+        self.levels[channel] = (sample as i16 - 8) * volume;
+
+        let phase = phase.to_le_bytes();
+        self.ram[base + 1] = phase[0];
+        self.ram[base + 3] = phase[1];
+        self.ram[base + 5] = phase[2];
+    }
+
+    fn data_port(&mut self) -> usize {
+        let address = self.address as usize;
+        if self.auto_increment {
+            self.address = (self.address + 1) & 0x7F;
+        }
+        address
+    }
+}
+
+impl Default for N163 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpansionAudio for N163 {
+    fn write(&mut self, address: u16, data: u8) {
+        match address & 0xF800 {
+            0x4800 => {
+                let address = self.data_port();
+                self.ram[address] = data;
+            }
+            // Shared with the mapper's CHR-RAM enable bits
+            0xE000 => self.sound_disabled = data & 0b0100_0000 != 0,
+            0xF800 => {
+                self.address = data & 0x7F;
+                self.auto_increment = data & 0b1000_0000 != 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn read(&mut self, address: u16) -> Option<u8> {
+        if address & 0xF800 == 0x4800 {
+            let address = self.data_port();
+            Some(self.ram[address])
+        } else {
+            None
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.sound_disabled {
+            return;
+        }
+        self.cycle += 1;
+        if self.cycle < CYCLES_PER_CHANNEL {
+            return;
+        }
+        self.cycle = 0;
+
+        self.update_channel(self.current_channel);
+        let first = 8 - self.active_channels();
+        self.current_channel = if self.current_channel <= first { 7 } else { self.current_channel - 1 };
+    }
+
+    fn output(&self) -> f32 {
+        if self.sound_disabled {
+            return 0.0;
+        }
+        // The real chip outputs one channel at a time, so more channels means each one is quieter. Averaging is
+        // what that time-multiplexing sounds like once filtered, minus the whine at 1.79MHz / 15 / channels
+        let active = self.active_channels();
+        let sum: i16 = self.levels[8 - active..].iter().sum();
+        sum as f32 / active as f32 / 105.0 * N163_LEVEL * APU_PULSE_FULL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_auto_increment_port() {
+        let mut n163 = N163::new();
+        n163.write(0xF800, 0b1000_0000 | 0x10);
+        n163.write(0x4800, 0xAB);
+        n163.write(0x4800, 0xCD);
+        assert_eq!(n163.ram[0x10..0x12], [0xAB, 0xCD]);
+
+        n163.write(0xF800, 0x11);
+        assert_eq!(n163.read(0x4800), Some(0xCD));
+        assert_eq!(n163.read(0x4800), Some(0xCD));
+    }
+
+    #[test]
+    fn test_channel_steps_through_wave() {
+        let mut n163 = N163::new();
+        // A 4 sample wave at address 0: 15, 0, 15, 0
+        n163.ram[0] = 0x0F;
+        n163.ram[1] = 0x0F;
+        // Channel 7, one sample per update, length 4, full volume, one channel active
+        n163.ram[0x7C] = 0b1111_1101;
+        n163.ram[0x7F] = 0x0F;
+
+        let mut levels = vec![];
+        for _ in 0..4 * CYCLES_PER_CHANNEL {
+            n163.tick();
+            if n163.cycle == 0 {
+                levels.push(n163.levels[7]);
+            }
+        }
+        assert_eq!(levels, [-8 * 15, 7 * 15, -8 * 15, 7 * 15]);
+    }
+}
@@ -0,0 +1,228 @@
+use super::{ExpansionAudio, APU_PULSE_FULL, SUNSOFT_5B_LEVEL};
+
+/// The chip's tone and noise counters are clocked once every 16 CPU cycles
+const PRESCALER: u8 = 16;
+
+/// One of the three square wave channels
+#[derive(Debug, Default)]
+struct Tone {
+    period: u16,
+    counter: u16,
+    high: bool,
+}
+
+impl Tone {
+    fn clock(&mut self) {
+        self.counter += 1;
+        if self.counter >= self.period {
+            self.counter = 0;
+            self.high = !self.high;
+        }
+    }
+}
+
+/// Sunsoft 5B, a YM2149F (the AY-3-8910 family) on a mapper: three squares, a shared noise generator and a
+/// shared envelope
+pub struct Sunsoft5b {
+    /// $C000 register select
+    selected: u8,
+    registers: [u8; 16],
+    tones: [Tone; 3],
+    prescaler: u8,
+    /// Noise runs at half the rate of the tones
+    noise_half: bool,
+    noise_counter: u8,
+    /// 17 bit LFSR
+    noise_shift: u32,
+    envelope_counter: u16,
+    /// 0-31 through one ramp, 5 bits where the channel volumes only have 4
+    envelope_step: u8,
+    envelope_holding: bool,
+    /// Which way the current ramp goes
+    envelope_attack: bool,
+}
+
+impl Sunsoft5b {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            registers: [0; 16],
+            tones: Default::default(),
+            prescaler: 0,
+            noise_half: false,
+            noise_counter: 0,
+            noise_shift: 1,
+            envelope_counter: 0,
+            envelope_step: 0,
+            envelope_holding: false,
+            envelope_attack: false,
+        }
+    }
+
+    fn write_register(&mut self, register: u8, data: u8) {
+        self.registers[register as usize] = data;
+        match register {
+            0..=5 => {
+                let tone = &mut self.tones[register as usize / 2];
+                let period = u16::from_le_bytes([self.registers[register as usize & !1], self.registers[register as usize | 1]]);
+                tone.period = period & 0x0FFF;
+            }
+            // Writing the shape restarts the envelope
+            13 => {
+                self.envelope_step = 0;
+                self.envelope_counter = 0;
+                self.envelope_holding = false;
+                self.envelope_attack = data & 0b0100 != 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn clock_noise(&mut self) {
+        self.noise_half = !self.noise_half;
+        if !self.noise_half {
+            return;
+        }
+        self.noise_counter += 1;
+        if self.noise_counter >= self.registers[6] & 0b1_1111 {
+            self.noise_counter = 0;
+            let feedback = (self.noise_shift ^ (self.noise_shift >> 3)) & 1;
+            self.noise_shift = (self.noise_shift >> 1) | (feedback << 16);
+        }
+    }
+
+    fn clock_envelope(&mut self) {
+        let period = u16::from_le_bytes([self.registers[11], self.registers[12]]);
+        self.envelope_counter += 1;
+        if self.envelope_counter < period || self.envelope_holding {
+            return;
+        }
+        self.envelope_counter = 0;
+        if self.envelope_step < 31 {
+            self.envelope_step += 1;
+            return;
+        }
+
+        let shape = self.registers[13];
+        let continuing = shape & 0b1000 != 0;
+        let alternate = shape & 0b0010 != 0;
+        let hold = shape & 0b0001 != 0;
+        if !continuing {
+            // Every shape without the continue bit ends at 0
+            self.envelope_holding = true;
+            self.envelope_attack = false;
+            self.envelope_step = 31;
+        } else if hold {
+            self.envelope_holding = true;
+            self.envelope_step = 31;
+            if alternate {
+                self.envelope_attack = !self.envelope_attack;
+            }
+        } else {
+            self.envelope_step = 0;
+            if alternate {
+                self.envelope_attack = !self.envelope_attack;
+            }
+        }
+    }
+
+    fn envelope_level(&self) -> u8 {
+        if self.envelope_attack {
+            self.envelope_step
+        } else {
+            31 - self.envelope_step
+        }
+    }
+
+    /// Volume of `channel` on the 5 bit scale, where each step is 1.5dB
+    fn channel_level(&self, channel: usize) -> u8 {
+        let mixer = self.registers[7];
+        let tone_off = mixer & (1 << channel) != 0;
+        let noise_off = mixer & (1 << (channel + 3)) != 0;
+        let on = (tone_off || self.tones[channel].high) && (noise_off || self.noise_shift & 1 != 0);
+        if !on {
+            return 0;
+        }
+
+        let volume = self.registers[8 + channel];
+        if volume & 0b1_0000 != 0 {
+            self.envelope_level()
+        } else if volume & 0b1111 == 0 {
+            0
+        } else {
+            (volume & 0b1111) * 2 + 1
+        }
+    }
+}
+
+impl Default for Sunsoft5b {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Logarithmic DAC, 1.5dB per step with 31 being full scale
+fn amplitude(level: u8) -> f32 {
+    if level == 0 {
+        0.0
+    } else {
+        10f32.powf((level as f32 - 31.0) * 1.5 / 20.0)
+    }
+}
+
+impl ExpansionAudio for Sunsoft5b {
+    fn write(&mut self, address: u16, data: u8) {
+        match address & 0xE000 {
+            0xC000 => self.selected = data & 0b1111,
+            0xE000 => self.write_register(self.selected, data),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self) {
+        self.prescaler += 1;
+        if self.prescaler < PRESCALER {
+            return;
+        }
+        self.prescaler = 0;
+
+        for tone in &mut self.tones {
+            tone.clock();
+        }
+        self.clock_noise();
+        self.clock_envelope();
+    }
+
+    fn output(&self) -> f32 {
+        let sum: f32 = (0..3).map(|channel| amplitude(self.channel_level(channel))).sum();
+        sum * SUNSOFT_5B_LEVEL * APU_PULSE_FULL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tone_period() {
+        let mut chip = Sunsoft5b::new();
+        for (register, data) in [(0, 10), (1, 0), (7, 0b11_1110), (8, 15)] {
+            chip.write(0xC000, register);
+            chip.write(0xE000, data);
+        }
+
+        // A period of 10 toggles every 160 CPU cycles
+        let mut toggles = 0;
+        let mut previous = chip.output();
+        for _ in 0..1600 {
+            chip.tick();
+            let output = chip.output();
+            if output != previous {
+                toggles += 1;
+            }
+            previous = output;
+        }
+        assert_eq!(toggles, 10);
+        assert!((amplitude(31) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,203 @@
+use super::{ExpansionAudio, APU_PULSE_FULL, VRC6_LEVEL};
+
+/// One of the VRC6's two pulse channels, $9000-$9002 and $A000-$A002
+#[derive(Debug, Default)]
+pub struct Vrc6Pulse {
+    /// Ignore the duty and output the volume constantly
+    digitized: bool,
+    /// The first `duty + 1` of the 16 steps are high
+    duty: u8,
+    volume: u8,
+    enabled: bool,
+    period: u16,
+    timer: u16,
+    step: u8,
+}
+
+impl Vrc6Pulse {
+    fn write(&mut self, register: u16, data: u8) {
+        match register {
+            0 => {
+                self.digitized = data & 0b1000_0000 != 0;
+                self.duty = (data >> 4) & 0b111;
+                self.volume = data & 0b1111;
+            }
+            1 => self.period = (self.period & 0x0F00) | data as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((data as u16 & 0b1111) << 8);
+                self.enabled = data & 0b1000_0000 != 0;
+                if !self.enabled {
+                    self.step = 15;
+                }
+            }
+        }
+    }
+
+    fn clock(&mut self, shift: u8) {
+        if !self.enabled {
+            return;
+        }
+        if self.timer == 0 {
+            self.timer = self.period >> shift;
+            self.step = self.step.wrapping_sub(1) & 0b1111;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.enabled && (self.digitized || self.step <= self.duty) {
+            self.volume
+        } else {
+            0
+        }
+    }
+}
+
+/// The VRC6's sawtooth channel, $B000-$B002
+#[derive(Debug, Default)]
+pub struct Sawtooth {
+    rate: u8,
+    enabled: bool,
+    period: u16,
+    timer: u16,
+    /// Counts the 14 timer clocks of one saw period, the accumulator grows on every second one
+    step: u8,
+    accumulator: u8,
+}
+
+impl Sawtooth {
+    fn write(&mut self, register: u16, data: u8) {
+        match register {
+            0 => self.rate = data & 0b11_1111,
+            1 => self.period = (self.period & 0x0F00) | data as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((data as u16 & 0b1111) << 8);
+                self.enabled = data & 0b1000_0000 != 0;
+                if !self.enabled {
+                    self.step = 0;
+                    self.accumulator = 0;
+                }
+            }
+        }
+    }
+
+    fn clock(&mut self, shift: u8) {
+        if !self.enabled {
+            return;
+        }
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.period >> shift;
+        self.step += 1;
+        if self.step == 14 {
+            self.step = 0;
+            self.accumulator = 0;
+        } else if self.step.is_multiple_of(2) {
+            self.accumulator = self.accumulator.wrapping_add(self.rate);
+        }
+    }
+
+    /// The top five bits of the accumulator
+    fn output(&self) -> u8 {
+        self.accumulator >> 3
+    }
+}
+
+/// Konami VRC6: two pulses and a sawtooth
+pub struct Vrc6 {
+    /// Mapper 26 boards wire A0 and A1 to the chip the other way around
+    swapped_lines: bool,
+    pub pulse1: Vrc6Pulse,
+    pub pulse2: Vrc6Pulse,
+    pub sawtooth: Sawtooth,
+    halt: bool,
+    /// Divides every period by 16 or 256, for tracker style pitch effects
+    frequency_shift: u8,
+}
+
+impl Vrc6 {
+    pub fn new(swapped_lines: bool) -> Self {
+        Self {
+            swapped_lines,
+            pulse1: Vrc6Pulse::default(),
+            pulse2: Vrc6Pulse::default(),
+            sawtooth: Sawtooth::default(),
+            halt: false,
+            frequency_shift: 0,
+        }
+    }
+}
+
+impl ExpansionAudio for Vrc6 {
+    fn write(&mut self, address: u16, data: u8) {
+        let register = if self.swapped_lines {
+            (address & 0b01) << 1 | (address & 0b10) >> 1
+        } else {
+            address & 0b11
+        };
+        match (address & 0xF000, register) {
+            (0x9000, 3) => {
+                self.halt = data & 0b001 != 0;
+                self.frequency_shift = if data & 0b100 != 0 {
+                    8
+                } else if data & 0b010 != 0 {
+                    4
+                } else {
+                    0
+                };
+            }
+            (0x9000, _) => self.pulse1.write(register, data),
+            (0xA000, 0..=2) => self.pulse2.write(register, data),
+            (0xB000, 0..=2) => self.sawtooth.write(register, data),
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.halt {
+            return;
+        }
+        self.pulse1.clock(self.frequency_shift);
+        self.pulse2.clock(self.frequency_shift);
+        self.sawtooth.clock(self.frequency_shift);
+    }
+
+    fn output(&self) -> f32 {
+        // The saw's five bits span twice the range of a pulse's four
+        let level = self.pulse1.output() + self.pulse2.output() + self.sawtooth.output();
+        level as f32 * VRC6_LEVEL * APU_PULSE_FULL / 15.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sawtooth_ramp() {
+        let mut vrc6 = Vrc6::new(false);
+        vrc6.write(0xB000, 42);
+        vrc6.write(0xB001, 0);
+        vrc6.write(0xB002, 0b1000_0000);
+
+        let mut outputs = vec![];
+        for _ in 0..14 {
+            vrc6.tick();
+            outputs.push(vrc6.sawtooth.output());
+        }
+        // Six additions of 42, then back to 0
+        assert_eq!(outputs, [0, 5, 5, 10, 10, 15, 15, 21, 21, 26, 26, 31, 31, 0]);
+    }
+
+    #[test]
+    fn test_mapper_26_swaps_registers() {
+        let mut vrc6 = Vrc6::new(true);
+        // $9001 on mapper 26 is the chip's $9002
+        vrc6.write(0x9001, 0b1000_0001);
+        assert!(vrc6.pulse1.enabled);
+        assert_eq!(vrc6.pulse1.period, 0x100);
+    }
+}
@@ -0,0 +1,379 @@
+use std::f32::consts::PI;
+
+use super::{ExpansionAudio, APU_PULSE_FULL, VRC7_LEVEL};
+
+/// The VRC7 makes one sample every 36 CPU cycles (3.58MHz / 72)
+const CYCLES_PER_SAMPLE: u8 = 36;
+const SAMPLE_RATE: f32 = 1_789_773.0 / CYCLES_PER_SAMPLE as f32;
+/// Attenuation at which an operator is considered silent
+const SILENT_DB: f32 = 48.0;
+
+/// The built-in instruments 1-15, 8 bytes each in the same layout as the custom one in $00-$07
+const PATCHES: [[u8; 8]; 15] = [
+    [0x03, 0x21, 0x05, 0x06, 0xE8, 0x81, 0x42, 0x27],
+    [0x13, 0x41, 0x14, 0x0D, 0xD8, 0xF6, 0x23, 0x12],
+    [0x11, 0x11, 0x08, 0x08, 0xFA, 0xB2, 0x20, 0x12],
+    [0x31, 0x61, 0x0C, 0x07, 0xA8, 0x64, 0x61, 0x27],
+    [0x32, 0x21, 0x1E, 0x06, 0xE1, 0x76, 0x01, 0x28],
+    [0x02, 0x01, 0x06, 0x00, 0xA3, 0xE2, 0xF4, 0xF4],
+    [0x21, 0x61, 0x1D, 0x07, 0x82, 0x81, 0x11, 0x07],
+    [0x23, 0x21, 0x22, 0x17, 0xA2, 0x72, 0x01, 0x17],
+    [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01],
+    [0xB5, 0x01, 0x0F, 0x0F, 0xA8, 0xA5, 0x51, 0x02],
+    [0x17, 0xC1, 0x24, 0x07, 0xF8, 0xF8, 0x22, 0x12],
+    [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16],
+    [0x01, 0x02, 0xD3, 0x05, 0xC9, 0x95, 0x03, 0x02],
+    [0x61, 0x63, 0x0C, 0x00, 0x94, 0xC0, 0x33, 0xF6],
+    [0x21, 0x72, 0x0D, 0x00, 0xC1, 0xD5, 0x56, 0x06],
+];
+
+/// Frequency multipliers selected by the low nibble of an operator's first patch byte
+const MULTIPLIERS: [f32; 16] = [0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 10.0, 12.0, 12.0, 15.0, 15.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeStage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+/// The parts of a patch that apply to one operator
+#[derive(Debug, Clone, Copy)]
+struct OperatorPatch {
+    tremolo: bool,
+    vibrato: bool,
+    /// Hold at the sustain level while the key is down, otherwise keep fading at the release rate
+    sustained: bool,
+    key_scale_rate: bool,
+    multiplier: f32,
+    /// Only output the positive half of the sine
+    rectified: bool,
+    attack_rate: u8,
+    decay_rate: u8,
+    sustain_level: u8,
+    release_rate: u8,
+}
+
+impl OperatorPatch {
+    fn new(patch: &[u8; 8], carrier: bool) -> Self {
+        let index = carrier as usize;
+        let flags = patch[index];
+        let rates = patch[4 + index];
+        let levels = patch[6 + index];
+        Self {
+            tremolo: flags & 0b1000_0000 != 0,
+            vibrato: flags & 0b0100_0000 != 0,
+            sustained: flags & 0b0010_0000 != 0,
+            key_scale_rate: flags & 0b0001_0000 != 0,
+            multiplier: MULTIPLIERS[(flags & 0b1111) as usize],
+            rectified: patch[3] & if carrier { 0b1_0000 } else { 0b1000 } != 0,
+            attack_rate: rates >> 4,
+            decay_rate: rates & 0b1111,
+            sustain_level: levels >> 4,
+            release_rate: levels & 0b1111,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Operator {
+    /// Position in the sine, in cycles
+    phase: f32,
+    stage: EnvelopeStage,
+    /// Envelope attenuation in dB
+    attenuation: f32,
+    output: f32,
+}
+
+impl Default for Operator {
+    fn default() -> Self {
+        Self {
+            phase: 0.0,
+            stage: EnvelopeStage::Release,
+            attenuation: SILENT_DB,
+            output: 0.0,
+        }
+    }
+}
+
+impl Operator {
+    fn key_on(&mut self) {
+        self.stage = EnvelopeStage::Attack;
+        self.phase = 0.0;
+    }
+
+    fn key_off(&mut self) {
+        self.stage = EnvelopeStage::Release;
+    }
+
+    /// dB per sample for a 4 bit rate, each step doubling the speed like the real envelope generator. Rate 1
+    /// with no key scaling takes about 10 seconds to fade out.
+    fn decay_speed(rate: u8, key_scale: u8) -> f32 {
+        if rate == 0 {
+            return 0.0;
+        }
+        let effective = (rate as u32 * 4 + key_scale as u32).min(63) as f32;
+        SILENT_DB / (10.0 * SAMPLE_RATE) * 2f32.powf((effective - 4.0) / 4.0)
+    }
+
+    fn clock_envelope(&mut self, patch: &OperatorPatch, key_scale: u8, channel_sustain: bool) {
+        let key_scale = if patch.key_scale_rate { key_scale } else { key_scale >> 2 };
+        match self.stage {
+            EnvelopeStage::Attack => {
+                if patch.attack_rate == 15 {
+                    self.attenuation = 0.0;
+                } else {
+                    // The attack curve is exponential, roughly 4 times faster than a decay of the same rate
+                    let speed = Self::decay_speed(patch.attack_rate, key_scale) * 4.0;
+                    self.attenuation -= speed * (1.0 + self.attenuation / 8.0);
+                }
+                if self.attenuation <= 0.0 {
+                    self.attenuation = 0.0;
+                    self.stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay => {
+                self.attenuation += Self::decay_speed(patch.decay_rate, key_scale);
+                let sustain_db = patch.sustain_level as f32 * 3.0;
+                if self.attenuation >= sustain_db {
+                    self.attenuation = sustain_db;
+                    self.stage = EnvelopeStage::Sustain;
+                }
+            }
+            EnvelopeStage::Sustain => {
+                if !patch.sustained {
+                    self.attenuation += Self::decay_speed(patch.release_rate, key_scale);
+                }
+            }
+            EnvelopeStage::Release => {
+                let rate = if channel_sustain {
+                    5
+                } else if patch.sustained {
+                    patch.release_rate
+                } else {
+                    7
+                };
+                self.attenuation += Self::decay_speed(rate, key_scale);
+            }
+        }
+        self.attenuation = self.attenuation.min(SILENT_DB);
+    }
+
+    /// Advances the phase and returns the new output, with `modulation` in radians
+    fn clock(&mut self, increment: f32, modulation: f32, attenuation: f32, rectified: bool) -> f32 {
+        self.phase = (self.phase + increment).fract();
+        let total = self.attenuation + attenuation;
+        if total >= SILENT_DB {
+            self.output = 0.0;
+            return 0.0;
+        }
+        let sine = (2.0 * PI * self.phase + modulation).sin();
+        let sine = if rectified { sine.max(0.0) } else { sine };
+        self.output = sine * 10f32.powf(-total / 20.0);
+        self.output
+    }
+}
+
+#[derive(Debug, Default)]
+struct Channel {
+    /// 9 bit frequency number
+    fnum: u16,
+    /// 3 bit octave
+    block: u8,
+    key_on: bool,
+    sustain: bool,
+    instrument: u8,
+    /// 3dB per step
+    volume: u8,
+    modulator: Operator,
+    carrier: Operator,
+    /// The modulator's last two outputs, for its self feedback
+    feedback: [f32; 2],
+}
+
+/// Konami VRC7: six channels of two operator FM synthesis, a cut down YM2413 with its own instrument ROM
+pub struct Vrc7 {
+    /// $9010 register select
+    selected: u8,
+    custom_patch: [u8; 8],
+    channels: [Channel; 6],
+    /// $E000 bit 6 holds the chip in reset and silences it
+    silenced: bool,
+    cycle: u8,
+    /// Shared low frequency oscillators for tremolo (3.7Hz) and vibrato (6.4Hz), in cycles
+    tremolo_phase: f32,
+    vibrato_phase: f32,
+    output: f32,
+}
+
+impl Vrc7 {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            custom_patch: [0; 8],
+            channels: Default::default(),
+            silenced: false,
+            cycle: 0,
+            tremolo_phase: 0.0,
+            vibrato_phase: 0.0,
+            output: 0.0,
+        }
+    }
+
+    fn patch(&self, instrument: u8) -> [u8; 8] {
+        match instrument {
+            0 => self.custom_patch,
+            _ => PATCHES[instrument as usize - 1],
+        }
+    }
+
+    fn write_register(&mut self, register: u8, data: u8) {
+        match register {
+            0x00..=0x07 => self.custom_patch[register as usize] = data,
+            0x10..=0x15 => {
+                let channel = &mut self.channels[register as usize - 0x10];
+                channel.fnum = (channel.fnum & 0x100) | data as u16;
+            }
+            0x20..=0x25 => {
+                let channel = &mut self.channels[register as usize - 0x20];
+                channel.fnum = (channel.fnum & 0xFF) | ((data as u16 & 1) << 8);
+                channel.block = (data >> 1) & 0b111;
+                channel.sustain = data & 0b10_0000 != 0;
+                let key_on = data & 0b1_0000 != 0;
+                if key_on && !channel.key_on {
+                    channel.modulator.key_on();
+                    channel.carrier.key_on();
+                } else if !key_on && channel.key_on {
+                    channel.modulator.key_off();
+                    channel.carrier.key_off();
+                }
+                channel.key_on = key_on;
+            }
+            0x30..=0x35 => {
+                let channel = &mut self.channels[register as usize - 0x30];
+                channel.instrument = data >> 4;
+                channel.volume = data & 0b1111;
+            }
+            _ => {}
+        }
+    }
+
+    fn generate_sample(&mut self) {
+        self.tremolo_phase = (self.tremolo_phase + 3.7 / SAMPLE_RATE).fract();
+        self.vibrato_phase = (self.vibrato_phase + 6.4 / SAMPLE_RATE).fract();
+        // Up to 4.8dB of tremolo, and vibrato of about 14 cents
+        let tremolo_db = (1.0 - (2.0 * PI * self.tremolo_phase).cos()) * 2.4;
+        let vibrato = 1.0 + (2.0 * PI * self.vibrato_phase).sin() * 0.004;
+
+        let mut sum = 0.0;
+        for index in 0..self.channels.len() {
+            let patch = self.patch(self.channels[index].instrument);
+            let modulator_patch = OperatorPatch::new(&patch, false);
+            let carrier_patch = OperatorPatch::new(&patch, true);
+            let channel = &mut self.channels[index];
+
+            // Hz = fnum * rate * 2^block / 2^19
+            let base = channel.fnum as f32 * 2f32.powi(channel.block as i32) / 524_288.0;
+            let key_scale = channel.block << 1 | (channel.fnum >> 8) as u8;
+
+            channel.modulator.clock_envelope(&modulator_patch, key_scale, channel.sustain);
+            channel.carrier.clock_envelope(&carrier_patch, key_scale, channel.sustain);
+
+            let increment = |patch: &OperatorPatch| base * patch.multiplier * if patch.vibrato { vibrato } else { 1.0 };
+            let tremolo = |patch: &OperatorPatch| if patch.tremolo { tremolo_db } else { 0.0 };
+
+            let feedback_level = patch[3] & 0b111;
+            let feedback = if feedback_level == 0 {
+                0.0
+            } else {
+                (channel.feedback[0] + channel.feedback[1]) / 2.0 * PI * 2f32.powi(feedback_level as i32 - 5)
+            };
+            let total_level = (patch[2] & 0b11_1111) as f32 * 0.75;
+            let modulator = channel.modulator.clock(
+                increment(&modulator_patch),
+                feedback,
+                total_level + tremolo(&modulator_patch),
+                modulator_patch.rectified,
+            );
+            channel.feedback = [modulator, channel.feedback[0]];
+
+            sum += channel.carrier.clock(
+                increment(&carrier_patch),
+                modulator * 4.0 * PI,
+                channel.volume as f32 * 3.0 + tremolo(&carrier_patch),
+                carrier_patch.rectified,
+            );
+        }
+        self.output = sum;
+    }
+}
+
+impl Default for Vrc7 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExpansionAudio for Vrc7 {
+    fn write(&mut self, address: u16, data: u8) {
+        match address {
+            0x9010 => self.selected = data,
+            0x9030 if !self.silenced => self.write_register(self.selected, data),
+            0xE000..=0xEFFF => {
+                self.silenced = data & 0b0100_0000 != 0;
+                if self.silenced {
+                    *self = Self { silenced: true, ..Self::new() };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.silenced {
+            return;
+        }
+        self.cycle += 1;
+        if self.cycle == CYCLES_PER_SAMPLE {
+            self.cycle = 0;
+            self.generate_sample();
+        }
+    }
+
+    fn output(&self) -> f32 {
+        self.output * VRC7_LEVEL * APU_PULSE_FULL
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(vrc7: &mut Vrc7, register: u8, data: u8) {
+        vrc7.write(0x9010, register);
+        vrc7.write(0x9030, data);
+    }
+
+    #[test]
+    fn test_key_on_makes_sound_and_key_off_fades() {
+        let mut vrc7 = Vrc7::new();
+        // Channel 0, instrument 3 (piano-ish), full volume, A4
+        write(&mut vrc7, 0x30, 0x30);
+        write(&mut vrc7, 0x10, 0xAC);
+        write(&mut vrc7, 0x20, 0b0001_1000 | 0b1_0000);
+
+        let mut peak = 0.0f32;
+        for _ in 0..36 * 2000 {
+            vrc7.tick();
+            peak = peak.max(vrc7.output().abs());
+        }
+        assert!(peak > 0.1 * APU_PULSE_FULL);
+
+        write(&mut vrc7, 0x20, 0b0001_1000);
+        for _ in 0..1_789_773 * 2 {
+            vrc7.tick();
+        }
+        assert!(vrc7.output().abs() < 0.01 * APU_PULSE_FULL);
+    }
+}
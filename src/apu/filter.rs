@@ -0,0 +1,81 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    HighPass,
+    LowPass,
+}
+
+/// A first order RC filter
+#[derive(Debug)]
+pub struct OnePoleFilter {
+    kind: FilterKind,
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl OnePoleFilter {
+    pub fn new(kind: FilterKind, cutoff: f32, sample_rate: u32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate as f32;
+        let alpha = match kind {
+            FilterKind::HighPass => rc / (rc + dt),
+            FilterKind::LowPass => dt / (rc + dt),
+        };
+
+        Self {
+            kind,
+            alpha,
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = match self.kind {
+            FilterKind::HighPass => self.alpha * (self.previous_output + input - self.previous_input),
+            FilterKind::LowPass => self.previous_output + self.alpha * (input - self.previous_output),
+        };
+        self.previous_input = input;
+        self.previous_output = output;
+        output
+    }
+}
+
+/// The filters between the 2A03's DAC and the NES's audio out: two high-passes at 90Hz and 440Hz and a low-pass
+/// at 14kHz
+pub struct OutputFilter {
+    filters: [OnePoleFilter; 3],
+}
+
+impl OutputFilter {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            filters: [
+                OnePoleFilter::new(FilterKind::HighPass, 90.0, sample_rate),
+                OnePoleFilter::new(FilterKind::HighPass, 440.0, sample_rate),
+                OnePoleFilter::new(FilterKind::LowPass, 14_000.0, sample_rate),
+            ],
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.filters.iter_mut().fold(input, |sample, filter| filter.process(sample))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_high_pass_removes_dc() {
+        let mut filter = OutputFilter::new(44_100);
+        let mut output = 0.0;
+        for _ in 0..44_100 {
+            output = filter.process(0.5);
+        }
+        assert!(output.abs() < 1e-3);
+    }
+}
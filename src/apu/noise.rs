@@ -0,0 +1,100 @@
+use super::{Envelope, LengthCounter};
+
+/// Timer periods in CPU cycles
+const PERIOD_TABLE: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+
+/// The pseudo-random noise channel, $400C-$400F
+#[derive(Debug)]
+pub struct Noise {
+    pub envelope: Envelope,
+    pub length_counter: LengthCounter,
+    /// Taps bit 6 instead of bit 1, giving a 93 step metallic loop instead of 32767 step noise
+    pub short_mode: bool,
+    shift_register: u16,
+    timer_period: u16,
+    timer: u16,
+}
+
+impl Noise {
+    pub fn new() -> Self {
+        Self {
+            envelope: Envelope::default(),
+            length_counter: LengthCounter::default(),
+            short_mode: false,
+            shift_register: 1,
+            timer_period: PERIOD_TABLE[0],
+            timer: 0,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        match address & 0b11 {
+            0 => {
+                self.envelope.write(data);
+                self.length_counter.halt = self.envelope.looping;
+            }
+            1 => {}
+            2 => {
+                self.short_mode = data & 0b1000_0000 != 0;
+                self.timer_period = PERIOD_TABLE[(data & 0b1111) as usize];
+            }
+            _ => {
+                self.length_counter.load(data);
+                self.envelope.start = true;
+            }
+        }
+    }
+
+    /// Clocked every other CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            // The table counts CPU cycles but the timer runs at half that
+            self.timer = self.timer_period / 2 - 1;
+            let tap = if self.short_mode { 6 } else { 1 };
+            let feedback = (self.shift_register ^ (self.shift_register >> tap)) & 1;
+            self.shift_register = (self.shift_register >> 1) | (feedback << 14);
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.shift_register & 1 != 0 || !self.length_counter.active() {
+            0
+        } else {
+            self.envelope.volume()
+        }
+    }
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Clocks the shift register until it returns to its starting value
+    fn lfsr_period(short_mode: bool) -> usize {
+        let mut noise = Noise::new();
+        noise.short_mode = short_mode;
+        noise.timer_period = 2;
+        let mut steps = 0;
+        loop {
+            noise.clock_timer();
+            steps += 1;
+            if noise.shift_register == 1 {
+                return steps;
+            }
+        }
+    }
+
+    #[test]
+    fn test_lfsr_periods() {
+        assert_eq!(lfsr_period(false), 32767);
+        assert_eq!(lfsr_period(true), 93);
+    }
+}
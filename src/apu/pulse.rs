@@ -0,0 +1,165 @@
+use super::{Envelope, LengthCounter};
+
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+/// Periodically bends the pitch of a pulse channel
+#[derive(Debug, Default)]
+pub struct Sweep {
+    pub enabled: bool,
+    pub period: u8,
+    pub negate: bool,
+    pub shift: u8,
+    reload: bool,
+    divider: u8,
+}
+
+/// One of the two square wave channels, $4000-$4003 and $4004-$4007
+#[derive(Debug)]
+pub struct Pulse {
+    /// Pulse 1 negates its sweep with one's complement, pulse 2 with two's complement
+    channel: u8,
+    /// MMC5's copies of the pulse channel lack the sweep unit, and with it the muting
+    sweep_unit: bool,
+    pub envelope: Envelope,
+    pub length_counter: LengthCounter,
+    pub sweep: Sweep,
+    duty: u8,
+    sequence_step: u8,
+    /// 11 bit timer reload value
+    pub timer_period: u16,
+    timer: u16,
+}
+
+impl Pulse {
+    pub fn new(channel: u8) -> Self {
+        Self {
+            channel,
+            sweep_unit: true,
+            envelope: Envelope::default(),
+            length_counter: LengthCounter::default(),
+            sweep: Sweep::default(),
+            duty: 0,
+            sequence_step: 0,
+            timer_period: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn without_sweep() -> Self {
+        Self {
+            sweep_unit: false,
+            ..Self::new(0)
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        match address & 0b11 {
+            0 => {
+                self.duty = data >> 6;
+                self.envelope.write(data);
+                self.length_counter.halt = self.envelope.looping;
+            }
+            1 => {
+                self.sweep.enabled = data & 0b1000_0000 != 0;
+                self.sweep.period = (data >> 4) & 0b111;
+                self.sweep.negate = data & 0b0000_1000 != 0;
+                self.sweep.shift = data & 0b111;
+                self.sweep.reload = true;
+            }
+            2 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            _ => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((data as u16 & 0b111) << 8);
+                self.length_counter.load(data);
+                self.sequence_step = 0;
+                self.envelope.start = true;
+            }
+        }
+    }
+
+    /// Clocked every other CPU cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            // The sequencer counts down
+            self.sequence_step = self.sequence_step.wrapping_sub(1) & 0b111;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked every half frame
+    pub fn clock_sweep(&mut self) {
+        if self.sweep.divider == 0 && self.sweep.enabled && self.sweep.shift > 0 && !self.muted() {
+            self.timer_period = self.sweep_target();
+        }
+        if self.sweep.divider == 0 || self.sweep.reload {
+            self.sweep.divider = self.sweep.period;
+            self.sweep.reload = false;
+        } else {
+            self.sweep.divider -= 1;
+        }
+    }
+
+    fn sweep_target(&self) -> u16 {
+        let change = self.timer_period >> self.sweep.shift;
+        if self.sweep.negate {
+            let change = if self.channel == 1 { change + 1 } else { change };
+            self.timer_period.saturating_sub(change)
+        } else {
+            self.timer_period + change
+        }
+    }
+
+    /// The sweep unit silences the channel whenever the period is too low or its target too high, even when the
+    /// sweep itself is disabled
+    fn muted(&self) -> bool {
+        self.sweep_unit && (self.timer_period < 8 || self.sweep_target() > 0x7FF)
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.muted() || !self.length_counter.active() || DUTY_TABLE[self.duty as usize][self.sequence_step as usize] == 0 {
+            0
+        } else {
+            self.envelope.volume()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sweep_negate_differs_between_channels() {
+        let mut pulse1 = Pulse::new(1);
+        let mut pulse2 = Pulse::new(2);
+        for pulse in [&mut pulse1, &mut pulse2] {
+            pulse.write_register(0x4001, 0b1000_1001);
+            pulse.timer_period = 0x100;
+        }
+
+        assert_eq!(pulse1.sweep_target(), 0x100 - 0x80 - 1);
+        assert_eq!(pulse2.sweep_target(), 0x100 - 0x80);
+    }
+
+    #[test]
+    fn test_sweep_target_overflow_mutes() {
+        let mut pulse = Pulse::new(1);
+        pulse.length_counter.set_enabled(true);
+        pulse.write_register(0x4000, 0b1101_1111);
+        pulse.write_register(0x4001, 0b0000_0111);
+        pulse.write_register(0x4002, 0xFF);
+        pulse.write_register(0x4003, 0b0000_1100);
+        assert!(!pulse.muted());
+
+        // Disabled, but a shift of 0 doubles the period past $7FF
+        pulse.write_register(0x4001, 0b0000_0000);
+        assert!(pulse.muted());
+        assert_eq!(pulse.output(), 0);
+    }
+}
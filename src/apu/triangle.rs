@@ -0,0 +1,68 @@
+use super::LengthCounter;
+
+const SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+/// The triangle wave channel, $4008-$400B
+#[derive(Debug, Default)]
+pub struct Triangle {
+    pub length_counter: LengthCounter,
+    /// Also the length counter halt flag
+    control: bool,
+    linear_counter_period: u8,
+    linear_counter: u8,
+    linear_counter_reload: bool,
+    sequence_step: u8,
+    pub timer_period: u16,
+    timer: u16,
+}
+
+impl Triangle {
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        match address & 0b11 {
+            0 => {
+                self.control = data & 0b1000_0000 != 0;
+                self.length_counter.halt = self.control;
+                self.linear_counter_period = data & 0b0111_1111;
+            }
+            1 => {}
+            2 => self.timer_period = (self.timer_period & 0x0700) | data as u16,
+            _ => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((data as u16 & 0b111) << 8);
+                self.length_counter.load(data);
+                self.linear_counter_reload = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle, the sequencer only moves while both counters are non-zero
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            if self.length_counter.active() && self.linear_counter > 0 {
+                self.sequence_step = (self.sequence_step + 1) & 0b1_1111;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked every quarter frame
+    pub fn clock_linear_counter(&mut self) {
+        if self.linear_counter_reload {
+            self.linear_counter = self.linear_counter_period;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+        if !self.control {
+            self.linear_counter_reload = false;
+        }
+    }
+
+    /// A stopped triangle keeps outputting whatever step it stopped on
+    pub fn output(&self) -> u8 {
+        SEQUENCE[self.sequence_step as usize]
+    }
+}
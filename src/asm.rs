@@ -0,0 +1,491 @@
+//! A small two pass 6502 assembler, so tests and examples can be written as source instead of bytes.
+//!
+//! The syntax follows ca65, which is also what the disassembler prints:
+//!
+//! ```text
+//! SCREEN = $0200      ; constants
+//!         .org $8000
+//! start:  LDX #<SCREEN
+//! loop:   STA SCREEN,X
+//!         INX
+//!         BNE loop
+//!         JMP (vector)
+//! vector: .word start
+//!         .byte "hi", 0, >start
+//! ```
+//!
+//! Operands are expressions as in `crate::expr`, of numbers (`$FF`, `%1010`, `255`, `'a'`), labels and `*` for
+//! the current address, with C's operators and unary `<`/`>` for the low and high byte. A leading `(` means
+//! indirect addressing. Zero page is used whenever the value is known to fit on the first pass, `a:` and `z:`
+//! force absolute or zero page.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use crate::{
+    cpu::CpuVariant,
+    expr::{Context, Expr, Parser, Token},
+    opcodes::AddressingMode,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    /// 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// The assembled bytes, which belong at `origin`, and where every label ended up
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub origin: u16,
+    pub bytes: Vec<u8>,
+    pub labels: HashMap<String, u16>,
+}
+
+/// Assembles `source` for `variant`, whose opcode table decides which instructions exist. `.org` may only move
+/// forward, any gap it leaves is filled with zeroes.
+pub fn assemble(source: &str, variant: CpuVariant) -> Result<Program, AsmError> {
+    let mut assembler = Assembler { variant, labels: HashMap::new(), pc: 0, statements: Vec::new() };
+    for (index, text) in source.lines().enumerate() {
+        assembler.first_pass(index + 1, text).map_err(|message| AsmError { line: index + 1, message })?;
+    }
+    assembler.second_pass()
+}
+
+/// Labels and `*`, the address of the line being assembled
+struct Scope<'a> {
+    labels: &'a HashMap<String, u16>,
+    pc: u16,
+}
+
+impl Context for Scope<'_> {
+    fn name(&self, name: &str) -> Result<i64, String> {
+        if name == "*" {
+            return Ok(self.pc as i64);
+        }
+        Ok(*self.labels.get(name).ok_or_else(|| format!("undefined label {name}"))? as i64)
+    }
+}
+
+fn evaluate(expr: &Expr, labels: &HashMap<String, u16>, pc: u16) -> Result<i64, String> {
+    expr.evaluate(&Scope { labels, pc })
+}
+
+/// True and consumed if the next token is the register `name`
+fn eat_register(parser: &mut Parser, name: &str) -> bool {
+    match parser.peek() {
+        Some(Token::Name(register)) if register.eq_ignore_ascii_case(name) => {
+            parser.next();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// An operand as written, before it is matched to an addressing mode
+enum Operand {
+    None,
+    Accumulator,
+    Immediate(Expr),
+    /// `$10`, `$1000,X`. The flag is Some(true) for `a:`, Some(false) for `z:`.
+    Direct { address: Expr, index: Option<char>, force_absolute: Option<bool> },
+    /// `($1000)`, `($10,X)`, `($10),Y`
+    Indirect { address: Expr, index: Option<char> },
+    /// `$10,label` for BBR and BBS
+    BitBranch { address: Expr, target: Expr },
+}
+
+fn parse_operand(parser: &mut Parser) -> Result<Operand, String> {
+    if parser.at_end() {
+        return Ok(Operand::None);
+    }
+    if parser.peek_at(1).is_none() && eat_register(parser, "A") {
+        return Ok(Operand::Accumulator);
+    }
+    if parser.eat("#") {
+        return Ok(Operand::Immediate(parser.expression()?));
+    }
+    if parser.eat("(") {
+        let address = parser.expression()?;
+        let index = if parser.eat(",") {
+            if !eat_register(parser, "X") {
+                return Err("expected X".to_string());
+            }
+            parser.expect(")")?;
+            Some('X')
+        } else {
+            parser.expect(")")?;
+            if parser.eat(",") {
+                if !eat_register(parser, "Y") {
+                    return Err("expected Y".to_string());
+                }
+                Some('Y')
+            } else {
+                None
+            }
+        };
+        return Ok(Operand::Indirect { address, index });
+    }
+
+    let mut force_absolute = None;
+    if let (Some(Token::Name(prefix)), Some(Token::Operator(":"))) = (parser.peek(), parser.peek_at(1)) {
+        force_absolute = match prefix.as_str() {
+            "a" | "A" => Some(true),
+            "z" | "Z" => Some(false),
+            _ => return Err(format!("unknown address size {prefix}:")),
+        };
+        parser.next();
+        parser.next();
+    }
+    let address = parser.expression()?;
+    if !parser.eat(",") {
+        return Ok(Operand::Direct { address, index: None, force_absolute });
+    }
+    if eat_register(parser, "X") {
+        Ok(Operand::Direct { address, index: Some('X'), force_absolute })
+    } else if eat_register(parser, "Y") {
+        Ok(Operand::Direct { address, index: Some('Y'), force_absolute })
+    } else {
+        Ok(Operand::BitBranch { address, target: parser.expression()? })
+    }
+}
+
+
+/// A line that produces bytes, with its address and size settled by the first pass
+struct Statement {
+    /// 1-based source line
+    line: usize,
+    address: u16,
+    kind: StatementKind,
+}
+
+enum StatementKind {
+    Instruction { opcode: u8, mode: AddressingMode, operands: Vec<Expr> },
+    Bytes(Vec<Expr>),
+    Words(Vec<Expr>),
+}
+
+struct Assembler {
+    variant: CpuVariant,
+    labels: HashMap<String, u16>,
+    /// Can be one past the end of memory after a line that fills it up to $FFFF
+    pc: usize,
+    statements: Vec<Statement>,
+}
+
+impl Assembler {
+    /// Every opcode called `mnemonic` with its addressing mode, the official one first where there are copies
+    fn opcodes(&self, mnemonic: &str) -> Vec<(u8, AddressingMode)> {
+        let mut found: Vec<(u8, AddressingMode)> = (0..=255u8)
+            .map(|byte| (byte, self.variant.opcodes()[byte as usize]))
+            .filter(|(_, opcode)| opcode.name().to_string().eq_ignore_ascii_case(mnemonic))
+            .map(|(byte, opcode)| (byte, opcode.addressing_mode()))
+            .collect();
+        // The unofficial one byte NOPs come before the real one
+        if let Some(official) = found.iter().position(|&(byte, _)| byte == 0xEA) {
+            found.swap(0, official);
+        }
+        found
+    }
+
+    fn address(&self) -> Result<u16, String> {
+        u16::try_from(self.pc).map_err(|_| "past the end of memory".to_string())
+    }
+
+    fn define(&mut self, name: String, value: u16) -> Result<(), String> {
+        if self.labels.insert(name.clone(), value).is_some() {
+            return Err(format!("{name} is already defined"));
+        }
+        Ok(())
+    }
+
+    /// A value the first pass needs right away, so it can't refer to anything defined further down
+    fn evaluate_now(&self, expr: &Expr) -> Result<u16, String> {
+        let value = evaluate(expr, &self.labels, self.address()?)?;
+        u16::try_from(value).map_err(|_| format!("{value} doesn't fit in 16 bits"))
+    }
+
+    /// Defines labels, works out the size of every line and picks the addressing modes
+    fn first_pass(&mut self, line: usize, text: &str) -> Result<(), String> {
+        let mut parser = Parser::new(text)?;
+
+        if let (Some(Token::Name(name)), Some(Token::Operator("="))) = (parser.peek(), parser.peek_at(1)) {
+            let name = name.clone();
+            parser.next();
+            parser.next();
+            let value = self.evaluate_now(&parser.expression()?)?;
+            parser.finish()?;
+            return self.define(name, value);
+        }
+        if let (Some(Token::Name(name)), Some(Token::Operator(":"))) = (parser.peek(), parser.peek_at(1)) {
+            let name = name.clone();
+            parser.next();
+            parser.next();
+            self.define(name, self.address()?)?;
+        }
+        let word = match parser.next() {
+            None => return Ok(()),
+            Some(Token::Name(word)) => word,
+            Some(token) => return Err(format!("expected an instruction, got '{token}'")),
+        };
+
+        let (kind, size) = match word.to_ascii_lowercase().as_str() {
+            ".org" => {
+                let address = self.evaluate_now(&parser.expression()?)?;
+                parser.finish()?;
+                if (address as usize) < self.pc && !self.statements.is_empty() {
+                    return Err(format!(".org ${address:04X} is behind ${:04X}", self.pc));
+                }
+                self.pc = address as usize;
+                return Ok(());
+            }
+            ".byte" => {
+                let mut items = Vec::new();
+                loop {
+                    if let Some(Token::String(text)) = parser.peek() {
+                        items.extend(text.bytes().map(|byte| Expr::Number(byte as i64)));
+                        parser.next();
+                    } else {
+                        items.push(parser.expression()?);
+                    }
+                    if !parser.eat(",") {
+                        break;
+                    }
+                }
+                let size = items.len();
+                (StatementKind::Bytes(items), size)
+            }
+            ".word" => {
+                let mut items = vec![parser.expression()?];
+                while parser.eat(",") {
+                    items.push(parser.expression()?);
+                }
+                let size = items.len() * 2;
+                (StatementKind::Words(items), size)
+            }
+            _ if word.starts_with('.') => return Err(format!("unknown directive {word}")),
+            _ => {
+                let operand = parse_operand(&mut parser)?;
+                let (opcode, mode, operands) = self.choose_opcode(&word, operand)?;
+                (StatementKind::Instruction { opcode, mode, operands }, 1 + mode.operand_length() as usize)
+            }
+        };
+        parser.finish()?;
+
+        let address = self.address()?;
+        if address as usize + size > 0x10000 {
+            return Err("runs past the end of memory".to_string());
+        }
+        self.pc += size;
+        self.statements.push(Statement { line, address, kind });
+        Ok(())
+    }
+
+    /// Picks the opcode for `mnemonic` with `operand`. Addresses that aren't known yet are assumed to need two
+    /// bytes.
+    fn choose_opcode(&self, mnemonic: &str, operand: Operand) -> Result<(u8, AddressingMode, Vec<Expr>), String> {
+        use AddressingMode::*;
+
+        let opcodes = self.opcodes(mnemonic);
+        if opcodes.is_empty() {
+            return Err(format!("unknown instruction {mnemonic}"));
+        }
+        let byte_for =
+            |wanted: AddressingMode| opcodes.iter().find(|&&(_, mode)| mode == wanted).map(|&(byte, _)| byte);
+        let pc = self.address()?;
+        let fits_zero_page =
+            |address: &Expr| evaluate(address, &self.labels, pc).is_ok_and(|value| (0..0x100).contains(&value));
+
+        let (candidates, operands): (&[AddressingMode], _) = match operand {
+            Operand::None => (&[Implied, Accumulator], vec![]),
+            Operand::Accumulator => (&[Accumulator], vec![]),
+            Operand::Immediate(value) => (&[Immediate], vec![value]),
+            Operand::Direct { address, index, force_absolute } => {
+                let zero_page = force_absolute.map_or_else(|| fits_zero_page(&address), |absolute| !absolute);
+                let candidates: &[AddressingMode] = match (index, zero_page) {
+                    (None, _) if byte_for(Relative).is_some() => &[Relative],
+                    (None, true) => &[ZeroPage, Absolute],
+                    (None, false) => &[Absolute],
+                    (Some('X'), true) => &[ZeroPageX, AbsoluteX],
+                    (Some('X'), false) => &[AbsoluteX],
+                    (_, true) => &[ZeroPageY, AbsoluteY],
+                    (_, false) => &[AbsoluteY],
+                };
+                // z: means zero page or nothing
+                let candidates = if force_absolute == Some(false) { &candidates[..1] } else { candidates };
+                (candidates, vec![address])
+            }
+            Operand::Indirect { address, index: None } => (&[Indirect, ZeroPageIndirect], vec![address]),
+            Operand::Indirect { address, index: Some('X') } => {
+                (&[IndexedIndirectX, AbsoluteIndexedIndirect], vec![address])
+            }
+            Operand::Indirect { address, index: _ } => (&[IndirectIndexedY], vec![address]),
+            Operand::BitBranch { address, target } => (&[ZeroPageRelative], vec![address, target]),
+        };
+        let &mode = candidates
+            .iter()
+            .find(|&&mode| byte_for(mode).is_some())
+            .ok_or_else(|| format!("{} has no {} addressing", mnemonic.to_ascii_uppercase(), candidates[0]))?;
+        Ok((byte_for(mode).unwrap(), mode, operands))
+    }
+
+    /// Evaluates every operand now that all labels are known and lays out the bytes
+    fn second_pass(self) -> Result<Program, AsmError> {
+        let origin = self.statements.first().map_or(self.pc as u16, |statement| statement.address);
+        let mut bytes = Vec::new();
+        for statement in &self.statements {
+            // .org only moves forward, so this is at or past the end
+            bytes.resize((statement.address - origin) as usize, 0);
+            self.emit(statement, &mut bytes)
+                .map_err(|message| AsmError { line: statement.line, message })?;
+        }
+        Ok(Program { origin, bytes, labels: self.labels })
+    }
+
+    fn emit(&self, statement: &Statement, bytes: &mut Vec<u8>) -> Result<(), String> {
+        use AddressingMode::*;
+
+        let evaluate = |expr: &Expr| evaluate(expr, &self.labels, statement.address);
+        match &statement.kind {
+            StatementKind::Bytes(items) => {
+                for item in items {
+                    bytes.push(to_byte(evaluate(item)?)?);
+                }
+            }
+            StatementKind::Words(items) => {
+                for item in items {
+                    bytes.extend_from_slice(&to_word(evaluate(item)?)?.to_le_bytes());
+                }
+            }
+            StatementKind::Instruction { opcode, mode, operands } => {
+                bytes.push(*opcode);
+                let next = statement.address as i64 + 1 + mode.operand_length() as i64;
+                match mode {
+                    Implied | Accumulator => {}
+                    Immediate => bytes.push(to_byte(evaluate(&operands[0])?)?),
+                    Relative => bytes.push(branch_offset(evaluate(&operands[0])?, next)?),
+                    ZeroPageRelative => {
+                        bytes.push(to_zero_page(evaluate(&operands[0])?)?);
+                        bytes.push(branch_offset(evaluate(&operands[1])?, next)?);
+                    }
+                    _ if mode.operand_length() == 1 => bytes.push(to_zero_page(evaluate(&operands[0])?)?),
+                    _ => bytes.extend_from_slice(&to_word(evaluate(&operands[0])?)?.to_le_bytes()),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bytes may be written signed or unsigned
+fn to_byte(value: i64) -> Result<u8, String> {
+    if (-0x80..0x100).contains(&value) {
+        Ok(value as u8)
+    } else {
+        Err(format!("{value} doesn't fit in a byte"))
+    }
+}
+
+fn to_zero_page(value: i64) -> Result<u8, String> {
+    u8::try_from(value).map_err(|_| format!("${value:X} isn't a zero page address"))
+}
+
+fn to_word(value: i64) -> Result<u16, String> {
+    if (-0x8000..0x10000).contains(&value) {
+        Ok(value as u16)
+    } else {
+        Err(format!("{value} doesn't fit in a word"))
+    }
+}
+
+fn branch_offset(target: i64, next: i64) -> Result<u8, String> {
+    i8::try_from(target - next).map(|offset| offset as u8).map_err(|_| format!("branch to ${target:04X} is too far"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::disasm::Instruction;
+
+    fn bytes(source: &str) -> Vec<u8> {
+        assemble(source, CpuVariant::Nmos6502).unwrap().bytes
+    }
+
+    fn error(source: &str) -> AsmError {
+        assemble(source, CpuVariant::Nmos6502).unwrap_err()
+    }
+
+    #[test]
+    fn test_labels_and_directives() {
+        let source = "
+            SCREEN = $0200      ; constants
+                    .org $8000
+            start:  LDX #<SCREEN
+            loop:   STA SCREEN,X
+                    INX
+                    BNE loop
+                    JMP (vector)
+            vector: .word start
+                    .byte \"hi\", 0, >start
+        ";
+        let program = assemble(source, CpuVariant::Nmos6502).unwrap();
+        assert_eq!(program.origin, 0x8000);
+        #[rustfmt::skip]
+        assert_eq!(program.bytes, [
+            0xA2, 0x00, 0x9D, 0x00, 0x02, 0xE8, 0xD0, 0xFA, 0x6C, 0x0B, 0x80, 0x00, 0x80, 0x68, 0x69, 0x00, 0x80,
+        ]);
+        assert_eq!(program.labels["vector"], 0x800B);
+    }
+
+    #[test]
+    fn test_expressions_and_sizes() {
+        assert_eq!(bytes("LDA #(1 + 2) * 3 | %10000000 & ~$7F"), [0xA9, 0x09 | 0x80]);
+        assert_eq!(bytes("LDA #'A' + 1\nLDX #-1\nLDY #1 << 4 >> 2"), [0xA9, 0x42, 0xA2, 0xFF, 0xA0, 0x04]);
+        assert_eq!(bytes("LDA #1 < 2 && !(3 != 3)"), [0xA9, 0x01]);
+        // Not known on the first pass, so absolute, and zero page once it is
+        assert_eq!(bytes("LDA later\nlater = $10\nLDA later"), [0xAD, 0x10, 0x00, 0xA5, 0x10]);
+        assert_eq!(bytes("LDA a:$10\nLDX z:$10,Y\nASL\nASL A\nNOP"), [0xAD, 0x10, 0x00, 0xB6, 0x10, 0x0A, 0x0A, 0xEA]);
+        // No zero page,Y for LDA, and .org fills the gap
+        assert_eq!(bytes(".org $10\nLDA $10,Y\n.org $15\nJMP *"), [0xB9, 0x10, 0x00, 0x00, 0x00, 0x4C, 0x15, 0x00]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(error("LDA #1\nFOO $10"), AsmError { line: 2, message: "unknown instruction FOO".to_string() });
+        assert_eq!(error("LDA missing").message, "undefined label missing");
+        assert_eq!(error("STA #$10").message, "STA has no Immediate addressing");
+        assert_eq!(error(".org $8000\n\nBNE $8100").line, 3);
+        assert_eq!(error("a: NOP\na: NOP").message, "a is already defined");
+        assert_eq!(error(".org $FFFF\n.word 0").message, "runs past the end of memory");
+        assert!(assemble(".org $FFFA\n.word 1, 2, 3", CpuVariant::Nmos6502).is_ok());
+        assert_eq!(error(".byte $FFFFFFFF * $FFFFFFFF").message, "overflow");
+        assert_eq!(error(".byte 1 << 64").message, "can't shift by 64");
+        assert_eq!(error(".byte 1 >> -1").message, "can't shift by -1");
+    }
+
+    /// Everything the disassembler prints assembles back to the same instruction
+    #[test]
+    fn test_disassembler_round_trip() {
+        for variant in [CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+            for byte in 0..=255 {
+                let instruction = Instruction::decode(&[byte, 0x12, 0x34], 0x8000, variant).unwrap();
+                let program = assemble(&format!(".org $8000\n{instruction}"), variant)
+                    .unwrap_or_else(|error| panic!("{instruction}: {error}"));
+                let again = Instruction::decode(&program.bytes, 0x8000, variant).unwrap();
+                assert_eq!(again.to_string(), instruction.to_string());
+                assert_eq!(again.length(), instruction.length());
+            }
+        }
+    }
+}
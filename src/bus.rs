@@ -0,0 +1,379 @@
+use crate::{
+    apu::{expansion, Apu},
+    cartridge::{Rom, RomError},
+    controller::{
+        multitap::{Multitap, MultitapKind},
+        InputDevice, Joypad, DEVICE_BITS,
+    },
+    ppu::{Mirroring, Ppu},
+    watch::Watch,
+};
+
+/// Everything the CPU can see through its address and data pins.
+///
+/// Every call to `mem_read` or `mem_write` is exactly one CPU cycle, so an implementation can use them to keep
+/// other chips in step with the CPU. The CPU accesses the bus on every cycle, including the dummy reads and
+/// writes of cycles it spends on internal work.
+pub trait Bus {
+    fn mem_read(&mut self, address: u16) -> u8;
+    fn mem_write(&mut self, address: u16, data: u8);
+    /// Called before every CPU read cycle, the only kind of cycle DMA can halt the CPU on. Returns true after
+    /// running one stolen cycle, which the CPU counts and then asks again. A halted 6502 keeps putting the
+    /// address of its read on the bus, so implementations should read `address` again on cycles where the DMA
+    /// unit isn't using the bus itself.
+    fn dma_cycle(&mut self, _address: u16) -> bool {
+        false
+    }
+    /// Level of the /NMI input, true when something is pulling it low
+    fn nmi_line(&self) -> bool {
+        false
+    }
+    /// Level of the /IRQ input, true when any device is pulling it low
+    fn irq_line(&self) -> bool {
+        false
+    }
+    /// What's at `address` without reading it, so no time passes and nothing changes. None where finding out
+    /// would have side effects, like most I/O registers. For debuggers.
+    fn peek(&self, _address: u16) -> Option<u8> {
+        None
+    }
+    /// Changes memory at `address` outside of any CPU cycle. Buses that can't do better write normally.
+    fn poke(&mut self, address: u16, data: u8) {
+        self.mem_write(address, data);
+    }
+    /// The watch of the PPU's address space, for buses that have one
+    fn ppu_watch(&mut self) -> Option<&mut Watch> {
+        None
+    }
+}
+
+/// 64KiB of plain RAM with nothing else attached, used for running bare 6502 programs
+pub struct FlatRam {
+    memory: Box<[u8; 0x10000]>,
+}
+
+impl FlatRam {
+    pub fn new() -> Self {
+        Self {
+            memory: Box::new([0; 0x10000]),
+        }
+    }
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        let start = address as usize;
+        self.memory[start..(start + data.len())].copy_from_slice(data);
+    }
+}
+
+impl Default for FlatRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for FlatRam {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+    fn mem_write(&mut self, address: u16, data: u8) {
+        self.memory[address as usize] = data;
+    }
+    fn peek(&self, address: u16) -> Option<u8> {
+        Some(self.memory[address as usize])
+    }
+}
+
+//  _______________ $10000  _______________
+// | PRG-ROM       |       |               |
+// | Upper Bank    |       |               |
+// |_ _ _ _ _ _ _ _| $C000 | PRG-ROM       |
+// | PRG-ROM       |       |               |
+// | Lower Bank    |       |               |
+// |_______________| $8000 |_______________|
+// | SRAM          |       | SRAM          |
+// |_______________| $6000 |_______________|
+// | Expansion ROM |       | Expansion ROM |
+// |_______________| $4020 |_______________|
+// | I/O Registers |       |               |
+// |_ _ _ _ _ _ _ _| $4000 |               |
+// | Mirrors       |       | I/O Registers |
+// | $2000-$2007   |       |               |
+// |_ _ _ _ _ _ _ _| $2008 |               |
+// | I/O Registers |       |               |
+// |_______________| $2000 |_______________|
+// | Mirrors       |       |               |
+// | $0000-$07FF   |       |               |
+// |_ _ _ _ _ _ _ _| $0800 |               |
+// | RAM           |       | RAM           |
+// |_ _ _ _ _ _ _ _| $0200 |               |
+// | Stack         |       |               |
+// |_ _ _ _ _ _ _ _| $0100 |               |
+// | Zero Page     |       |               |
+// |_______________| $0000 |_______________|
+const RAM: u16 = 0x0000;
+const RAM_MIRRORS_END: u16 = 0x1FFF;
+const PPU_REGISTERS: u16 = 0x2000;
+const PPU_REGISTERS_MIRRORS_END: u16 = 0x3FFF;
+const APU_REGISTERS: u16 = 0x4000;
+const APU_REGISTERS_END: u16 = 0x4013;
+const OAM_DMA: u16 = 0x4014;
+const APU_STATUS: u16 = 0x4015;
+const JOYPAD_1: u16 = 0x4016;
+/// Also the joypad 2 port when read
+const APU_FRAME_COUNTER: u16 = 0x4017;
+const EXPANSION_ROM: u16 = 0x4020;
+const EXPANSION_ROM_END: u16 = 0x5FFF;
+const PRG_RAM: u16 = 0x6000;
+const PRG_RAM_END: u16 = 0x7FFF;
+const PRG_ROM: u16 = 0x8000;
+
+/// A copy of a page of CPU memory into OAM, started by writing the page number to $4014
+struct OamDma {
+    page: u8,
+    /// Next byte of the page to copy
+    index: u16,
+    /// Read on a get cycle, written to $2004 on the next put cycle
+    data: Option<u8>,
+    /// The first cycle only halts the CPU
+    halted: bool,
+}
+
+/// The NES CPU address space
+pub struct NesBus {
+    cpu_ram: [u8; 0x800],
+    prg_ram: [u8; 0x2000],
+    prg_rom: Vec<u8>,
+    pub ppu: Ppu,
+    pub apu: Apu,
+    /// Cycles the current DMC DMA has halted the CPU for so far
+    dmc_dma_cycles: u8,
+    oam_dma: Option<OamDma>,
+    /// Controller ports 1 and 2
+    ports: [Option<Box<dyn InputDevice>>; 2],
+    /// The last value driven onto the data bus, what undriven bits read as
+    data_bus: u8,
+}
+
+impl NesBus {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        Self {
+            cpu_ram: [0; 0x800],
+            prg_ram: [0; 0x2000],
+            prg_rom,
+            ppu: Ppu::new(chr_rom, mirroring),
+            apu: Apu::new(),
+            dmc_dma_cycles: 0,
+            oam_dma: None,
+            ports: [Some(Box::new(Joypad::new())), Some(Box::new(Joypad::new()))],
+            data_bus: 0,
+        }
+    }
+    /// Only NROM boards are supported so far. Boards with a sound chip get it fitted here once their mapper is.
+    pub fn from_rom(rom: Rom) -> Result<Self, RomError> {
+        if rom.mapper != 0 {
+            return Err(RomError::UnsupportedMapper(rom.mapper));
+        }
+        let mut bus = Self::new(rom.prg_rom, rom.chr_rom, rom.mirroring);
+        bus.apu.expansion = expansion::for_mapper(rom.mapper);
+        Ok(bus)
+    }
+    /// The cartridge RAM at $6000-$7FFF, read without touching the bus
+    pub fn prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+    fn read_prg_rom(&self, address: u16) -> u8 {
+        if self.prg_rom.is_empty() {
+            return self.data_bus;
+        }
+        // A 16KiB NROM-128 board mirrors its only bank into $C000-$FFFF
+        let offset = (address - PRG_ROM) as usize % self.prg_rom.len();
+        self.prg_rom[offset]
+    }
+    /// Plugs `device` into controller port `port` (0 or 1), or leaves the port empty for None. Both ports start
+    /// out with a standard joypad.
+    pub fn connect(&mut self, port: usize, device: Option<Box<dyn InputDevice>>) {
+        self.ports[port] = device;
+    }
+    /// Unplugs whatever is in `port` and hands it back
+    pub fn disconnect(&mut self, port: usize) -> Option<Box<dyn InputDevice>> {
+        self.ports[port].take()
+    }
+    /// The device in `port` if there is one and it is a `T`
+    pub fn device_mut<T: InputDevice>(&mut self, port: usize) -> Option<&mut T> {
+        self.ports[port].as_mut()?.as_any_mut().downcast_mut()
+    }
+    pub fn joypad_mut(&mut self, port: usize) -> Option<&mut Joypad> {
+        self.device_mut(port)
+    }
+    /// Plugs a four player adapter into both ports, replacing whatever was there
+    pub fn connect_multitap(&mut self, kind: MultitapKind) {
+        for port in 0..2 {
+            self.connect(port, Some(Box::new(Multitap::new(kind, port))));
+        }
+    }
+    /// The joypad of `player` (0-3), whether it's plugged straight into a port or into a four player adapter
+    pub fn player_mut(&mut self, player: usize) -> Option<&mut Joypad> {
+        let port = player % 2;
+        if self.device_mut::<Multitap>(port).is_some() {
+            return self.device_mut::<Multitap>(port).map(|multitap| &mut multitap.joypads[player / 2]);
+        }
+        if player < 2 {
+            self.joypad_mut(port)
+        } else {
+            None
+        }
+    }
+    fn read_port(&mut self, port: usize) -> u8 {
+        let bits = self.ports[port].as_mut().map_or(0, |device| device.read(&self.ppu) & DEVICE_BITS);
+        bits | (self.data_bus & !DEVICE_BITS)
+    }
+    fn write_ports(&mut self, data: u8) {
+        for device in self.ports.iter_mut().flatten() {
+            device.write(data);
+        }
+    }
+    /// One cycle of OAM DMA, or of the halted CPU repeating its read when there is none. OAM DMA reads on get
+    /// cycles and writes on put cycles, so it takes 513 or 514 cycles depending on where it starts.
+    fn oam_dma_cycle(&mut self, address: u16, get_cycle: bool) {
+        let Some(mut dma) = self.oam_dma.take() else {
+            // A halted 6502 keeps reading, so $2007 and the joypad registers see the read more than once
+            self.data_bus = self.read(address);
+            return;
+        };
+
+        match dma.data {
+            Some(data) if !get_cycle => {
+                self.data_bus = data;
+                self.ppu.write_register(0x2004, data);
+                dma.data = None;
+                dma.index += 1;
+            }
+            None if dma.halted && get_cycle => {
+                let data = self.read(u16::from_le_bytes([dma.index as u8, dma.page]));
+                self.data_bus = data;
+                dma.data = Some(data);
+            }
+            // Halt and alignment cycles
+            _ => {
+                dma.halted = true;
+                self.data_bus = self.read(address);
+            }
+        }
+
+        if dma.index < 256 {
+            self.oam_dma = Some(dma);
+        }
+    }
+    /// Clocks the PPU up to the point in a CPU cycle where the data bus is sampled
+    fn begin_cycle(&mut self) {
+        self.ppu.tick();
+        self.ppu.tick();
+    }
+    fn end_cycle(&mut self) {
+        self.ppu.tick();
+        self.apu.tick();
+    }
+    fn read(&mut self, address: u16) -> u8 {
+        match address {
+            RAM..=RAM_MIRRORS_END => self.cpu_ram[(address & 0x07FF) as usize],
+            PPU_REGISTERS..=PPU_REGISTERS_MIRRORS_END => self.ppu.read_register(address),
+            // Bit 5 isn't driven
+            APU_STATUS => self.apu.read_status() | (self.data_bus & 0b0010_0000),
+            JOYPAD_1 => self.read_port(0),
+            APU_FRAME_COUNTER => self.read_port(1),
+            EXPANSION_ROM..=EXPANSION_ROM_END => self.apu.read_expansion(address).unwrap_or(self.data_bus),
+            PRG_RAM..=PRG_RAM_END => self.prg_ram[(address - PRG_RAM) as usize],
+            PRG_ROM..=0xFFFF => self.read_prg_rom(address),
+            // Nothing answers, the bus still holds the last value on it, usually the high byte of the address
+            _ => self.data_bus,
+        }
+    }
+    fn write(&mut self, address: u16, data: u8) {
+        match address {
+            RAM..=RAM_MIRRORS_END => self.cpu_ram[(address & 0x07FF) as usize] = data,
+            PPU_REGISTERS..=PPU_REGISTERS_MIRRORS_END => self.ppu.write_register(address, data),
+            APU_REGISTERS..=APU_REGISTERS_END | APU_STATUS | APU_FRAME_COUNTER => {
+                self.apu.write_register(address, data)
+            }
+            OAM_DMA => {
+                self.oam_dma = Some(OamDma {
+                    page: data,
+                    index: 0,
+                    data: None,
+                    halted: false,
+                })
+            }
+            JOYPAD_1 => self.write_ports(data),
+            PRG_RAM..=PRG_RAM_END => self.prg_ram[(address - PRG_RAM) as usize] = data,
+            _ => {}
+        }
+        // Cartridge sound chips decode their registers out of the same writes the mapper sees
+        if address >= EXPANSION_ROM {
+            self.apu.write_expansion(address, data);
+        }
+    }
+}
+
+impl Bus for NesBus {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        // The CPU samples the data bus late in the cycle, two of its three PPU dots have already happened
+        self.begin_cycle();
+        let data = self.read(address);
+        // $4015 is inside the 2A03, reading it doesn't drive the external data bus
+        if address != APU_STATUS {
+            self.data_bus = data;
+        }
+        self.end_cycle();
+        data
+    }
+    fn mem_write(&mut self, address: u16, data: u8) {
+        self.begin_cycle();
+        self.data_bus = data;
+        self.write(address, data);
+        self.end_cycle();
+    }
+    fn dma_cycle(&mut self, address: u16) -> bool {
+        let dmc_request = self.apu.dmc.dma_request();
+        if dmc_request.is_none() && self.oam_dma.is_none() {
+            return false;
+        }
+
+        self.begin_cycle();
+        let get_cycle = self.apu.get_cycle();
+        // The DMC gets a halt cycle and a dummy cycle, then waits for an APU get cycle to do its own read. It
+        // takes that get cycle over from a running OAM DMA
+        if let Some(sample_address) = dmc_request.filter(|_| self.dmc_dma_cycles >= 2 && get_cycle) {
+            let data = self.read(sample_address);
+            self.data_bus = data;
+            self.apu.dmc.load_sample_byte(data);
+            self.dmc_dma_cycles = 0;
+        } else {
+            if dmc_request.is_some() {
+                self.dmc_dma_cycles += 1;
+            }
+            self.oam_dma_cycle(address, get_cycle);
+        }
+        self.end_cycle();
+        true
+    }
+    fn nmi_line(&self) -> bool {
+        self.ppu.nmi_line()
+    }
+    fn irq_line(&self) -> bool {
+        self.apu.irq_line()
+    }
+    fn peek(&self, address: u16) -> Option<u8> {
+        match address {
+            RAM..=RAM_MIRRORS_END => Some(self.cpu_ram[(address & 0x07FF) as usize]),
+            PRG_RAM..=PRG_RAM_END => Some(self.prg_ram[(address - PRG_RAM) as usize]),
+            PRG_ROM..=0xFFFF => Some(self.read_prg_rom(address)),
+            _ => None,
+        }
+    }
+    fn poke(&mut self, address: u16, data: u8) {
+        // Registers still see the write, but the PPU and APU don't move
+        self.write(address, data);
+    }
+    fn ppu_watch(&mut self) -> Option<&mut Watch> {
+        Some(&mut self.ppu.watch)
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::{self, Display};
+
+use crate::ppu::Mirroring;
+
+const NES_TAG: [u8; 4] = [b'N', b'E', b'S', 0x1A];
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+const PRG_ROM_PAGE_SIZE: usize = 0x4000;
+const CHR_ROM_PAGE_SIZE: usize = 0x2000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RomError {
+    /// The file does not start with "NES\x1A"
+    NotINes,
+    /// The header promises more PRG or CHR data than the file has
+    Truncated { expected: usize, actual: usize },
+    UnsupportedMapper(u8),
+}
+
+impl Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RomError::NotINes => write!(f, "not an iNES file"),
+            RomError::Truncated { expected, actual } => {
+                write!(f, "file is {actual} bytes but the header describes {expected}")
+            }
+            RomError::UnsupportedMapper(mapper) => write!(f, "mapper {mapper} is not supported"),
+        }
+    }
+}
+
+impl std::error::Error for RomError {}
+
+/// The contents of an iNES (.nes) file
+#[derive(Debug, Clone)]
+pub struct Rom {
+    pub prg_rom: Vec<u8>,
+    /// Empty when the board has CHR-RAM instead
+    pub chr_rom: Vec<u8>,
+    pub mapper: u8,
+    pub mirroring: Mirroring,
+    /// PRG-RAM at $6000-$7FFF is battery backed
+    pub battery: bool,
+}
+
+impl Rom {
+    pub fn new(raw: &[u8]) -> Result<Rom, RomError> {
+        if raw.len() < HEADER_SIZE || raw[0..4] != NES_TAG {
+            return Err(RomError::NotINes);
+        }
+
+        let prg_rom_size = raw[4] as usize * PRG_ROM_PAGE_SIZE;
+        let chr_rom_size = raw[5] as usize * CHR_ROM_PAGE_SIZE;
+        let flags_6 = raw[6];
+        let flags_7 = raw[7];
+
+        let mapper = (flags_7 & 0b1111_0000) | (flags_6 >> 4);
+        let mirroring = if flags_6 & 0b1000 != 0 {
+            Mirroring::FourScreen
+        } else if flags_6 & 0b1 != 0 {
+            Mirroring::Vertical
+        } else {
+            Mirroring::Horizontal
+        };
+        let battery = flags_6 & 0b10 != 0;
+        let has_trainer = flags_6 & 0b100 != 0;
+
+        let prg_rom_start = HEADER_SIZE + if has_trainer { TRAINER_SIZE } else { 0 };
+        let chr_rom_start = prg_rom_start + prg_rom_size;
+        let expected = chr_rom_start + chr_rom_size;
+        if raw.len() < expected {
+            return Err(RomError::Truncated { expected, actual: raw.len() });
+        }
+
+        Ok(Rom {
+            prg_rom: raw[prg_rom_start..chr_rom_start].to_vec(),
+            chr_rom: raw[chr_rom_start..expected].to_vec(),
+            mapper,
+            mirroring,
+            battery,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_rom::TestRom;
+
+    #[test]
+    fn test_parse_header() {
+        let rom = Rom::new(&TestRom::new(2, 1).mapper(1).mirroring(Mirroring::Vertical).ines()).unwrap();
+        assert_eq!(rom.prg_rom.len(), 0x8000);
+        assert_eq!(rom.chr_rom.len(), 0x2000);
+        assert_eq!(rom.mapper, 1);
+        assert_eq!(rom.mirroring, Mirroring::Vertical);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut raw = TestRom::new(1, 1).ines();
+        raw.truncate(100);
+        assert!(matches!(Rom::new(&raw), Err(RomError::Truncated { .. })));
+        assert_eq!(Rom::new(b"NOPE").unwrap_err(), RomError::NotINes);
+    }
+}
@@ -0,0 +1,197 @@
+//! The whole console, what a frontend drives
+
+use crate::{
+    bus::NesBus,
+    cartridge::{Rom, RomError},
+    controller::{InputDevice, Joypad},
+    cpu::{Cpu, Jam},
+    ppu::palette::SYSTEM_PALETTE,
+};
+
+/// A NES with a cartridge inserted.
+///
+/// The CPU owns the bus, which owns the PPU, APU, cartridge memory and controller ports, so everything can be
+/// reached through `cpu` when the methods here aren't enough.
+pub struct Console {
+    pub cpu: Cpu<NesBus>,
+    /// Kept for power cycling
+    rom: Rom,
+}
+
+impl Console {
+    /// Inserts `rom` and powers on
+    pub fn new(rom: Rom) -> Result<Self, RomError> {
+        let mut cpu = Cpu::with_bus(NesBus::from_rom(rom.clone())?);
+        cpu.reset();
+        Ok(Self { cpu, rom })
+    }
+
+    /// Inserts the iNES image `bytes` and powers on
+    pub fn from_rom_bytes(bytes: &[u8]) -> Result<Self, RomError> {
+        Self::new(Rom::new(bytes)?)
+    }
+
+    /// Presses the reset button. RAM and the picture survive, the CPU restarts from the reset vector.
+    pub fn reset(&mut self) {
+        self.cpu.bus.ppu.reset();
+        self.cpu.bus.apu.reset();
+        self.cpu.reset();
+    }
+
+    /// Switches the console off and on again, clearing all memory. Controllers stay plugged in and the audio
+    /// output keeps its settings.
+    pub fn power_cycle(&mut self) {
+        let old = &mut self.cpu.bus;
+        let mut bus = NesBus::from_rom(self.rom.clone()).expect("the mapper was supported at power on");
+        for port in 0..2 {
+            bus.connect(port, old.disconnect(port));
+        }
+        bus.apu.expansion = old.apu.expansion.take();
+        bus.apu.set_sample_rate(old.apu.sample_rate());
+        bus.apu.set_filters_enabled(old.apu.filters_enabled());
+        bus.apu.set_stems_enabled(old.apu.stems_enabled());
+
+        self.cpu = Cpu::with_bus(bus);
+        self.cpu.reset();
+    }
+
+    /// Runs until the PPU finishes the frame it is on. A jammed CPU doesn't stop the rest of the console, the
+    /// frame still completes but the jam is reported until the next reset.
+    pub fn run_frame(&mut self) -> Result<(), Jam> {
+        let next_frame = self.cpu.bus.ppu.frame + 1;
+        while self.cpu.bus.ppu.frame < next_frame {
+            self.cpu.step();
+        }
+        self.cpu.jam().map_or(Ok(()), Err)
+    }
+
+    /// Palette indices of the last frame, `SCREEN_WIDTH` by `SCREEN_HEIGHT`
+    pub fn framebuffer(&self) -> &[u8] {
+        self.cpu.bus.ppu.frame_buffer()
+    }
+
+    /// The last frame as packed 8 bit RGB
+    pub fn framebuffer_rgb(&self) -> Vec<u8> {
+        self.framebuffer()
+            .iter()
+            .flat_map(|&index| {
+                let (r, g, b) = SYSTEM_PALETTE[index as usize];
+                [r, g, b]
+            })
+            .collect()
+    }
+
+    /// Copies as many audio samples as are ready and fit into `out`, returning how many that was. Samples
+    /// pile up until they are drained, so frontends should drain after every frame.
+    pub fn drain_samples(&mut self, out: &mut [f32]) -> usize {
+        self.cpu.bus.apu.drain_samples(out)
+    }
+
+    /// Plugs `device` into controller port `port` (0 or 1), see `NesBus::connect`
+    pub fn connect(&mut self, port: usize, device: Option<Box<dyn InputDevice>>) {
+        self.cpu.bus.connect(port, device);
+    }
+
+    /// The joypad of `player` (0-3), see `NesBus::player_mut`
+    pub fn player_mut(&mut self, player: usize) -> Option<&mut Joypad> {
+        self.cpu.bus.player_mut(player)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{apu::expansion, controller::Button, ppu::SCREEN_WIDTH, test_rom::TestRom};
+
+    #[test]
+    fn test_reset_keeps_ram_and_power_cycle_clears_it() {
+        // INC $00, then spin
+        let mut console = Console::from_rom_bytes(&TestRom::nrom(&[0xE6, 0x00, 0x4C, 0x02, 0xC0]).ines()).unwrap();
+        console.player_mut(0).unwrap().set_pressed(Button::Start, true);
+        console.run_frame().unwrap();
+        assert_eq!(console.cpu.bus.ppu.frame, 1);
+        assert_eq!(console.cpu.mem_read(0x0000), 1);
+
+        console.reset();
+        console.run_frame().unwrap();
+        assert_eq!(console.cpu.mem_read(0x0000), 2);
+
+        console.power_cycle();
+        console.run_frame().unwrap();
+        assert_eq!(console.cpu.mem_read(0x0000), 1);
+        assert!(console.player_mut(0).unwrap().pressed(Button::Start));
+    }
+
+    #[test]
+    fn test_framebuffer_and_audio() {
+        // Backdrop color $30, then spin with rendering off
+        #[rustfmt::skip]
+        let program = [
+            0xA9, 0x3F, 0x8D, 0x06, 0x20, // LDA #$3F, STA $2006
+            0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00, STA $2006
+            0xA9, 0x30, 0x8D, 0x07, 0x20, // LDA #$30, STA $2007
+            // Point v away from the palette so the backdrop is what's drawn
+            0xA9, 0x00, 0x8D, 0x06, 0x20, // LDA #$00, STA $2006
+            0x8D, 0x06, 0x20,             // STA $2006
+            0x4C, 0x17, 0xC0,             // JMP $C017
+        ];
+        let mut console = Console::from_rom_bytes(&TestRom::nrom(&program).ines()).unwrap();
+        console.run_frame().unwrap();
+        console.run_frame().unwrap();
+        let rgb = console.framebuffer_rgb();
+        assert_eq!(rgb.len(), console.framebuffer().len() * 3);
+        let pixel = (SCREEN_WIDTH * 100 + 10) * 3;
+        assert_eq!(rgb[pixel..pixel + 3], [SYSTEM_PALETTE[0x30].0, SYSTEM_PALETTE[0x30].1, SYSTEM_PALETTE[0x30].2]);
+
+        let mut samples = vec![0.0; 4096];
+        assert!(console.drain_samples(&mut samples) > 1000);
+    }
+
+    #[test]
+    fn test_vrc6_audio() {
+        // VRC6 pulse 1 at full volume around 440Hz, then spin
+        #[rustfmt::skip]
+        let program = [
+            0xA9, 0x7F, 0x8D, 0x00, 0x90, // LDA #$7F, STA $9000
+            0xA9, 0xFD, 0x8D, 0x01, 0x90, // LDA #$FD, STA $9001
+            0xA9, 0x80, 0x8D, 0x02, 0x90, // LDA #$80, STA $9002
+            0x4C, 0x0F, 0xC0,             // JMP $C00F
+        ];
+        // No mapper with a sound chip is supported yet, so the chip is attached by hand
+        let loudest = |vrc6: bool| {
+            let mut console = Console::from_rom_bytes(&TestRom::nrom(&program).ines()).unwrap();
+            if vrc6 {
+                console.cpu.bus.apu.expansion = expansion::for_mapper(24);
+            }
+            let mut samples = vec![0.0; 4096];
+            let mut loudest = 0.0f32;
+            for frame in 0..6 {
+                console.run_frame().unwrap();
+                let count = console.drain_samples(&mut samples);
+                // Past the thump of the DACs powering on
+                if frame >= 3 {
+                    loudest = samples[..count].iter().fold(loudest, |loudest, sample| loudest.max(sample.abs()));
+                }
+            }
+            loudest
+        };
+        assert!(loudest(false) < 1e-3);
+        assert!(loudest(true) > 0.05);
+    }
+
+    #[test]
+    fn test_jam_until_reset() {
+        // INC $00, JAM
+        let mut console = Console::from_rom_bytes(&TestRom::nrom(&[0xE6, 0x00, 0x02]).ines()).unwrap();
+        let jam = console.run_frame().unwrap_err();
+        assert_eq!(jam, Jam { address: 0xC002, opcode: 0x02 });
+        // The PPU kept going while the CPU was stuck
+        assert_eq!(console.cpu.bus.ppu.frame, 1);
+        assert_eq!(console.run_frame(), Err(jam));
+
+        console.reset();
+        assert!(console.cpu.jam().is_none());
+        assert!(console.run_frame().is_err());
+        assert_eq!(console.cpu.mem_read(0x0000), 2);
+    }
+}
@@ -0,0 +1,193 @@
+pub mod keyboard;
+pub mod multitap;
+pub mod paddle;
+pub mod power_pad;
+pub mod zapper;
+
+use std::any::Any;
+
+use crate::ppu::Ppu;
+
+/// Something plugged into one of the controller ports.
+///
+/// Every device sees each $4016 write, which drives the shared OUT lines, and answers reads of its own port,
+/// $4016 for port 1 and $4017 for port 2.
+pub trait InputDevice: Any {
+    /// A write to $4016, bit 0 is the strobe line
+    fn write(&mut self, data: u8);
+    /// A read of the port, only bits 0-4 are driven by the device, the rest are filled in from open bus. Light
+    /// guns look at the picture the PPU is drawing.
+    fn read(&mut self, ppu: &Ppu) -> u8;
+    /// For getting at the concrete device through `NesBus::device_mut`
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Bits of a port read that come from the controller, the others are whatever was last on the data bus
+pub const DEVICE_BITS: u8 = 0b0001_1111;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+    Select,
+    Start,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Button {
+    /// In the order the joypad reports them
+    pub const ALL: [Button; 8] = [
+        Button::A,
+        Button::B,
+        Button::Select,
+        Button::Start,
+        Button::Up,
+        Button::Down,
+        Button::Left,
+        Button::Right,
+    ];
+
+    /// The button's bit in a joypad report
+    pub fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Button::A => "A",
+            Button::B => "B",
+            Button::Select => "SELECT",
+            Button::Start => "START",
+            Button::Up => "UP",
+            Button::Down => "DOWN",
+            Button::Left => "LEFT",
+            Button::Right => "RIGHT",
+        }
+    }
+
+    /// Case insensitive
+    pub fn from_name(name: &str) -> Option<Button> {
+        Button::ALL.into_iter().find(|button| button.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// The standard NES controller, an 8 bit parallel-in serial-out shift register
+#[derive(Debug, Default)]
+pub struct Joypad {
+    /// One bit per button, in `Button::ALL` order
+    buttons: u8,
+    shift: u8,
+    strobe: bool,
+    /// A real D-pad can't press Left+Right or Up+Down at once and some games crash when they see it. When false
+    /// both directions of such a pair read as released.
+    pub allow_opposing_directions: bool,
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_pressed(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.buttons |= button.bit();
+        } else {
+            self.buttons &= !button.bit();
+        }
+    }
+
+    pub fn pressed(&self, button: Button) -> bool {
+        self.buttons & button.bit() != 0
+    }
+
+    /// Sets every button at once, one bit each in `Button::ALL` order
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.buttons = buttons;
+    }
+
+    pub fn buttons(&self) -> u8 {
+        self.buttons
+    }
+
+    /// What the shift register loads when strobed
+    fn report(&self) -> u8 {
+        let mut report = self.buttons;
+        if !self.allow_opposing_directions {
+            for (first, second) in [(Button::Up, Button::Down), (Button::Left, Button::Right)] {
+                let pair = first.bit() | second.bit();
+                if report & pair == pair {
+                    report &= !pair;
+                }
+            }
+        }
+        report
+    }
+}
+
+impl InputDevice for Joypad {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 1 != 0;
+        if self.strobe {
+            self.shift = self.report();
+        }
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        // While the strobe is high the register keeps reloading, so every read is the A button
+        if self.strobe {
+            return self.report() & 1;
+        }
+        let bit = self.shift & 1;
+        // Official controllers shift in 1s once all eight buttons have been read
+        self.shift = self.shift >> 1 | 0b1000_0000;
+        bit
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ppu::Mirroring;
+
+    fn read_report(joypad: &mut Joypad) -> Vec<u8> {
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        joypad.write(1);
+        joypad.write(0);
+        (0..10).map(|_| joypad.read(&ppu)).collect()
+    }
+
+    #[test]
+    fn test_serial_report() {
+        let mut joypad = Joypad::new();
+        joypad.set_pressed(Button::A, true);
+        joypad.set_pressed(Button::Start, true);
+        joypad.set_pressed(Button::Left, true);
+        assert_eq!(read_report(&mut joypad), [1, 0, 0, 1, 0, 0, 1, 0, 1, 1]);
+
+        // Reads with the strobe held all return A
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        joypad.write(1);
+        joypad.set_pressed(Button::A, false);
+        assert_eq!(joypad.read(&ppu), 0);
+        assert_eq!(joypad.read(&ppu), 0);
+    }
+
+    #[test]
+    fn test_opposing_directions() {
+        let mut joypad = Joypad::new();
+        joypad.set_pressed(Button::Left, true);
+        joypad.set_pressed(Button::Right, true);
+        joypad.set_pressed(Button::Up, true);
+        assert_eq!(read_report(&mut joypad)[4..8], [1, 0, 0, 0]);
+
+        joypad.allow_opposing_directions = true;
+        assert_eq!(read_report(&mut joypad)[4..8], [1, 0, 1, 1]);
+    }
+}
@@ -0,0 +1,201 @@
+use std::any::Any;
+
+use super::InputDevice;
+use crate::ppu::Ppu;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Digit0,
+    Minus,
+    Caret,
+    Yen,
+    Stop,
+    Escape,
+    Q,
+    W,
+    E,
+    R,
+    T,
+    Y,
+    U,
+    I,
+    O,
+    P,
+    At,
+    LeftBracket,
+    Return,
+    Control,
+    A,
+    S,
+    D,
+    F,
+    G,
+    H,
+    J,
+    K,
+    L,
+    Semicolon,
+    Colon,
+    RightBracket,
+    Kana,
+    LeftShift,
+    Z,
+    X,
+    C,
+    V,
+    B,
+    N,
+    M,
+    Comma,
+    Period,
+    Slash,
+    Underscore,
+    RightShift,
+    Graph,
+    Space,
+    ClearHome,
+    Insert,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The key matrix, by row. Each row is column 0 then column 1, each column read as bits 1 to 4.
+#[rustfmt::skip]
+const MATRIX: [[Key; 8]; 9] = [
+    [Key::F8, Key::Return, Key::LeftBracket, Key::RightBracket, Key::Kana, Key::RightShift, Key::Yen, Key::Stop],
+    [Key::F7, Key::At, Key::Colon, Key::Semicolon, Key::Underscore, Key::Slash, Key::Minus, Key::Caret],
+    [Key::F6, Key::O, Key::L, Key::K, Key::Period, Key::Comma, Key::P, Key::Digit0],
+    [Key::F5, Key::I, Key::U, Key::J, Key::M, Key::N, Key::Digit9, Key::Digit8],
+    [Key::F4, Key::Y, Key::G, Key::H, Key::B, Key::V, Key::Digit7, Key::Digit6],
+    [Key::F3, Key::T, Key::R, Key::D, Key::F, Key::C, Key::Digit5, Key::Digit4],
+    [Key::F2, Key::W, Key::S, Key::A, Key::X, Key::Z, Key::E, Key::Digit3],
+    [Key::F1, Key::Escape, Key::Q, Key::Control, Key::LeftShift, Key::Graph, Key::Digit1, Key::Digit2],
+    [Key::ClearHome, Key::Up, Key::Right, Key::Left, Key::Down, Key::Space, Key::Delete, Key::Insert],
+];
+
+impl Key {
+    /// Row and bit of the key in `MATRIX`
+    fn position(self) -> (usize, u8) {
+        for (row, keys) in MATRIX.iter().enumerate() {
+            if let Some(bit) = keys.iter().position(|&key| key == self) {
+                return (row, bit as u8);
+            }
+        }
+        unreachable!("{self:?} is missing from the key matrix")
+    }
+}
+
+/// The Family BASIC keyboard. It sits on the Famicom expansion port and answers $4017 reads, so it goes in
+/// port 2.
+///
+/// Writes to $4016 scan the 9x8 key matrix: bit 2 enables the keyboard, bit 1 picks which half of the current
+/// row to read and bit 0 goes back to the first row. Each time bit 1 goes from 1 to 0 the next row is selected.
+/// Reads return the selected four keys on bits 1 to 4, 0 for pressed.
+#[derive(Debug, Default)]
+pub struct Keyboard {
+    /// One byte per row, a set bit is a pressed key
+    keys: [u8; 9],
+    row: usize,
+    column: u8,
+    enabled: bool,
+}
+
+impl Keyboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_pressed(&mut self, key: Key, pressed: bool) {
+        let (row, bit) = key.position();
+        if pressed {
+            self.keys[row] |= 1 << bit;
+        } else {
+            self.keys[row] &= !(1 << bit);
+        }
+    }
+
+    pub fn pressed(&self, key: Key) -> bool {
+        let (row, bit) = key.position();
+        self.keys[row] & 1 << bit != 0
+    }
+
+    pub fn release_all(&mut self) {
+        self.keys = [0; 9];
+    }
+}
+
+impl InputDevice for Keyboard {
+    fn write(&mut self, data: u8) {
+        let column = (data >> 1) & 1;
+        if data & 0b001 != 0 {
+            self.row = 0;
+        } else if self.column == 1 && column == 0 {
+            self.row += 1;
+        }
+        self.column = column;
+        self.enabled = data & 0b100 != 0;
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        // Past the last row nothing reads as pressed
+        let keys = self.keys.get(self.row).map_or(0, |keys| keys >> (self.column * 4) & 0b1111);
+        !keys << 1 & 0b1_1110
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ppu::Mirroring;
+
+    #[test]
+    fn test_matrix_scan() {
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut keyboard = Keyboard::new();
+        keyboard.set_pressed(Key::Return, true);
+        keyboard.set_pressed(Key::Space, true);
+
+        // Reset to row 0, then read both halves of every row
+        keyboard.write(0b101);
+        let mut reads = vec![];
+        for _ in 0..10 {
+            keyboard.write(0b100);
+            reads.push(keyboard.read(&ppu));
+            keyboard.write(0b110);
+            reads.push(keyboard.read(&ppu));
+        }
+        assert_eq!(reads[0], 0b1_1010);
+        assert_eq!(reads[17], 0b1_1010);
+        assert!(reads.iter().enumerate().all(|(index, &read)| index == 0 || index == 17 || read == 0b1_1110));
+
+        keyboard.write(0);
+        assert_eq!(keyboard.read(&ppu), 0);
+    }
+}
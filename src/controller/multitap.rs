@@ -0,0 +1,122 @@
+use std::any::Any;
+
+use super::{InputDevice, Joypad};
+use crate::ppu::Ppu;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultitapKind {
+    /// The NES Four Score, plugged into both controller ports and reporting on D0
+    FourScore,
+    /// Hori's 4 Players Adapter in 4 player mode, on the Famicom expansion port and reporting on D1
+    Hori,
+}
+
+/// One port's half of a four player adapter: two joypads read back to back, followed by a signature byte that
+/// tells games the adapter is there.
+///
+/// Port 1 carries players 1 and 3, port 2 players 2 and 4.
+#[derive(Debug)]
+pub struct Multitap {
+    kind: MultitapKind,
+    /// 0 or 1
+    port: usize,
+    pub joypads: [Joypad; 2],
+    /// 24 bits, shifted out lowest first
+    shift: u32,
+    strobe: bool,
+}
+
+impl Multitap {
+    pub fn new(kind: MultitapKind, port: usize) -> Self {
+        Self {
+            kind,
+            port,
+            joypads: [Joypad::new(), Joypad::new()],
+            shift: 0,
+            strobe: false,
+        }
+    }
+
+    /// The signature byte in the order games read it, most significant bit first
+    fn signature(&self) -> u8 {
+        // The Hori adapter swaps the two
+        match (self.kind, self.port) {
+            (MultitapKind::FourScore, 0) | (MultitapKind::Hori, 1) => 0b0001_0000,
+            _ => 0b0010_0000,
+        }
+    }
+
+    fn report(&self) -> u32 {
+        self.joypads[0].report() as u32
+            | (self.joypads[1].report() as u32) << 8
+            | (self.signature().reverse_bits() as u32) << 16
+    }
+
+    fn data_line(&self) -> u8 {
+        match self.kind {
+            MultitapKind::FourScore => 0,
+            MultitapKind::Hori => 1,
+        }
+    }
+}
+
+impl InputDevice for Multitap {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 1 != 0;
+        if self.strobe {
+            self.shift = self.report();
+        }
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        let bit = if self.strobe {
+            self.report() & 1
+        } else {
+            let bit = self.shift & 1;
+            // Like a joypad, all 1s once the 24 bits are out
+            self.shift = self.shift >> 1 | 1 << 23;
+            bit
+        };
+        (bit as u8) << self.data_line()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{controller::Button, ppu::Mirroring};
+
+    fn read_all(multitap: &mut Multitap) -> Vec<u8> {
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        multitap.write(1);
+        multitap.write(0);
+        (0..26).map(|_| multitap.read(&ppu)).collect()
+    }
+
+    #[test]
+    fn test_four_score_signature() {
+        let mut port1 = Multitap::new(MultitapKind::FourScore, 0);
+        port1.joypads[0].set_pressed(Button::A, true);
+        port1.joypads[1].set_pressed(Button::Right, true);
+        let bits = read_all(&mut port1);
+        assert_eq!(bits[..8], [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bits[8..16], [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(bits[16..], [0, 0, 0, 1, 0, 0, 0, 0, 1, 1]);
+
+        let mut port2 = Multitap::new(MultitapKind::FourScore, 1);
+        assert_eq!(read_all(&mut port2)[16..24], [0, 0, 1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_hori_reports_on_d1() {
+        let mut port1 = Multitap::new(MultitapKind::Hori, 0);
+        port1.joypads[0].set_pressed(Button::A, true);
+        let bits = read_all(&mut port1);
+        assert_eq!(bits[0], 0b10);
+        assert_eq!(bits[16..24], [0, 0, 0b10, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,65 @@
+use std::any::Any;
+
+use super::InputDevice;
+use crate::ppu::Ppu;
+
+/// The Vaus controller that came with Arkanoid, normally in port 2.
+///
+/// A strobe latches the knob's position, which then comes out inverted and most significant bit first on bit 4
+/// of each read. Bit 3 is the fire button.
+#[derive(Debug, Default)]
+pub struct Paddle {
+    /// Where the knob is turned to. Arkanoid expects roughly 98 to 242.
+    pub position: u8,
+    pub fire: bool,
+    shift: u8,
+    strobe: bool,
+}
+
+impl Paddle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl InputDevice for Paddle {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 1 != 0;
+        if self.strobe {
+            self.shift = !self.position;
+        }
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        let bit = if self.strobe {
+            !self.position >> 7
+        } else {
+            let bit = self.shift >> 7;
+            self.shift <<= 1;
+            bit
+        };
+        bit << 4 | (self.fire as u8) << 3
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ppu::Mirroring;
+
+    #[test]
+    fn test_position_is_inverted_msb_first() {
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut paddle = Paddle::new();
+        paddle.position = 0b1010_0000;
+        paddle.fire = true;
+        paddle.write(1);
+        paddle.write(0);
+        let reads: Vec<u8> = (0..8).map(|_| paddle.read(&ppu)).collect();
+        assert_eq!(reads, [0x08, 0x18, 0x08, 0x18, 0x18, 0x18, 0x18, 0x18]);
+    }
+}
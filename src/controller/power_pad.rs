@@ -0,0 +1,95 @@
+use std::any::Any;
+
+use super::InputDevice;
+use crate::ppu::Ppu;
+
+/// Buttons in the order bit 3 reports them
+const D3_ORDER: [u8; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+/// Buttons in the order bit 4 reports them
+const D4_ORDER: [u8; 4] = [4, 3, 12, 8];
+
+/// The Power Pad floor mat, sold in Japan as the Family Trainer, normally in port 2.
+///
+/// Its twelve buttons come out of two shift registers at once, eight on bit 3 and four on bit 4, each followed
+/// by 1s.
+#[derive(Debug, Default)]
+pub struct PowerPad {
+    /// Bit n is button n + 1
+    buttons: u16,
+    d3_shift: u8,
+    d4_shift: u8,
+    strobe: bool,
+}
+
+impl PowerPad {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buttons are numbered 1 to 12 as printed on side B of the mat, side A leaves some of them blank
+    pub fn set_pressed(&mut self, button: u8, pressed: bool) {
+        assert!((1..=12).contains(&button), "no power pad button {button}");
+        if pressed {
+            self.buttons |= 1 << (button - 1);
+        } else {
+            self.buttons &= !(1 << (button - 1));
+        }
+    }
+
+    pub fn pressed(&self, button: u8) -> bool {
+        (1..=12).contains(&button) && self.buttons & 1 << (button - 1) != 0
+    }
+
+    fn latch(&mut self) {
+        let collect = |order: &[u8]| {
+            order.iter().enumerate().fold(0, |bits, (index, &button)| bits | (self.pressed(button) as u8) << index)
+        };
+        let (d3, d4) = (collect(&D3_ORDER), collect(&D4_ORDER));
+        self.d3_shift = d3;
+        self.d4_shift = d4 | 0b1111_0000;
+    }
+}
+
+impl InputDevice for PowerPad {
+    fn write(&mut self, data: u8) {
+        self.strobe = data & 1 != 0;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    fn read(&mut self, _ppu: &Ppu) -> u8 {
+        if self.strobe {
+            self.latch();
+        }
+        let bits = (self.d4_shift & 1) << 4 | (self.d3_shift & 1) << 3;
+        if !self.strobe {
+            self.d3_shift = self.d3_shift >> 1 | 0b1000_0000;
+            self.d4_shift = self.d4_shift >> 1 | 0b1000_0000;
+        }
+        bits
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ppu::Mirroring;
+
+    #[test]
+    fn test_serial_order() {
+        let ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut pad = PowerPad::new();
+        pad.set_pressed(1, true);
+        pad.set_pressed(12, true);
+        pad.write(1);
+        pad.write(0);
+        let reads: Vec<u8> = (0..9).map(|_| pad.read(&ppu)).collect();
+        // Button 1 is second on bit 3, button 12 third on bit 4
+        assert_eq!(reads, [0x00, 0x08, 0x10, 0x00, 0x10, 0x10, 0x10, 0x10, 0x18]);
+    }
+}
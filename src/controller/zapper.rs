@@ -0,0 +1,120 @@
+use std::any::Any;
+
+use super::InputDevice;
+use crate::ppu::{Ppu, DOTS_PER_SCANLINE, SCANLINES_PER_FRAME, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// How long the photodiode keeps reporting light after the beam passes, in scanlines
+const LIGHT_PERSISTENCE: u32 = 26;
+/// Luma (0-255) a pixel needs for the sensor to pick it up
+const BRIGHTNESS_THRESHOLD: u32 = 128;
+
+/// The NES Zapper light gun, normally in port 2.
+///
+/// Bit 3 of a read is low while the sensor sees light and bit 4 is high while the trigger is pulled. The sensor
+/// only reacts to pixels the PPU drew recently, so games that flash the screen or targets read it during the
+/// following frame.
+#[derive(Debug)]
+pub struct Zapper {
+    /// Screen coordinates the gun points at, None when it points away from the screen
+    aim: Option<(u16, u16)>,
+    pub trigger: bool,
+    /// Half the width of the square around the aim point the sensor sees, in pixels
+    pub radius: u16,
+}
+
+impl Zapper {
+    pub fn new() -> Self {
+        Self { aim: None, trigger: false, radius: 2 }
+    }
+
+    /// Points the gun at pixel (`x`, `y`), or away from the screen for None
+    pub fn aim(&mut self, position: Option<(u16, u16)>) {
+        self.aim = position;
+    }
+
+    fn sees_light(&self, ppu: &Ppu) -> bool {
+        let Some((aim_x, aim_y)) = self.aim else {
+            return false;
+        };
+        let frame_dots = SCANLINES_PER_FRAME as u32 * DOTS_PER_SCANLINE as u32;
+        let beam = ppu.scanline as u32 * DOTS_PER_SCANLINE as u32 + ppu.dot as u32;
+
+        let rows = aim_y.saturating_sub(self.radius)..=aim_y.saturating_add(self.radius).min(SCREEN_HEIGHT as u16 - 1);
+        rows.flat_map(|y| {
+            let columns = aim_x.saturating_sub(self.radius)..=aim_x.saturating_add(self.radius).min(SCREEN_WIDTH as u16 - 1);
+            columns.map(move |x| (x, y))
+        })
+        .any(|(x, y)| {
+            // Pixel x is drawn on dot x + 1, anything past the beam is still from the previous frame
+            let drawn = y as u32 * DOTS_PER_SCANLINE as u32 + x as u32 + 1;
+            let age = (beam + frame_dots - drawn) % frame_dots;
+            if age > LIGHT_PERSISTENCE * DOTS_PER_SCANLINE as u32 {
+                return false;
+            }
+            let (r, g, b) = ppu.pixel_rgb(x as usize, y as usize);
+            (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000 >= BRIGHTNESS_THRESHOLD
+        })
+    }
+}
+
+impl Default for Zapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputDevice for Zapper {
+    fn write(&mut self, _data: u8) {}
+
+    fn read(&mut self, ppu: &Ppu) -> u8 {
+        let dark = !self.sees_light(ppu) as u8;
+        (self.trigger as u8) << 4 | dark << 3
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ppu::Mirroring;
+
+    /// A PPU with rendering off, drawing a backdrop of `color`, stopped at `scanline`
+    fn ppu_drawing(color: u8, scanline: u16) -> Ppu {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        ppu.write_register(0x2006, 0x3F);
+        ppu.write_register(0x2006, 0x00);
+        ppu.write_register(0x2007, color);
+        // Leave v away from the palette so the backdrop is what's drawn
+        ppu.write_register(0x2006, 0x00);
+        ppu.write_register(0x2006, 0x00);
+        while ppu.scanline != scanline {
+            ppu.tick();
+        }
+        ppu
+    }
+
+    #[test]
+    fn test_light_follows_the_beam() {
+        let mut zapper = Zapper::new();
+        zapper.aim(Some((128, 100)));
+
+        // White, just drawn
+        assert_eq!(zapper.read(&ppu_drawing(0x30, 110)), 0b0000);
+        // Long enough ago that the sensor has gone dark again
+        assert_eq!(zapper.read(&ppu_drawing(0x30, 200)), 0b1000);
+        // Black
+        assert_eq!(zapper.read(&ppu_drawing(0x0F, 110)), 0b1000);
+
+        zapper.aim(None);
+        zapper.trigger = true;
+        assert_eq!(zapper.read(&ppu_drawing(0x30, 110)), 0b1_1000);
+
+        // Far off the screen, with the radius reaching past the end of u16
+        zapper.aim(Some((u16::MAX, u16::MAX)));
+        zapper.radius = 10;
+        assert_eq!(zapper.read(&ppu_drawing(0x30, 110)), 0b1_1000);
+    }
+}
@@ -1,25 +1,164 @@
-use std::process;
+use std::fmt::{self, Display};
 
-use crate::{registers::Registers, NegativeSet, opcodes::{OPCODES, OpcodeName, AddressingMode}};
+use crate::{bus::{Bus, FlatRam}, registers::Registers, NegativeSet, opcodes::{OPCODES, OPCODES_65C02, Opcode, OpcodeName, AddressingMode}};
+use crate::loader::{EntryPoint, Image, LoadError};
+use crate::watch::{Access, Interrupt, Watch};
 
-pub struct Cpu {
+const STACK: u16 = 0x0100;
+const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+
+/// Which member of the 6502 family the core behaves as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuVariant {
+    /// The NES's CPU, an NMOS 6502 with the decimal mode circuitry cut out so the D flag does nothing
+    #[default]
+    Ricoh2A03,
+    /// A stock NMOS 6502 with working BCD arithmetic
+    Nmos6502,
+    /// WDC's CMOS 65C02: new instructions and addressing modes in place of the unofficial opcodes, decimal mode
+    /// with valid flags and the JMP ($xxFF) bug fixed
+    Wdc65C02,
+}
+
+impl CpuVariant {
+    pub fn opcodes(self) -> &'static [Opcode; 256] {
+        match self {
+            CpuVariant::Ricoh2A03 | CpuVariant::Nmos6502 => &OPCODES,
+            CpuVariant::Wdc65C02 => &OPCODES_65C02,
+        }
+    }
+}
+
+/// Values the unstable XAA and LXA opcodes OR into A before using it. They vary from chip to chip and even with
+/// temperature, the defaults are what NES consoles usually show.
+#[derive(Debug, Clone, Copy)]
+pub struct Magic {
+    pub xaa: u8,
+    pub lxa: u8,
+}
+
+impl Default for Magic {
+    fn default() -> Self {
+        Self { xaa: 0xEE, lxa: 0xFF }
+    }
+}
+
+/// A JAM opcode, or STP on the 65C02, was executed and the CPU has locked up until the next reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jam {
+    /// Where the JAM instruction is
+    pub address: u16,
+    pub opcode: u8,
+}
+
+impl Display for Jam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CPU jammed by opcode ${:02X} at ${:04X}", self.opcode, self.address)
+    }
+}
+
+impl std::error::Error for Jam {}
+
+pub struct Cpu<B: Bus = FlatRam> {
     pub registers: Registers,
-    memory: [u8; 0xFFFF],
+    pub bus: B,
+    pub variant: CpuVariant,
+    pub magic: Magic,
+    /// CPU cycles since power on
+    pub cycles: u64,
+    /// Accesses and interrupts to record for a debugger
+    pub watch: Watch,
+    /// Level of /NMI at the end of the last cycle, NMI is edge triggered
+    nmi_previous_line: bool,
+    /// An NMI edge was seen and has not been serviced yet
+    nmi_pending: bool,
+    /// `nmi_pending` as it was one cycle ago. Interrupts are polled before the last cycle of an instruction, so
+    /// an NMI that arrives on the last cycle waits until after the next instruction
+    nmi_polled: bool,
+    /// /IRQ is low and the I flag is clear
+    irq_pending: bool,
+    /// `irq_pending` as it was one cycle ago
+    irq_polled: bool,
+    jam: Option<Jam>,
+    /// Inside a 65C02 WAI, waiting for an interrupt
+    waiting: bool,
 }
 
 impl Cpu {
     pub fn new() -> Self {
-        Self { 
-            registers: Registers::default(), 
-            memory: [0; 0xFFFF],
+        Self::with_bus(FlatRam::new())
+    }
+    /// Copies `program` to $8000 and runs it from there, for tests. Panics if it doesn't fit.
+    pub fn load_and_run(&mut self, program: &[u8]) {
+        self.load_at(0x8000, program, EntryPoint::Address(0x8000)).expect("the program runs past $FFFF");
+        self.run();
+    }
+    /// Copies `program` to $8000 and starts there. Panics if it doesn't fit.
+    #[deprecated(note = "use `load_at`, which returns an error when the program doesn't fit")]
+    pub fn load(&mut self, program: &[u8]) {
+        self.load_at(0x8000, program, EntryPoint::Address(0x8000)).expect("the program runs past $FFFF");
+    }
+    /// Copies a raw binary to `origin`
+    pub fn load_at(&mut self, origin: u16, program: &[u8], entry: EntryPoint) -> Result<(), LoadError> {
+        self.load_image(&Image::raw(origin, program), entry)
+    }
+    /// Copies every segment of `image` into memory, or nothing at all if any of them doesn't fit
+    pub fn load_image(&mut self, image: &Image, entry: EntryPoint) -> Result<(), LoadError> {
+        image.check()?;
+        for (address, data) in &image.segments {
+            self.bus.load(*address, data);
+        }
+        match (entry, image.entry) {
+            (EntryPoint::Address(address), _) | (EntryPoint::FromImage, Some(address)) => {
+                self.registers.program_counter = address
+            }
+            (EntryPoint::ResetVector, _) | (EntryPoint::FromImage, None) => self.reset(),
         }
+        Ok(())
     }
-    pub fn mem_read(&self, address: u16) -> u8 {
-        self.memory[address as usize]
+}
+
+impl Default for Cpu {
+    fn default() -> Self {
+        Self::new()
     }
-    pub fn mem_read_u16(&self, address: u16) -> u16 {
+}
+
+impl<B: Bus> Cpu<B> {
+    pub fn with_bus(bus: B) -> Self {
+        let mut registers = Registers { stack_pointer: 0xFD, ..Default::default() };
+        registers.status.interrupt_disable = true;
+
+        Self {
+            registers,
+            bus,
+            variant: CpuVariant::default(),
+            magic: Magic::default(),
+            cycles: 0,
+            watch: Watch::default(),
+            nmi_previous_line: false,
+            nmi_pending: false,
+            nmi_polled: false,
+            irq_pending: false,
+            irq_polled: false,
+            jam: None,
+            waiting: false,
+        }
+    }
+    pub fn mem_read(&mut self, address: u16) -> u8 {
+        while self.bus.dma_cycle(address) {
+            self.end_cycle();
+        }
+        let data = self.bus.mem_read(address);
+        self.watch.access(Access::Read, address, data);
+        self.end_cycle();
+        data
+    }
+    pub fn mem_read_u16(&mut self, address: u16) -> u16 {
         let lo = self.mem_read(address) as u16;
-        let hi = self.mem_read(address + 1) as u16;
+        let hi = self.mem_read(address.wrapping_add(1)) as u16;
 
         (hi << 8) | lo
     }
@@ -28,289 +167,974 @@ impl Cpu {
         let lo = (data & 0xFF) as u8;
 
         self.mem_write(address, lo);
-        self.mem_write(address + 1, hi)
+        self.mem_write(address.wrapping_add(1), hi)
     }
     pub fn mem_write(&mut self, address: u16, data: u8) {
-        self.memory[address as usize] = data;
+        self.bus.mem_write(address, data);
+        self.watch.access(Access::Write, address, data);
+        self.end_cycle();
+    }
+    /// The 6502 reads or writes on every cycle, even ones where it is busy inside. Those reads still have side
+    /// effects on registers like $2002 and $2007.
+    fn dummy_read(&mut self, address: u16) {
+        self.mem_read(address);
     }
-    pub fn fetch_address(&self, mode: AddressingMode) -> u16 {
+    fn end_cycle(&mut self) {
+        self.cycles += 1;
+
+        self.nmi_polled = self.nmi_pending;
+        let nmi_line = self.bus.nmi_line();
+        if nmi_line && !self.nmi_previous_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_previous_line = nmi_line;
+
+        // IRQ is level triggered, and masked by the I flag as it is when polled. CLI, SEI and PLP change the flag
+        // after the poll, so they only take effect after the next instruction
+        self.irq_polled = self.irq_pending;
+        self.irq_pending = self.bus.irq_line() && !self.registers.status.interrupt_disable;
+    }
+    /// Returns the address of the operand and whether indexing it crossed a page, reading any operand bytes and
+    /// moving the program counter past them
+    pub fn fetch_address(&mut self, mode: AddressingMode) -> (u16, bool) {
         match mode {
-            AddressingMode::Accumulator => todo!(),
-            AddressingMode::Implied => {
-                panic!("addressing mode is implied, no data to be read");
+            AddressingMode::Accumulator | AddressingMode::Implied | AddressingMode::ZeroPageRelative => {
+                panic!("addressing mode is {mode}, no data to be read");
+            },
+            // An address in the program rom
+            AddressingMode::Immediate => {
+                let address = self.registers.program_counter;
+                self.registers.program_counter = address.wrapping_add(1);
+                (address, false)
             },
-            // An address in the program rom 
-            AddressingMode::Immediate => self.registers.program_counter,
             // An address in zero page
-            AddressingMode::ZeroPage => self.mem_read(self.registers.program_counter) as u16,
-            AddressingMode::ZeroPageX => todo!(),
-            AddressingMode::ZeroPageY => todo!(),
-            AddressingMode::Relative => todo!(),
-            AddressingMode::Absolute => todo!(),
-            AddressingMode::AbsoluteX => todo!(),
-            AddressingMode::AbsoluteY => todo!(),
-            AddressingMode::Indirect => todo!(),
-            AddressingMode::IndexedIndirectX => todo!(),
-            AddressingMode::IndirectIndexedY => todo!(),
-        }
-    }
-    pub fn load_and_run(&mut self, program: &Vec<u8>) {
-        self.load(program);
-        self.run();
+            AddressingMode::ZeroPage => (self.fetch_byte() as u16, false),
+            AddressingMode::ZeroPageX => {
+                let base = self.fetch_byte();
+                self.dummy_read(base as u16);
+                (base.wrapping_add(self.registers.x) as u16, false)
+            },
+            AddressingMode::ZeroPageY => {
+                let base = self.fetch_byte();
+                self.dummy_read(base as u16);
+                (base.wrapping_add(self.registers.y) as u16, false)
+            },
+            AddressingMode::Relative => {
+                let offset = self.fetch_byte() as i8;
+                let address = self.registers.program_counter.wrapping_add(offset as u16);
+                (address, address & 0xFF00 != self.registers.program_counter & 0xFF00)
+            },
+            AddressingMode::Absolute => (self.fetch_word(), false),
+            AddressingMode::AbsoluteX => {
+                let base = self.fetch_word();
+                let address = base.wrapping_add(self.registers.x as u16);
+                (address, address & 0xFF00 != base & 0xFF00)
+            },
+            AddressingMode::AbsoluteY => {
+                let base = self.fetch_word();
+                let address = base.wrapping_add(self.registers.y as u16);
+                (address, address & 0xFF00 != base & 0xFF00)
+            },
+            AddressingMode::Indirect => {
+                let pointer = self.fetch_word();
+                if self.variant == CpuVariant::Wdc65C02 {
+                    // Fixed at the cost of a cycle
+                    self.dummy_read(self.registers.program_counter.wrapping_sub(1));
+                    return (self.mem_read_u16(pointer), false);
+                }
+                // The high byte is fetched without carrying into the page, so JMP ($10FF) reads $10FF and $1000
+                let lo = self.mem_read(pointer) as u16;
+                let hi = self.mem_read((pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF)) as u16;
+                ((hi << 8) | lo, false)
+            },
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let base = self.fetch_word();
+                self.dummy_read(self.registers.program_counter.wrapping_sub(1));
+                (self.mem_read_u16(base.wrapping_add(self.registers.x as u16)), false)
+            },
+            AddressingMode::ZeroPageIndirect => {
+                let pointer = self.fetch_byte();
+                (self.read_zero_page_u16(pointer), false)
+            },
+            AddressingMode::IndexedIndirectX => {
+                let pointer = self.fetch_byte();
+                self.dummy_read(pointer as u16);
+                let pointer = pointer.wrapping_add(self.registers.x);
+                (self.read_zero_page_u16(pointer), false)
+            },
+            AddressingMode::IndirectIndexedY => {
+                let pointer = self.fetch_byte();
+                let base = self.read_zero_page_u16(pointer);
+                let address = base.wrapping_add(self.registers.y as u16);
+                (address, address & 0xFF00 != base & 0xFF00)
+            },
+        }
+    }
+    /// Address for an instruction that only reads its operand. Indexing across a page costs a cycle, which reads
+    /// from the right offset in the wrong page while the high byte is fixed up.
+    fn read_address(&mut self, mode: AddressingMode) -> u16 {
+        let (address, page_crossed) = self.fetch_address(mode);
+        if page_crossed {
+            let unfixed = address.wrapping_sub(0x100);
+            self.index_fix_up_read(unfixed);
+        }
+        address
+    }
+    /// The dummy read while the high byte of an indexed address is fixed up. The 65C02 rereads the last operand
+    /// byte instead of the half-formed address, which could have side effects.
+    fn index_fix_up_read(&mut self, unfixed: u16) {
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.dummy_read(self.registers.program_counter.wrapping_sub(1));
+        } else {
+            self.dummy_read(unfixed);
+        }
+    }
+    /// Address for a store or read-modify-write, these always spend the cycle fixing up the high byte and read
+    /// whatever the unfixed address points at
+    fn write_address(&mut self, mode: AddressingMode) -> u16 {
+        let (address, page_crossed) = self.fetch_address(mode);
+        if let AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectIndexedY = mode {
+            self.index_fix_up_read(if page_crossed { address.wrapping_sub(0x100) } else { address });
+        }
+        address
+    }
+    fn read_operand(&mut self, mode: AddressingMode) -> u8 {
+        let address = self.read_address(mode);
+        self.mem_read(address)
+    }
+    fn fetch_byte(&mut self) -> u8 {
+        let data = self.mem_read(self.registers.program_counter);
+        self.registers.program_counter = self.registers.program_counter.wrapping_add(1);
+        data
+    }
+    fn fetch_word(&mut self) -> u16 {
+        let lo = self.fetch_byte() as u16;
+        let hi = self.fetch_byte() as u16;
+        (hi << 8) | lo
+    }
+    /// Pointers in zero page wrap around inside it
+    fn read_zero_page_u16(&mut self, pointer: u8) -> u16 {
+        let lo = self.mem_read(pointer as u16) as u16;
+        let hi = self.mem_read(pointer.wrapping_add(1) as u16) as u16;
+        (hi << 8) | lo
+    }
+    fn stack_push(&mut self, data: u8) {
+        self.mem_write(STACK | self.registers.stack_pointer as u16, data);
+        self.registers.stack_pointer = self.registers.stack_pointer.wrapping_sub(1);
+    }
+    fn stack_push_u16(&mut self, data: u16) {
+        self.stack_push((data >> 8) as u8);
+        self.stack_push((data & 0xFF) as u8);
     }
-    pub fn load(&mut self, program: &Vec<u8>) {
-        self.memory[0x8000 .. (0x8000 + program.len())].copy_from_slice(&program);
-        self.registers.program_counter = 0x8000;
+    fn stack_pop(&mut self) -> u8 {
+        self.registers.stack_pointer = self.registers.stack_pointer.wrapping_add(1);
+        self.mem_read(STACK | self.registers.stack_pointer as u16)
     }
+    fn stack_pop_u16(&mut self) -> u16 {
+        let lo = self.stack_pop() as u16;
+        let hi = self.stack_pop() as u16;
+        (hi << 8) | lo
+    }
+    fn update_zero_and_negative_flags(&mut self, result: u8) {
+        // when the result is zero set the zero register (otherwise it will be reset)
+        self.registers.status.zero = result == 0;
+
+        // if the result is "negative", set the negative register (otherwise it will be reset)
+        self.registers.status.negative = result.negative_set();
+    }
+    /// Pushes the program counter and status then jumps through `vector`, shared by BRK, IRQ and NMI
+    fn interrupt(&mut self, vector: u16, brk: bool) {
+        self.stack_push_u16(self.registers.program_counter);
+        let mut status = self.registers.status.bits() | 0b0010_0000;
+        if brk {
+            status |= 0b0001_0000;
+        } else {
+            status &= !0b0001_0000;
+        }
+        self.stack_push(status);
+
+        // An NMI that shows up before the vector is fetched hijacks a BRK
+        let vector = if vector == IRQ_VECTOR && self.nmi_pending {
+            self.nmi_pending = false;
+            NMI_VECTOR
+        } else {
+            vector
+        };
+        self.registers.status.interrupt_disable = true;
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.registers.status.decimal = false;
+        }
+        self.registers.program_counter = self.mem_read_u16(vector);
+        self.nmi_polled = false;
+        self.irq_polled = false;
+        self.watch.interrupt(match vector {
+            NMI_VECTOR => Interrupt::Nmi,
+            _ if brk => Interrupt::Brk,
+            _ => Interrupt::Irq,
+        });
+    }
+    fn nmi(&mut self) {
+        self.nmi_pending = false;
+        self.dummy_read(self.registers.program_counter);
+        self.dummy_read(self.registers.program_counter);
+        self.interrupt(NMI_VECTOR, false);
+    }
+    fn irq(&mut self) {
+        self.dummy_read(self.registers.program_counter);
+        self.dummy_read(self.registers.program_counter);
+        self.interrupt(IRQ_VECTOR, false);
+    }
+    /// Runs the 7 cycle reset sequence, the three stack accesses are reads so nothing is pushed
+    pub fn reset(&mut self) {
+        self.dummy_read(self.registers.program_counter);
+        self.dummy_read(self.registers.program_counter);
+        for _ in 0..3 {
+            self.dummy_read(STACK | self.registers.stack_pointer as u16);
+            self.registers.stack_pointer = self.registers.stack_pointer.wrapping_sub(1);
+        }
+        self.registers.status.interrupt_disable = true;
+        self.registers.program_counter = self.mem_read_u16(RESET_VECTOR);
+        self.nmi_pending = false;
+        self.nmi_polled = false;
+        self.irq_polled = false;
+        self.jam = None;
+        self.waiting = false;
+    }
+    /// Set from when a JAM opcode is executed until the next reset
+    pub fn jam(&self) -> Option<Jam> {
+        self.jam
+    }
+    /// Runs until a BRK instruction is executed or the CPU jams
     pub fn run(&mut self) {
         loop {
-            let opcode_byte = self.mem_read(self.registers.program_counter);
-            println!("{:x}", self.registers.program_counter);
-            let opcode = OPCODES[opcode_byte as usize].unwrap_or_else(|| {
-                eprintln!("Invalid opcode: {opcode_byte}");
-                process::exit(1);
-            });
-            self.registers.program_counter += 1;
-
-            match opcode.name() {
-                OpcodeName::ADC => todo!(),
-                OpcodeName::AND => todo!(),
-                OpcodeName::ASL => todo!(),
-                OpcodeName::BCC => todo!(),
-                OpcodeName::BCS => todo!(),
-                OpcodeName::BEQ => todo!(),
-                OpcodeName::BIT => todo!(),
-                OpcodeName::BMI => todo!(),
-                OpcodeName::BNE => todo!(),
-                OpcodeName::BPL => todo!(),
-                OpcodeName::BRK => return,
-                OpcodeName::BVC => todo!(),
-                OpcodeName::BVS => todo!(),
-                OpcodeName::CLC => todo!(),
-                OpcodeName::CLD => todo!(),
-                OpcodeName::CLI => todo!(),
-                OpcodeName::CLV => todo!(),
-                OpcodeName::CMP => todo!(),
-                OpcodeName::CPX => todo!(),
-                OpcodeName::CPY => todo!(),
-                OpcodeName::DEC => todo!(),
-                OpcodeName::DEX => todo!(),
-                OpcodeName::DEY => todo!(),
-                OpcodeName::EOR => todo!(),
-                OpcodeName::INC => todo!(),
-                OpcodeName::INX => todo!(),
-                OpcodeName::INY => todo!(),
-                OpcodeName::JMP => todo!(),
-                OpcodeName::JSR => todo!(),
-                OpcodeName::LDA => self.lda(opcode.addressing_mode()),
-                OpcodeName::LDX => todo!(),
-                OpcodeName::LDY => todo!(),
-                OpcodeName::LSR => todo!(),
-                OpcodeName::NOP => todo!(),
-                OpcodeName::ORA => todo!(),
-                OpcodeName::PHA => todo!(),
-                OpcodeName::PHP => todo!(),
-                OpcodeName::PLA => todo!(),
-                OpcodeName::PLP => todo!(),
-                OpcodeName::ROL => todo!(),
-                OpcodeName::ROR => todo!(),
-                OpcodeName::RTI => todo!(),
-                OpcodeName::RTS => todo!(),
-                OpcodeName::SBC => todo!(),
-                OpcodeName::SEC => todo!(),
-                OpcodeName::SED => todo!(),
-                OpcodeName::SEI => todo!(),
-                OpcodeName::STA => self.sta(opcode.addressing_mode()),
-                OpcodeName::STX => todo!(),
-                OpcodeName::STY => todo!(),
-                OpcodeName::TAX => todo!(),
-                OpcodeName::TAY => todo!(),
-                OpcodeName::TSX => todo!(),
-                OpcodeName::TXA => todo!(),
-                OpcodeName::TXS => todo!(),
-                OpcodeName::TYA => todo!(),
+            if let OpcodeName::BRK | OpcodeName::JAM = self.step().name() {
+                return;
             }
         }
     }
+    /// Executes a single instruction, followed by the NMI or IRQ sequence if one was polled during it. A jammed
+    /// CPU only runs a single cycle with $FFFF on the address bus, so the rest of the system keeps going.
+    pub fn step(&mut self) -> Opcode {
+        if let Some(jam) = self.jam {
+            self.dummy_read(0xFFFF);
+            return self.variant.opcodes()[jam.opcode as usize];
+        }
+        if self.waiting {
+            // WAI ends when an interrupt is asserted, taken or not
+            if !self.bus.irq_line() && !self.nmi_pending {
+                self.dummy_read(self.registers.program_counter);
+                return self.variant.opcodes()[0xCB];
+            }
+            self.waiting = false;
+        }
 
-    fn adc(&mut self, addressing_mode: AddressingMode) {
+        let opcode_byte = self.mem_read(self.registers.program_counter);
+        let opcode = self.variant.opcodes()[opcode_byte as usize];
+        self.registers.program_counter = self.registers.program_counter.wrapping_add(1);
 
-    }
-    fn and(&mut self, addressing_mode: AddressingMode) {
+        let addressing_mode = opcode.addressing_mode();
+        // Single byte instructions still read the byte after the opcode, except the 65C02's one cycle NOPs
+        let single_cycle = self.variant == CpuVariant::Wdc65C02
+            && matches!(opcode.name(), OpcodeName::NOP)
+            && opcode_byte & 0b11 == 0b11;
+        if let AddressingMode::Implied | AddressingMode::Accumulator = addressing_mode {
+            if !single_cycle {
+                self.dummy_read(self.registers.program_counter);
+            }
+        }
+
+        match opcode.name() {
+            OpcodeName::ADC => self.adc(addressing_mode),
+            OpcodeName::AND => self.and(addressing_mode),
+            OpcodeName::ASL => self.asl(addressing_mode),
+            OpcodeName::BCC => self.bcc(),
+            OpcodeName::BCS => self.bcs(),
+            OpcodeName::BEQ => self.beq(),
+            OpcodeName::BIT => self.bit(addressing_mode),
+            OpcodeName::BMI => self.bmi(),
+            OpcodeName::BNE => self.bne(),
+            OpcodeName::BPL => self.bpl(),
+            OpcodeName::BRK => self.brk(),
+            OpcodeName::BVC => self.bvc(),
+            OpcodeName::BVS => self.bvs(),
+            OpcodeName::CLC => self.clc(),
+            OpcodeName::CLD => self.cld(),
+            OpcodeName::CLI => self.cli(),
+            OpcodeName::CLV => self.clv(),
+            OpcodeName::CMP => self.cmp(addressing_mode),
+            OpcodeName::CPX => self.cpx(addressing_mode),
+            OpcodeName::CPY => self.cpy(addressing_mode),
+            OpcodeName::DEC => self.dec(addressing_mode),
+            OpcodeName::DEX => self.dex(),
+            OpcodeName::DEY => self.dey(),
+            OpcodeName::EOR => self.eor(addressing_mode),
+            OpcodeName::INC => self.inc(addressing_mode),
+            OpcodeName::INX => self.inx(),
+            OpcodeName::INY => self.iny(),
+            OpcodeName::JMP => self.jmp(addressing_mode),
+            OpcodeName::JSR => self.jsr(),
+            OpcodeName::LDA => self.lda(addressing_mode),
+            OpcodeName::LDX => self.ldx(addressing_mode),
+            OpcodeName::LDY => self.ldy(addressing_mode),
+            OpcodeName::LSR => self.lsr(addressing_mode),
+            OpcodeName::NOP => self.nop(addressing_mode),
+            OpcodeName::ORA => self.ora(addressing_mode),
+            OpcodeName::PHA => self.pha(),
+            OpcodeName::PHP => self.php(),
+            OpcodeName::PLA => self.pla(),
+            OpcodeName::PLP => self.plp(),
+            OpcodeName::ROL => self.rol(addressing_mode),
+            OpcodeName::ROR => self.ror(addressing_mode),
+            OpcodeName::RTI => self.rti(),
+            OpcodeName::RTS => self.rts(),
+            OpcodeName::SBC => self.sbc(addressing_mode),
+            OpcodeName::SEC => self.sec(),
+            OpcodeName::SED => self.sed(),
+            OpcodeName::SEI => self.sei(),
+            OpcodeName::STA => self.sta(addressing_mode),
+            OpcodeName::STX => self.stx(addressing_mode),
+            OpcodeName::STY => self.sty(addressing_mode),
+            OpcodeName::TAX => self.tax(),
+            OpcodeName::TAY => self.tay(),
+            OpcodeName::TSX => self.tsx(),
+            OpcodeName::TXA => self.txa(),
+            OpcodeName::TXS => self.txs(),
+            OpcodeName::TYA => self.tya(),
+            OpcodeName::ALR => self.alr(addressing_mode),
+            OpcodeName::ANC => self.anc(addressing_mode),
+            OpcodeName::ARR => self.arr(addressing_mode),
+            OpcodeName::DCP => self.dcp(addressing_mode),
+            OpcodeName::ISC => self.isc(addressing_mode),
+            OpcodeName::LAS => self.las(addressing_mode),
+            OpcodeName::LAX => self.lax(addressing_mode),
+            OpcodeName::LXA => self.lxa(addressing_mode),
+            OpcodeName::RLA => self.rla(addressing_mode),
+            OpcodeName::RRA => self.rra(addressing_mode),
+            OpcodeName::SAX => self.sax(addressing_mode),
+            OpcodeName::SBX => self.sbx(addressing_mode),
+            OpcodeName::SHA => self.store_and_high(addressing_mode, self.registers.a & self.registers.x),
+            OpcodeName::SHX => self.store_and_high(addressing_mode, self.registers.x),
+            OpcodeName::SHY => self.store_and_high(addressing_mode, self.registers.y),
+            OpcodeName::SLO => self.slo(addressing_mode),
+            OpcodeName::SRE => self.sre(addressing_mode),
+            OpcodeName::TAS => self.tas(addressing_mode),
+            OpcodeName::XAA => self.xaa(addressing_mode),
+            OpcodeName::JAM | OpcodeName::STP => {
+                // Interrupts are ignored from here on
+                self.jam = Some(Jam { address: self.registers.program_counter.wrapping_sub(1), opcode: opcode_byte });
+                return opcode;
+            }
+            OpcodeName::BRA => self.branch(true),
+            OpcodeName::PHX => self.stack_push(self.registers.x),
+            OpcodeName::PHY => self.stack_push(self.registers.y),
+            OpcodeName::PLX => self.plx(),
+            OpcodeName::PLY => self.ply(),
+            OpcodeName::STZ => self.stz(addressing_mode),
+            OpcodeName::TRB => self.trb(addressing_mode),
+            OpcodeName::TSB => self.tsb(addressing_mode),
+            OpcodeName::WAI => self.wai(),
+            OpcodeName::RMB0
+            | OpcodeName::RMB1
+            | OpcodeName::RMB2
+            | OpcodeName::RMB3
+            | OpcodeName::RMB4
+            | OpcodeName::RMB5
+            | OpcodeName::RMB6
+            | OpcodeName::RMB7 => self.modify(addressing_mode, |_, data| data & !(1 << ((opcode_byte >> 4) & 0b111))),
+            OpcodeName::SMB0
+            | OpcodeName::SMB1
+            | OpcodeName::SMB2
+            | OpcodeName::SMB3
+            | OpcodeName::SMB4
+            | OpcodeName::SMB5
+            | OpcodeName::SMB6
+            | OpcodeName::SMB7 => self.modify(addressing_mode, |_, data| data | 1 << ((opcode_byte >> 4) & 0b111)),
+            OpcodeName::BBR0
+            | OpcodeName::BBR1
+            | OpcodeName::BBR2
+            | OpcodeName::BBR3
+            | OpcodeName::BBR4
+            | OpcodeName::BBR5
+            | OpcodeName::BBR6
+            | OpcodeName::BBR7 => self.branch_on_bit((opcode_byte >> 4) & 0b111, false),
+            OpcodeName::BBS0
+            | OpcodeName::BBS1
+            | OpcodeName::BBS2
+            | OpcodeName::BBS3
+            | OpcodeName::BBS4
+            | OpcodeName::BBS5
+            | OpcodeName::BBS6
+            | OpcodeName::BBS7 => self.branch_on_bit((opcode_byte >> 4) & 0b111, true),
+        }
 
+        if self.nmi_polled {
+            self.nmi();
+        } else if self.irq_polled {
+            self.irq();
+        }
+
+        opcode
     }
-    fn asl(&mut self, addressing_mode: AddressingMode) {
 
+    /// Whether ADC and SBC work in BCD
+    fn decimal_mode(&self) -> bool {
+        self.registers.status.decimal && self.variant != CpuVariant::Ricoh2A03
+    }
+    /// ADC, in BCD when decimal mode is on
+    fn add_with_carry(&mut self, data: u8) {
+        if !self.decimal_mode() {
+            self.add_to_a(data);
+            return;
+        }
+        let a = self.registers.a;
+        let carry = self.registers.status.carry as u16;
+        let binary = (a as u16 + data as u16 + carry) as u8;
+
+        let mut low = (a & 0x0F) as u16 + (data & 0x0F) as u16 + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        let mut sum = (a & 0xF0) as u16 + (data & 0xF0) as u16 + low;
+        // The NMOS chip takes N and V from the sum before the high digit is adjusted, and Z from the binary sum
+        let unadjusted = sum as u8;
+        self.registers.status.overflow = (a ^ unadjusted) & (data ^ unadjusted) & 0x80 != 0;
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
+        self.registers.status.carry = sum > 0xFF;
+        self.registers.a = sum as u8;
+
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.finish_decimal_65c02();
+        } else {
+            self.registers.status.zero = binary == 0;
+            self.registers.status.negative = unadjusted.negative_set();
+        }
     }
-    fn bcc(&mut self, addressing_mode: AddressingMode) {
+    /// SBC, in BCD when decimal mode is on. C and V always come from the binary subtraction.
+    fn subtract_with_borrow(&mut self, data: u8) {
+        if !self.decimal_mode() {
+            // A - M - (1 - C) is the same as A + !M + C
+            self.add_to_a(!data);
+            return;
+        }
+        let a = self.registers.a;
+        let borrow = !self.registers.status.carry as i16;
+        self.add_to_a(!data);
 
+        let mut low = (a & 0x0F) as i16 - (data & 0x0F) as i16 - borrow;
+        let result = if self.variant == CpuVariant::Wdc65C02 {
+            let mut difference = a as i16 - data as i16 - borrow;
+            if difference < 0 {
+                difference -= 0x60;
+            }
+            if low < 0 {
+                difference -= 0x06;
+            }
+            difference
+        } else {
+            if low < 0 {
+                low = ((low - 0x06) & 0x0F) - 0x10;
+            }
+            let mut difference = (a & 0xF0) as i16 - (data & 0xF0) as i16 + low;
+            if difference < 0 {
+                difference -= 0x60;
+            }
+            difference
+        };
+        self.registers.a = result as u8;
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.finish_decimal_65c02();
+        }
+    }
+    /// The 65C02 spends an extra cycle on decimal ADC and SBC to set N and Z from the BCD result
+    fn finish_decimal_65c02(&mut self) {
+        self.dummy_read(self.registers.program_counter);
+        self.update_zero_and_negative_flags(self.registers.a);
     }
-    fn bcs(&mut self, addressing_mode: AddressingMode) {
+    /// Adds to A with carry in and out in binary, shared by ADC and SBC
+    fn add_to_a(&mut self, data: u8) {
+        let sum = self.registers.a as u16 + data as u16 + self.registers.status.carry as u16;
+        let result = sum as u8;
+
+        self.registers.status.carry = sum > 0xFF;
+        // Overflow when both inputs have the same sign and the result has the other one
+        self.registers.status.overflow = (self.registers.a ^ result) & (data ^ result) & 0x80 != 0;
+        self.registers.a = result;
+        self.update_zero_and_negative_flags(result);
+    }
+    fn compare(&mut self, register: u8, addressing_mode: AddressingMode) {
+        let data = self.read_operand(addressing_mode);
+        self.registers.status.carry = register >= data;
+        self.update_zero_and_negative_flags(register.wrapping_sub(data));
+    }
+    fn branch(&mut self, condition: bool) {
+        let (target, page_crossed) = self.fetch_address(AddressingMode::Relative);
+        if !condition {
+            return;
+        }
 
+        // A taken branch that stays on its page polls interrupts as if it were a two cycle instruction
+        // The CPU reads the next opcode while adding the offset, then on a page cross the target without the carry
+        let nmi_polled = self.nmi_polled;
+        let irq_polled = self.irq_polled;
+        self.dummy_read(self.registers.program_counter);
+        if page_crossed {
+            self.dummy_read((self.registers.program_counter & 0xFF00) | (target & 0x00FF));
+        } else {
+            self.nmi_polled = nmi_polled;
+            self.irq_polled = irq_polled;
+        }
+        self.registers.program_counter = target;
+    }
+    /// SHA, SHX, SHY and TAS store `value` ANDed with one more than the high byte of the unindexed address. When
+    /// indexing crosses a page the high byte of the address is replaced by the stored value.
+    fn store_and_high(&mut self, addressing_mode: AddressingMode, value: u8) {
+        let (address, page_crossed) = self.fetch_address(addressing_mode);
+        let unfixed = if page_crossed { address.wrapping_sub(0x100) } else { address };
+        self.dummy_read(unfixed);
+        let data = value & ((unfixed >> 8) as u8).wrapping_add(1);
+        let address = if page_crossed { (data as u16) << 8 | (address & 0x00FF) } else { address };
+        self.mem_write(address, data);
+    }
+    /// Runs `operation` on the accumulator, or on memory with the read-modify-write cycle pattern
+    fn modify(&mut self, addressing_mode: AddressingMode, operation: impl FnOnce(&mut Self, u8) -> u8) {
+        if let AddressingMode::Accumulator = addressing_mode {
+            self.registers.a = operation(self, self.registers.a);
+            return;
+        }
+        let address = self.write_address(addressing_mode);
+        self.modify_at(address, operation);
+    }
+    /// Like `modify` for the shifts and rotates, which the 65C02 does a cycle faster when indexing stays on the
+    /// page
+    fn shift(&mut self, addressing_mode: AddressingMode, operation: impl FnOnce(&mut Self, u8) -> u8) {
+        if self.variant == CpuVariant::Wdc65C02 && matches!(addressing_mode, AddressingMode::AbsoluteX) {
+            let address = self.read_address(addressing_mode);
+            self.modify_at(address, operation);
+        } else {
+            self.modify(addressing_mode, operation);
+        }
+    }
+    fn modify_at(&mut self, address: u16, operation: impl FnOnce(&mut Self, u8) -> u8) {
+        let data = self.mem_read(address);
+        // The NMOS chips write the unmodified value back while the new one is worked out, the 65C02 reads again
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.dummy_read(address);
+        } else {
+            self.mem_write(address, data);
+        }
+        let result = operation(self, data);
+        self.mem_write(address, result);
+    }
+    /// BBR and BBS, which test a bit of a zero page byte and branch on it
+    fn branch_on_bit(&mut self, bit: u8, set: bool) {
+        let zero_page = self.fetch_byte() as u16;
+        let data = self.mem_read(zero_page);
+        self.dummy_read(zero_page);
+        self.branch((data >> bit) & 1 == set as u8);
     }
-    fn beq(&mut self, addressing_mode: AddressingMode) {
 
+    fn adc(&mut self, addressing_mode: AddressingMode) {
+        let data = self.read_operand(addressing_mode);
+        self.add_with_carry(data);
+    }
+    fn and(&mut self, addressing_mode: AddressingMode) {
+        self.registers.a &= self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.a);
+    }
+    fn asl(&mut self, addressing_mode: AddressingMode) {
+        self.shift(addressing_mode, |cpu, data| {
+            cpu.registers.status.carry = data.negative_set();
+            let result = data << 1;
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
+    }
+    fn bcc(&mut self) {
+        self.branch(!self.registers.status.carry);
+    }
+    fn bcs(&mut self) {
+        self.branch(self.registers.status.carry);
+    }
+    fn beq(&mut self) {
+        self.branch(self.registers.status.zero);
     }
     fn bit(&mut self, addressing_mode: AddressingMode) {
-
+        let data = self.read_operand(addressing_mode);
+        self.registers.status.zero = self.registers.a & data == 0;
+        // The 65C02's BIT #imm only sets Z
+        if let AddressingMode::Immediate = addressing_mode {
+            return;
+        }
+        self.registers.status.negative = data.negative_set();
+        self.registers.status.overflow = data & 0b0100_0000 != 0;
     }
-    fn bmi(&mut self, addressing_mode: AddressingMode) {
-
+    fn bmi(&mut self) {
+        self.branch(self.registers.status.negative);
     }
-    fn bne(&mut self, addressing_mode: AddressingMode) {
-
+    fn bne(&mut self) {
+        self.branch(!self.registers.status.zero);
     }
-    fn bpl(&mut self, addressing_mode: AddressingMode) {
-
+    fn bpl(&mut self) {
+        self.branch(!self.registers.status.negative);
     }
     fn brk(&mut self) {
-        return;
+        // BRK skips the byte after it
+        self.registers.program_counter = self.registers.program_counter.wrapping_add(1);
+        self.interrupt(IRQ_VECTOR, true);
     }
-    fn bvc(&mut self, addressing_mode: AddressingMode) {
-
+    fn bvc(&mut self) {
+        self.branch(!self.registers.status.overflow);
     }
-    fn bvs(&mut self, addressing_mode: AddressingMode) {
-
+    fn bvs(&mut self) {
+        self.branch(self.registers.status.overflow);
     }
-    fn clc(&mut self, addressing_mode: AddressingMode) {
-
+    fn clc(&mut self) {
+        self.registers.status.carry = false;
     }
-    fn cld(&mut self, addressing_mode: AddressingMode) {
-
+    fn cld(&mut self) {
+        self.registers.status.decimal = false;
     }
-    fn cli(&mut self, addressing_mode: AddressingMode) {
-
+    fn cli(&mut self) {
+        self.registers.status.interrupt_disable = false;
     }
-    fn clv(&mut self, addressing_mode: AddressingMode) {
-
+    fn clv(&mut self) {
+        self.registers.status.overflow = false;
     }
     fn cmp(&mut self, addressing_mode: AddressingMode) {
-
+        self.compare(self.registers.a, addressing_mode);
     }
     fn cpx(&mut self, addressing_mode: AddressingMode) {
-
+        self.compare(self.registers.x, addressing_mode);
     }
     fn cpy(&mut self, addressing_mode: AddressingMode) {
-
+        self.compare(self.registers.y, addressing_mode);
     }
     fn dec(&mut self, addressing_mode: AddressingMode) {
-
+        self.modify(addressing_mode, |cpu, data| {
+            let result = data.wrapping_sub(1);
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
     }
-    fn dex(&mut self, addressing_mode: AddressingMode) {
-
+    fn dex(&mut self) {
+        self.registers.x = self.registers.x.wrapping_sub(1);
+        self.update_zero_and_negative_flags(self.registers.x);
     }
-    fn dey(&mut self, addressing_mode: AddressingMode) {
-
+    fn dey(&mut self) {
+        self.registers.y = self.registers.y.wrapping_sub(1);
+        self.update_zero_and_negative_flags(self.registers.y);
     }
     fn eor(&mut self, addressing_mode: AddressingMode) {
-
+        self.registers.a ^= self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.a);
     }
     fn inc(&mut self, addressing_mode: AddressingMode) {
-
+        self.modify(addressing_mode, |cpu, data| {
+            let result = data.wrapping_add(1);
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
     }
-    fn inx(&mut self, addressing_mode: AddressingMode) {
-
+    fn inx(&mut self) {
+        self.registers.x = self.registers.x.wrapping_add(1);
+        self.update_zero_and_negative_flags(self.registers.x);
     }
-    fn iny(&mut self, addressing_mode: AddressingMode) {
-
+    fn iny(&mut self) {
+        self.registers.y = self.registers.y.wrapping_add(1);
+        self.update_zero_and_negative_flags(self.registers.y);
     }
     fn jmp(&mut self, addressing_mode: AddressingMode) {
-
+        let (address, _) = self.fetch_address(addressing_mode);
+        self.registers.program_counter = address;
     }
-    fn jsr(&mut self, addressing_mode: AddressingMode) {
-
+    fn jsr(&mut self) {
+        let lo = self.fetch_byte() as u16;
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        // The pushed return address is the last byte of the JSR, RTS adds the missing one
+        self.stack_push_u16(self.registers.program_counter);
+        let hi = self.mem_read(self.registers.program_counter) as u16;
+        self.registers.program_counter = (hi << 8) | lo;
     }
     fn lda(&mut self, addressing_mode: AddressingMode) {
-        let addr = self.fetch_address(addressing_mode);
-        let load = self.mem_read(addr);
-        self.registers.program_counter += 1;
-        self.registers.a = load;
-
-        // when a is zero set the zero register (otherwise it will be reset)
-        self.registers.status.zero = self.registers.a == 0;
-
-        // if a is "negative", set the negative register (otherwise it will be reset)
-        self.registers.status.negative = self.registers.a.negative_set();
+        self.registers.a = self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.a);
     }
     fn ldx(&mut self, addressing_mode: AddressingMode) {
-
+        self.registers.x = self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.x);
     }
     fn ldy(&mut self, addressing_mode: AddressingMode) {
-
+        self.registers.y = self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.y);
     }
     fn lsr(&mut self, addressing_mode: AddressingMode) {
-
+        self.shift(addressing_mode, |cpu, data| {
+            cpu.registers.status.carry = data & 1 != 0;
+            let result = data >> 1;
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
     }
     fn nop(&mut self, addressing_mode: AddressingMode) {
-
+        // The unofficial NOPs with an operand still read it
+        if !matches!(addressing_mode, AddressingMode::Implied) {
+            self.read_operand(addressing_mode);
+        }
     }
     fn ora(&mut self, addressing_mode: AddressingMode) {
-
+        self.registers.a |= self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.a);
     }
-    fn pha(&mut self, addressing_mode: AddressingMode) {
-
+    fn pha(&mut self) {
+        self.stack_push(self.registers.a);
     }
-    fn php(&mut self, addressing_mode: AddressingMode) {
-
+    fn php(&mut self) {
+        // PHP always pushes with B and bit 5 set
+        self.stack_push(self.registers.status.bits() | 0b0011_0000);
     }
-    fn pla(&mut self, addressing_mode: AddressingMode) {
-
+    fn pla(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        self.registers.a = self.stack_pop();
+        self.update_zero_and_negative_flags(self.registers.a);
     }
-    fn plp(&mut self, addressing_mode: AddressingMode) {
-
+    fn plp(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        let status = self.stack_pop();
+        self.registers.status.set_bits(status);
     }
     fn rol(&mut self, addressing_mode: AddressingMode) {
-
+        self.shift(addressing_mode, |cpu, data| {
+            let result = (data << 1) | cpu.registers.status.carry as u8;
+            cpu.registers.status.carry = data.negative_set();
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
     }
     fn ror(&mut self, addressing_mode: AddressingMode) {
-
-    }
-    fn rti(&mut self, addressing_mode: AddressingMode) {
-
-    }
-    fn rts(&mut self, addressing_mode: AddressingMode) {
-
+        self.shift(addressing_mode, |cpu, data| {
+            let result = (data >> 1) | (cpu.registers.status.carry as u8) << 7;
+            cpu.registers.status.carry = data & 1 != 0;
+            cpu.update_zero_and_negative_flags(result);
+            result
+        });
+    }
+    fn rti(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        let status = self.stack_pop();
+        self.registers.status.set_bits(status);
+        self.registers.program_counter = self.stack_pop_u16();
+    }
+    fn rts(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        self.registers.program_counter = self.stack_pop_u16();
+        self.dummy_read(self.registers.program_counter);
+        self.registers.program_counter = self.registers.program_counter.wrapping_add(1);
     }
     fn sbc(&mut self, addressing_mode: AddressingMode) {
-
+        let data = self.read_operand(addressing_mode);
+        self.subtract_with_borrow(data);
     }
-    fn sec(&mut self, addressing_mode: AddressingMode) {
-
+    fn sec(&mut self) {
+        self.registers.status.carry = true;
     }
-    fn sed(&mut self, addressing_mode: AddressingMode) {
-
+    fn sed(&mut self) {
+        self.registers.status.decimal = true;
     }
-    fn sei(&mut self, addressing_mode: AddressingMode) {
-
+    fn sei(&mut self) {
+        self.registers.status.interrupt_disable = true;
     }
     fn sta(&mut self, addressing_mode: AddressingMode) {
-        let store_at = self.fetch_address(addressing_mode);
-        self.registers.program_counter += 1;
+        let store_at = self.write_address(addressing_mode);
         self.mem_write(store_at, self.registers.a);
     }
     fn stx(&mut self, addressing_mode: AddressingMode) {
-
+        let store_at = self.write_address(addressing_mode);
+        self.mem_write(store_at, self.registers.x);
     }
     fn sty(&mut self, addressing_mode: AddressingMode) {
-
+        let store_at = self.write_address(addressing_mode);
+        self.mem_write(store_at, self.registers.y);
     }
-    fn tax(&mut self, addressing_mode: AddressingMode) {
-
+    fn tax(&mut self) {
+        self.registers.x = self.registers.a;
+        self.update_zero_and_negative_flags(self.registers.x);
     }
-    fn tay(&mut self, addressing_mode: AddressingMode) {
-
+    fn tay(&mut self) {
+        self.registers.y = self.registers.a;
+        self.update_zero_and_negative_flags(self.registers.y);
     }
-    fn tsx(&mut self, addressing_mode: AddressingMode) {
-
+    fn tsx(&mut self) {
+        self.registers.x = self.registers.stack_pointer;
+        self.update_zero_and_negative_flags(self.registers.x);
     }
-    fn txa(&mut self, addressing_mode: AddressingMode) {
-
+    fn txa(&mut self) {
+        self.registers.a = self.registers.x;
+        self.update_zero_and_negative_flags(self.registers.a);
     }
-    fn txs(&mut self, addressing_mode: AddressingMode) {
-
+    fn txs(&mut self) {
+        self.registers.stack_pointer = self.registers.x;
+    }
+    fn tya(&mut self) {
+        self.registers.a = self.registers.y;
+        self.update_zero_and_negative_flags(self.registers.a);
     }
-    fn tya(&mut self, addressing_mode: AddressingMode) {
 
+    fn alr(&mut self, addressing_mode: AddressingMode) {
+        let data = self.registers.a & self.read_operand(addressing_mode);
+        self.registers.status.carry = data & 1 != 0;
+        self.registers.a = data >> 1;
+        self.update_zero_and_negative_flags(self.registers.a);
+    }
+    fn anc(&mut self, addressing_mode: AddressingMode) {
+        self.registers.a &= self.read_operand(addressing_mode);
+        self.update_zero_and_negative_flags(self.registers.a);
+        self.registers.status.carry = self.registers.a.negative_set();
+    }
+    fn arr(&mut self, addressing_mode: AddressingMode) {
+        let data = self.registers.a & self.read_operand(addressing_mode);
+        let result = (data >> 1) | (self.registers.status.carry as u8) << 7;
+        self.registers.a = result;
+        self.update_zero_and_negative_flags(result);
+        if self.decimal_mode() {
+            // N and Z are from the rotated value, V is whether the rotate changed bit 6, then each nibble gets
+            // the BCD fix up based on the value before rotating and the high one's sets C
+            self.registers.status.overflow = (data ^ result) & 0b0100_0000 != 0;
+            let mut result = result;
+            if (data & 0x0F) + (data & 0x01) > 0x05 {
+                result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+            }
+            self.registers.status.carry = (data & 0xF0) as u16 + (data & 0x10) as u16 > 0x50;
+            if self.registers.status.carry {
+                result = result.wrapping_add(0x60);
+            }
+            self.registers.a = result;
+            return;
+        }
+        // The flags come out of the adder, C is bit 6 and V is bit 6 XOR bit 5
+        self.registers.status.carry = result & 0b0100_0000 != 0;
+        self.registers.status.overflow = ((result >> 6) ^ (result >> 5)) & 1 != 0;
+    }
+    fn dcp(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            let result = data.wrapping_sub(1);
+            cpu.registers.status.carry = cpu.registers.a >= result;
+            cpu.update_zero_and_negative_flags(cpu.registers.a.wrapping_sub(result));
+            result
+        });
+    }
+    fn isc(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            let result = data.wrapping_add(1);
+            cpu.subtract_with_borrow(result);
+            result
+        });
+    }
+    fn las(&mut self, addressing_mode: AddressingMode) {
+        let data = self.read_operand(addressing_mode) & self.registers.stack_pointer;
+        self.registers.a = data;
+        self.registers.x = data;
+        self.registers.stack_pointer = data;
+        self.update_zero_and_negative_flags(data);
+    }
+    fn lax(&mut self, addressing_mode: AddressingMode) {
+        let data = self.read_operand(addressing_mode);
+        self.registers.a = data;
+        self.registers.x = data;
+        self.update_zero_and_negative_flags(data);
+    }
+    fn lxa(&mut self, addressing_mode: AddressingMode) {
+        let data = (self.registers.a | self.magic.lxa) & self.read_operand(addressing_mode);
+        self.registers.a = data;
+        self.registers.x = data;
+        self.update_zero_and_negative_flags(data);
+    }
+    fn rla(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            let result = (data << 1) | cpu.registers.status.carry as u8;
+            cpu.registers.status.carry = data.negative_set();
+            cpu.registers.a &= result;
+            cpu.update_zero_and_negative_flags(cpu.registers.a);
+            result
+        });
+    }
+    fn rra(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            let result = (data >> 1) | (cpu.registers.status.carry as u8) << 7;
+            cpu.registers.status.carry = data & 1 != 0;
+            cpu.add_with_carry(result);
+            result
+        });
+    }
+    fn sax(&mut self, addressing_mode: AddressingMode) {
+        let store_at = self.write_address(addressing_mode);
+        self.mem_write(store_at, self.registers.a & self.registers.x);
+    }
+    fn sbx(&mut self, addressing_mode: AddressingMode) {
+        // A compare, so there's no borrow in and V is left alone
+        let data = self.read_operand(addressing_mode);
+        let a_and_x = self.registers.a & self.registers.x;
+        self.registers.status.carry = a_and_x >= data;
+        self.registers.x = a_and_x.wrapping_sub(data);
+        self.update_zero_and_negative_flags(self.registers.x);
+    }
+    fn slo(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            cpu.registers.status.carry = data.negative_set();
+            let result = data << 1;
+            cpu.registers.a |= result;
+            cpu.update_zero_and_negative_flags(cpu.registers.a);
+            result
+        });
+    }
+    fn sre(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            cpu.registers.status.carry = data & 1 != 0;
+            let result = data >> 1;
+            cpu.registers.a ^= result;
+            cpu.update_zero_and_negative_flags(cpu.registers.a);
+            result
+        });
+    }
+    fn tas(&mut self, addressing_mode: AddressingMode) {
+        self.registers.stack_pointer = self.registers.a & self.registers.x;
+        self.store_and_high(addressing_mode, self.registers.stack_pointer);
+    }
+    fn plx(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        self.registers.x = self.stack_pop();
+        self.update_zero_and_negative_flags(self.registers.x);
+    }
+    fn ply(&mut self) {
+        self.dummy_read(STACK | self.registers.stack_pointer as u16);
+        self.registers.y = self.stack_pop();
+        self.update_zero_and_negative_flags(self.registers.y);
+    }
+    fn stz(&mut self, addressing_mode: AddressingMode) {
+        let store_at = self.write_address(addressing_mode);
+        self.mem_write(store_at, 0);
+    }
+    fn trb(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            cpu.registers.status.zero = cpu.registers.a & data == 0;
+            data & !cpu.registers.a
+        });
+    }
+    fn tsb(&mut self, addressing_mode: AddressingMode) {
+        self.modify(addressing_mode, |cpu, data| {
+            cpu.registers.status.zero = cpu.registers.a & data == 0;
+            data | cpu.registers.a
+        });
+    }
+    fn wai(&mut self) {
+        self.dummy_read(self.registers.program_counter);
+        self.waiting = true;
+    }
+    fn xaa(&mut self, addressing_mode: AddressingMode) {
+        let data = self.read_operand(addressing_mode);
+        self.registers.a = (self.registers.a | self.magic.xaa) & self.registers.x & data;
+        self.update_zero_and_negative_flags(self.registers.a);
     }
 
 }
@@ -0,0 +1,598 @@
+//! A command line debugger that runs the CPU one instruction at a time
+
+pub mod breakpoint;
+pub mod expression;
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use self::{
+    breakpoint::{Accesses, Breakpoint, Breakpoints, Kind, Space},
+    expression::Expression,
+};
+use crate::{
+    bus::Bus,
+    cpu::Cpu,
+    disasm::Instruction,
+    opcodes::{Opcode, OpcodeName},
+    watch::Interrupt,
+};
+
+const HELP: &str = "\
+s, step [n]            run n instructions, 1 if not given
+n, next                step, running subroutine calls through to their return
+c, continue [n]        run until BRK, a jam, n instructions or Ctrl-C
+fin, finish            run until the current subroutine returns
+r, regs                show the registers
+m, mem <addr> [len]    dump memory, -- where reading would have side effects
+w, write <addr> <byte>...
+                       change memory
+d, dis [addr] [n]      disassemble n instructions from addr, the PC if not given
+set <reg> <value>      set a, x, y, p, sp or pc
+reset                  run the reset sequence
+b, break <addr>        stop before the instruction at addr runs
+watch [ppu] <addr>[-<end>] [r|w|x...]
+                       stop when the range is read, written or executed, rw if not given. PPU watchpoints see
+                       accesses through $2007.
+catch <nmi|irq|brk>    stop at the start of the interrupt handler
+catch op <opcode>      stop before an instruction with the mnemonic or opcode byte runs
+cond <id> [<cond>]     change or remove a breakpoint's condition
+bl, breakpoints        list breakpoints
+del, delete [id]       delete a breakpoint, or all of them
+p, print <expr>        evaluate an expression
+q, quit
+Numbers are hexadecimal, with or without $, except in expressions. An empty line repeats the last command.
+
+break, watch and catch take an optional `if <expr>` at the end to stop only when the expression is non-zero.
+Expressions are written like `[$00FE] == 3 && a > $10`, the same way as the assembler's: decimal, $hex or
+%binary numbers, C's operators except % and < and > for the low and high byte. They can use the registers a, x,
+y, sp, pc (or *) and p, the flags n, v, d, i, z and c, `hits` for the number of times the breakpoint has been
+reached, `cycles` and memory as [address].";
+
+/// What the REPL should do after a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+#[derive(Debug, Default)]
+pub struct Debugger {
+    /// Repeated when an empty line is entered
+    last_command: String,
+    pub breakpoints: Breakpoints,
+    /// Set from outside, usually a SIGINT handler, to stop whatever command is running
+    interrupt: Arc<AtomicBool>,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A flag that stops the running command after the current instruction when it is set, for a Ctrl-C handler
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.interrupt.clone()
+    }
+
+    /// Reads commands from `input` until it ends or `quit` is entered
+    pub fn run<B: Bus>(&mut self, cpu: &mut Cpu<B>, mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        writeln!(output, "{}", location(cpu))?;
+        loop {
+            write!(output, "(rnes) ")?;
+            output.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let mut text = String::new();
+            let flow = self.execute(cpu, &line, &mut text);
+            output.write_all(text.as_bytes())?;
+            if flow == Flow::Quit {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs one command line, appending what it prints to `output`
+    pub fn execute<B: Bus>(&mut self, cpu: &mut Cpu<B>, line: &str, output: &mut String) -> Flow {
+        let line = match line.trim() {
+            "" => self.last_command.clone(),
+            line => line.to_string(),
+        };
+        self.last_command.clone_from(&line);
+        // An interrupt that came while waiting at the prompt isn't meant for this command
+        self.interrupt.store(false, Ordering::Relaxed);
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match self.command(cpu, &words, output) {
+            Ok(flow) => flow,
+            Err(message) => {
+                let _ = writeln!(output, "{message}");
+                Flow::Continue
+            }
+        }
+    }
+
+    fn command<B: Bus>(&mut self, cpu: &mut Cpu<B>, words: &[&str], output: &mut String) -> Result<Flow, String> {
+        let Some((&name, arguments)) = words.split_first() else {
+            return Ok(Flow::Continue);
+        };
+        let argument = |index: usize| arguments.get(index).map(|text| parse_number(text)).transpose();
+        match name {
+            "s" | "step" => {
+                let count = argument(0)?.unwrap_or(1);
+                let stop = (0..count).find_map(|_| self.step(cpu).err());
+                report(cpu, stop, output);
+            }
+            "n" | "next" => {
+                let stop = match instruction_at(cpu, cpu.registers.program_counter) {
+                    Some(instruction) if matches!(instruction.mnemonic(), OpcodeName::JSR) => {
+                        let stack_pointer = cpu.registers.stack_pointer;
+                        let return_address = instruction.next_address();
+                        self.run_until(cpu, |cpu, _| {
+                            cpu.registers.program_counter == return_address
+                                && cpu.registers.stack_pointer == stack_pointer
+                        })
+                    }
+                    _ => self.step(cpu).err(),
+                };
+                report(cpu, stop, output);
+            }
+            "c" | "continue" => {
+                let stop = match argument(0)? {
+                    Some(count) => (0..count).find_map(|_| self.step(cpu).err()),
+                    None => self.run_until(cpu, |_, _| false),
+                };
+                report(cpu, stop, output);
+            }
+            "fin" | "finish" => {
+                let stack_pointer = cpu.registers.stack_pointer;
+                let stop = self.run_until(cpu, |cpu, opcode| {
+                    matches!(opcode.name(), OpcodeName::RTS | OpcodeName::RTI)
+                        && cpu.registers.stack_pointer > stack_pointer
+                });
+                report(cpu, stop, output);
+            }
+            "r" | "regs" => {
+                let _ = writeln!(output, "{}", registers(cpu));
+            }
+            "m" | "mem" => {
+                let start = argument(0)?.ok_or("mem needs an address")?;
+                let length = argument(1)?.unwrap_or(0x40);
+                for row in (0..length).step_by(16) {
+                    let address = start.wrapping_add(row);
+                    let _ = write!(output, "{address:04X}:");
+                    for column in 0..16.min(length - row) {
+                        let _ = match cpu.bus.peek(address.wrapping_add(column)) {
+                            Some(data) => write!(output, " {data:02X}"),
+                            None => write!(output, " --"),
+                        };
+                    }
+                    output.push('\n');
+                }
+            }
+            "w" | "write" => {
+                let address = argument(0)?.ok_or("write needs an address")?;
+                if arguments.len() < 2 {
+                    return Err("write needs at least one byte".to_string());
+                }
+                let bytes = arguments[1..].iter().map(|text| parse_byte(text)).collect::<Result<Vec<u8>, String>>()?;
+                for (offset, data) in bytes.into_iter().enumerate() {
+                    cpu.bus.poke(address.wrapping_add(offset as u16), data);
+                }
+            }
+            "d" | "dis" => {
+                let mut address = argument(0)?.unwrap_or(cpu.registers.program_counter);
+                for _ in 0..argument(1)?.unwrap_or(10) {
+                    let Some(instruction) = instruction_at(cpu, address) else {
+                        let _ = writeln!(output, "{address:04X}  can't be read without side effects");
+                        break;
+                    };
+                    let marker = if address == cpu.registers.program_counter { '>' } else { ' ' };
+                    let _ = writeln!(output, "{marker} {}", listing(&instruction));
+                    address = instruction.next_address();
+                }
+            }
+            "set" => {
+                let [register, value] = arguments else {
+                    return Err("usage: set <a|x|y|p|sp|pc> <value>".to_string());
+                };
+                let cpu_registers = &mut cpu.registers;
+                match register.to_ascii_lowercase().as_str() {
+                    "pc" => cpu_registers.program_counter = parse_number(value)?,
+                    "a" => cpu_registers.a = parse_byte(value)?,
+                    "x" => cpu_registers.x = parse_byte(value)?,
+                    "y" => cpu_registers.y = parse_byte(value)?,
+                    "sp" => cpu_registers.stack_pointer = parse_byte(value)?,
+                    "p" => cpu_registers.status.set_bits(parse_byte(value)?),
+                    _ => return Err(format!("no register {register}")),
+                }
+                let _ = writeln!(output, "{}", registers(cpu));
+            }
+            "reset" => {
+                cpu.reset();
+                let _ = writeln!(output, "{}", location(cpu));
+            }
+            "b" | "break" => {
+                let (words, condition) = split_condition(arguments)?;
+                let [address] = words else {
+                    return Err("usage: break <addr> [if <condition>]".to_string());
+                };
+                self.add(cpu, Kind::Address(parse_number(address)?), condition, output);
+            }
+            "watch" => {
+                let usage = "usage: watch [ppu] <addr>[-<end>] [r|w|x...] [if <condition>]";
+                let (words, condition) = split_condition(arguments)?;
+                let (space, words) = match words.split_first() {
+                    Some((&"ppu", rest)) => (Space::Ppu, rest),
+                    _ => (Space::Cpu, words),
+                };
+                let (range, accesses) = match words {
+                    [range] => (range, "rw"),
+                    [range, accesses] => (range, *accesses),
+                    _ => return Err(usage.to_string()),
+                };
+                if accesses.is_empty() || !accesses.chars().all(|c| "rwx".contains(c)) {
+                    return Err(usage.to_string());
+                }
+                let accesses = Accesses {
+                    read: accesses.contains('r'),
+                    write: accesses.contains('w'),
+                    execute: accesses.contains('x'),
+                };
+                if space == Space::Ppu && accesses.execute {
+                    return Err("the PPU doesn't execute anything".to_string());
+                }
+                if space == Space::Ppu && cpu.bus.ppu_watch().is_none() {
+                    return Err("there's no PPU on this bus".to_string());
+                }
+                let (start, end) = match range.split_once('-') {
+                    Some((start, end)) => (parse_number(start)?, parse_number(end)?),
+                    None => (parse_number(range)?, parse_number(range)?),
+                };
+                if start > end {
+                    return Err(format!("{range} ends before it starts"));
+                }
+                self.add(cpu, Kind::Watch { space, range: start..=end, accesses }, condition, output);
+            }
+            "catch" => {
+                let usage = "usage: catch <nmi|irq|brk|op <mnemonic|opcode>> [if <condition>]";
+                let (words, condition) = split_condition(arguments)?;
+                let kind = match words {
+                    [event] => match event.to_ascii_lowercase().as_str() {
+                        "nmi" => Kind::Interrupt(Interrupt::Nmi),
+                        "irq" => Kind::Interrupt(Interrupt::Irq),
+                        "brk" => Kind::Interrupt(Interrupt::Brk),
+                        _ => return Err(usage.to_string()),
+                    },
+                    ["op", opcode] => {
+                        let opcodes: Vec<u8> = (0..=0xFF)
+                            .filter(|&byte| {
+                                let name = cpu.variant.opcodes()[byte as usize].name().to_string();
+                                name.eq_ignore_ascii_case(opcode)
+                            })
+                            .collect();
+                        if opcodes.is_empty() {
+                            let byte = parse_byte(opcode).map_err(|_| format!("no opcode {opcode}"))?;
+                            Kind::Opcode { name: format!("${byte:02X}"), opcodes: vec![byte] }
+                        } else {
+                            Kind::Opcode { name: opcode.to_ascii_uppercase(), opcodes }
+                        }
+                    }
+                    _ => return Err(usage.to_string()),
+                };
+                self.add(cpu, kind, condition, output);
+            }
+            "cond" => {
+                let [id, condition @ ..] = arguments else {
+                    return Err("usage: cond <id> [<condition>]".to_string());
+                };
+                let condition = match condition {
+                    [] => None,
+                    _ => Some(Expression::parse(&condition.join(" "))?),
+                };
+                let breakpoint = self.breakpoint(id)?;
+                breakpoint.condition = condition;
+                let _ = writeln!(output, "{breakpoint}");
+            }
+            "bl" | "breakpoints" => {
+                for breakpoint in self.breakpoints.iter() {
+                    let _ = writeln!(output, "{breakpoint}");
+                }
+            }
+            "del" | "delete" => {
+                match arguments {
+                    [] => self.breakpoints.clear(),
+                    [id] => {
+                        let id = self.breakpoint(id)?.id;
+                        self.breakpoints.remove(id);
+                    }
+                    _ => return Err("usage: delete [<id>]".to_string()),
+                }
+                self.breakpoints.arm(cpu);
+            }
+            "p" | "print" => {
+                let value = Expression::parse(&arguments.join(" "))?.evaluate(cpu, 0)?;
+                let _ = match value {
+                    0.. => writeln!(output, "{value} ${value:X}"),
+                    _ => writeln!(output, "{value}"),
+                };
+            }
+            "h" | "help" => {
+                let _ = writeln!(output, "{HELP}");
+            }
+            "q" | "quit" => return Ok(Flow::Quit),
+            _ => return Err(format!("unknown command {name}, try help")),
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Runs one instruction, failing with the reason if it stopped the program or a breakpoint stops before the
+    /// next one
+    fn step<B: Bus>(&mut self, cpu: &mut Cpu<B>) -> Result<Opcode, String> {
+        let address = cpu.registers.program_counter;
+        self.breakpoints.forget_events(cpu);
+        let opcode = cpu.step();
+        if let Some(stop) = self.breakpoints.after(cpu) {
+            return Err(stop);
+        }
+        if let Some(jam) = cpu.jam() {
+            return Err(jam.to_string());
+        }
+        if let OpcodeName::BRK = opcode.name() {
+            return Err(format!("BRK at ${address:04X}"));
+        }
+        if let Some(stop) = self.breakpoints.before(cpu) {
+            return Err(stop);
+        }
+        if self.interrupt.swap(false, Ordering::Relaxed) {
+            return Err("interrupted".to_string());
+        }
+        Ok(opcode)
+    }
+
+    /// Steps until `done` is true after an instruction, returning why it stopped if it was something else
+    fn run_until<B: Bus>(&mut self, cpu: &mut Cpu<B>, done: impl Fn(&Cpu<B>, Opcode) -> bool) -> Option<String> {
+        loop {
+            match self.step(cpu) {
+                Ok(opcode) if done(cpu, opcode) => return None,
+                Ok(_) => {}
+                Err(reason) => return Some(reason),
+            }
+        }
+    }
+
+    fn add<B: Bus>(&mut self, cpu: &mut Cpu<B>, kind: Kind, condition: Option<Expression>, output: &mut String) {
+        let id = self.breakpoints.add(kind, condition);
+        self.breakpoints.arm(cpu);
+        if let Some(breakpoint) = self.breakpoints.iter().find(|breakpoint| breakpoint.id == id) {
+            let _ = writeln!(output, "{breakpoint}");
+        }
+    }
+
+    fn breakpoint(&mut self, id: &str) -> Result<&mut Breakpoint, String> {
+        let number = id.parse().map_err(|_| format!("bad breakpoint number {id}"))?;
+        self.breakpoints.get_mut(number).ok_or_else(|| format!("no breakpoint {id}"))
+    }
+}
+
+/// Splits `... if <condition>` into the words before the `if` and the condition
+fn split_condition<'a>(words: &'a [&'a str]) -> Result<(&'a [&'a str], Option<Expression>), String> {
+    match words.iter().position(|&word| word == "if") {
+        Some(index) => Ok((&words[..index], Some(Expression::parse(&words[index + 1..].join(" "))?))),
+        None => Ok((words, None)),
+    }
+}
+
+fn report<B: Bus>(cpu: &Cpu<B>, stop: Option<String>, output: &mut String) {
+    if let Some(reason) = stop {
+        let _ = writeln!(output, "{reason}");
+    }
+    let _ = writeln!(output, "{}", location(cpu));
+}
+
+fn instruction_at<B: Bus>(cpu: &Cpu<B>, address: u16) -> Option<Instruction> {
+    let opcode = cpu.bus.peek(address)?;
+    let length = cpu.variant.opcodes()[opcode as usize].length();
+    let bytes = (0..length).map(|offset| cpu.bus.peek(address.wrapping_add(offset))).collect::<Option<Vec<u8>>>()?;
+    Instruction::decode(&bytes, address, cpu.variant)
+}
+
+/// `8000  A9 42     LDA #$42`
+fn listing(instruction: &Instruction) -> String {
+    let bytes: Vec<String> = instruction.bytes.iter().map(|byte| format!("{byte:02X}")).collect();
+    format!("{:04X}  {:<8}  {instruction}", instruction.address, bytes.join(" "))
+}
+
+/// The instruction about to run
+fn location<B: Bus>(cpu: &Cpu<B>) -> String {
+    let address = cpu.registers.program_counter;
+    instruction_at(cpu, address).map_or_else(|| format!("{address:04X}  ??"), |instruction| listing(&instruction))
+}
+
+fn registers<B: Bus>(cpu: &Cpu<B>) -> String {
+    let registers = &cpu.registers;
+    let bits = registers.status.bits();
+    let flags: String = "NV-BDIZC"
+        .chars()
+        .enumerate()
+        .map(|(index, flag)| if bits & 0x80 >> index != 0 { flag } else { flag.to_ascii_lowercase() })
+        .collect();
+    format!(
+        "PC={:04X} A={:02X} X={:02X} Y={:02X} SP={:02X} P={bits:02X} {flags} cycles={}",
+        registers.program_counter, registers.a, registers.x, registers.y, registers.stack_pointer, cpu.cycles
+    )
+}
+
+/// Hexadecimal, optionally starting with $ or 0x
+fn parse_number(text: &str) -> Result<u16, String> {
+    let digits = text.strip_prefix('$').or_else(|| text.strip_prefix("0x")).unwrap_or(text);
+    u16::from_str_radix(digits, 16).map_err(|_| format!("bad number {text}"))
+}
+
+fn parse_byte(text: &str) -> Result<u8, String> {
+    u8::try_from(parse_number(text)?).map_err(|_| format!("{text} doesn't fit in a byte"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{asm::assemble, bus::NesBus, cpu::CpuVariant, ppu::Mirroring, test_rom::TestRom};
+
+    fn cpu(source: &str) -> Cpu {
+        let program = assemble(&format!(".org $0600\n{source}"), CpuVariant::Nmos6502).unwrap();
+        let mut cpu = Cpu::new();
+        cpu.bus.load(program.origin, &program.bytes);
+        cpu.registers.program_counter = 0x0600;
+        cpu
+    }
+
+    fn execute<B: Bus>(debugger: &mut Debugger, cpu: &mut Cpu<B>, line: &str) -> String {
+        let mut output = String::new();
+        debugger.execute(cpu, line, &mut output);
+        output
+    }
+
+    #[test]
+    fn test_step_and_inspect() {
+        let mut cpu = cpu("LDA #$42\nSTA $10\nBRK");
+        let mut debugger = Debugger::new();
+        assert_eq!(execute(&mut debugger, &mut cpu, "step"), "0602  85 10     STA $10\n");
+        // An empty line steps again
+        assert_eq!(execute(&mut debugger, &mut cpu, ""), "0604  00        BRK\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "m 10 4"), "0010: 42 00 00 00\n");
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "r"),
+            "PC=0604 A=42 X=00 Y=00 SP=FD P=04 nv-bdIzc cycles=5\n"
+        );
+
+        execute(&mut debugger, &mut cpu, "w $11 A2 07");
+        execute(&mut debugger, &mut cpu, "set pc 0011");
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "dis 0010 2"),
+            "  0010  42        JAM\n> 0011  A2 07     LDX #$07\n"
+        );
+        assert_eq!(execute(&mut debugger, &mut cpu, "d 11 2"), "> 0011  A2 07     LDX #$07\n  0013  00        BRK\n");
+    }
+
+    #[test]
+    fn test_next_and_finish() {
+        let source = "
+                    JSR sub
+                    LDX #$01
+                    BRK
+            sub:    LDA #$42
+                    JSR inner
+                    RTS
+            inner:  RTS
+        ";
+        let mut cpu = cpu(source);
+        let mut debugger = Debugger::new();
+        assert_eq!(execute(&mut debugger, &mut cpu, "next"), "0603  A2 01     LDX #$01\n");
+        assert_eq!(cpu.registers.a, 0x42);
+
+        cpu.registers.program_counter = 0x0600;
+        execute(&mut debugger, &mut cpu, "s 2");
+        assert_eq!(execute(&mut debugger, &mut cpu, "fin"), "0603  A2 01     LDX #$01\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "c"), "BRK at $0605\n0000  00        BRK\n");
+
+        assert_eq!(execute(&mut debugger, &mut cpu, "frobnicate"), "unknown command frobnicate, try help\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "set q 1"), "no register q\n");
+        assert_eq!(debugger.execute(&mut cpu, "quit", &mut String::new()), Flow::Quit);
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let mut cpu = cpu("loop: JMP loop");
+        let mut debugger = Debugger::new();
+        let interrupt = debugger.interrupt_flag();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            interrupt.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(execute(&mut debugger, &mut cpu, "c"), "interrupted\n0600  4C 00 06  JMP $0600\n");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let source = "
+                    LDX #$00
+            loop:   INX
+                    STX $10
+                    CPX #$05
+                    BNE loop
+                    JSR sub
+                    BRK
+            sub:    LDA $10
+                    RTS
+        ";
+        let mut cpu = cpu(source);
+        let mut debugger = Debugger::new();
+        assert_eq!(execute(&mut debugger, &mut cpu, "b 602 if x == 3"), "1: break $0602 if x == 3 (hits 0)\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "c"), "breakpoint 1: reached $0602\n0602  E8        INX\n");
+        assert_eq!(cpu.registers.x, 3);
+
+        execute(&mut debugger, &mut cpu, "watch 10 w if [$10] == 5");
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "c"),
+            "breakpoint 2: write $05 to $0010\n0605  E0 05     CPX #$05\n"
+        );
+        execute(&mut debugger, &mut cpu, "catch op lda");
+        assert_eq!(execute(&mut debugger, &mut cpu, "c"), "breakpoint 3: LDA at $060D\n060D  A5 10     LDA $10\n");
+        execute(&mut debugger, &mut cpu, "watch 0-FF r");
+        assert_eq!(execute(&mut debugger, &mut cpu, "s"), "breakpoint 4: read $05 from $0010\n060F  60        RTS\n");
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "bl"),
+            "1: break $0602 if x == 3 (hits 5)\n2: watch $0010 w if [$10] == 5 (hits 2)\n3: catch op LDA (hits 1)\n\
+             4: watch $0000-$00FF r (hits 1)\n"
+        );
+
+        execute(&mut debugger, &mut cpu, "del 4");
+        assert_eq!(execute(&mut debugger, &mut cpu, "cond 1"), "1: break $0602 (hits 5)\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "p x + [$10] * 2"), "15 $F\n");
+        execute(&mut debugger, &mut cpu, "catch brk");
+        assert_eq!(execute(&mut debugger, &mut cpu, "c"), "breakpoint 5: BRK\n0000  00        BRK\n");
+
+        assert_eq!(execute(&mut debugger, &mut cpu, "watch ppu 2000"), "there's no PPU on this bus\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "del 9"), "no breakpoint 9\n");
+        assert_eq!(execute(&mut debugger, &mut cpu, "b 600 if q"), "unknown name q\n");
+        execute(&mut debugger, &mut cpu, "del");
+        assert!(cpu.watch.ranges.is_empty() && !cpu.watch.interrupts);
+    }
+
+    #[test]
+    fn test_ppu_watchpoint() {
+        let source = "
+                    .org $8000
+                    LDA #$20
+                    STA $2006
+                    LDA #$00
+                    STA $2006
+                    LDA #$42
+                    STA $2007
+            loop:   JMP loop
+        ";
+        let program = assemble(source, CpuVariant::Ricoh2A03).unwrap().bytes;
+        let prg_rom = TestRom::new(1, 0).code(0x8000, &program).vectors(0, 0x8000, 0).prg_rom;
+        let mut cpu = Cpu::with_bus(NesBus::new(prg_rom, Vec::new(), Mirroring::Horizontal));
+        cpu.reset();
+
+        let mut debugger = Debugger::new();
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "watch ppu 2000-23FF w"),
+            "1: watch ppu $2000-$23FF w (hits 0)\n"
+        );
+        assert_eq!(
+            execute(&mut debugger, &mut cpu, "c"),
+            "breakpoint 1: PPU write $42 to $2000\n800F  4C 0F 80  JMP $800F\n"
+        );
+        assert_eq!(execute(&mut debugger, &mut cpu, "watch ppu 2000 x"), "the PPU doesn't execute anything\n");
+    }
+}
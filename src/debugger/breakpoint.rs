@@ -0,0 +1,224 @@
+//! Breakpoints on addresses and opcodes, watchpoints on CPU and PPU memory and catchpoints on interrupts
+
+use std::{
+    fmt::{self, Display},
+    ops::RangeInclusive,
+};
+
+use super::expression::Expression;
+use crate::{
+    bus::Bus,
+    cpu::Cpu,
+    watch::{Access, Event, Interrupt, Watch},
+};
+
+/// Which address space a watchpoint is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Space {
+    Cpu,
+    Ppu,
+}
+
+/// The kinds of access a watchpoint stops on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accesses {
+    pub read: bool,
+    pub write: bool,
+    /// Only for the CPU, stops before an instruction in the range runs
+    pub execute: bool,
+}
+
+impl Display for Accesses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (set, letter) in [(self.read, 'r'), (self.write, 'w'), (self.execute, 'x')] {
+            if set {
+                write!(f, "{letter}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// Stops before the instruction at the address runs
+    Address(u16),
+    Watch { space: Space, range: RangeInclusive<u16>, accesses: Accesses },
+    /// Stops at the start of the handler
+    Interrupt(Interrupt),
+    /// Stops before any instruction with one of `opcodes` runs
+    Opcode { name: String, opcodes: Vec<u8> },
+}
+
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub kind: Kind,
+    /// Must be true, non-zero, for the breakpoint to stop
+    pub condition: Option<Expression>,
+    /// Times it was reached, whether the condition held or not
+    pub hits: u32,
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.id)?;
+        match &self.kind {
+            Kind::Address(address) => write!(f, "break ${address:04X}")?,
+            Kind::Watch { space, range, accesses } => {
+                let space = if *space == Space::Ppu { "ppu " } else { "" };
+                write!(f, "watch {space}${:04X}", range.start())?;
+                if range.end() != range.start() {
+                    write!(f, "-${:04X}", range.end())?;
+                }
+                write!(f, " {accesses}")?;
+            }
+            Kind::Interrupt(interrupt) => write!(f, "catch {}", format!("{interrupt:?}").to_lowercase())?,
+            Kind::Opcode { name, .. } => write!(f, "catch op {name}")?,
+        }
+        if let Some(condition) = &self.condition {
+            write!(f, " if {condition}")?;
+        }
+        write!(f, " (hits {})", self.hits)
+    }
+}
+
+/// Every breakpoint the debugger has, checked around each instruction it runs
+#[derive(Debug, Default)]
+pub struct Breakpoints {
+    list: Vec<Breakpoint>,
+    next_id: usize,
+}
+
+impl Breakpoints {
+    /// Returns the new breakpoint's id. Call `arm` afterwards so the CPU records what it needs.
+    pub fn add(&mut self, kind: Kind, condition: Option<Expression>) -> usize {
+        self.next_id += 1;
+        self.list.push(Breakpoint { id: self.next_id, kind, condition, hits: 0 });
+        self.next_id
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Breakpoint> {
+        let index = self.list.iter().position(|breakpoint| breakpoint.id == id)?;
+        Some(self.list.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Breakpoint> {
+        self.list.iter_mut().find(|breakpoint| breakpoint.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.list.iter()
+    }
+
+    /// Sets up the CPU's and PPU's watches to record just what the watchpoints and catchpoints need, and
+    /// nothing at all when there are none
+    pub fn arm<B: Bus>(&self, cpu: &mut Cpu<B>) {
+        cpu.watch = self.watch(Space::Cpu);
+        if let Some(watch) = cpu.bus.ppu_watch() {
+            *watch = self.watch(Space::Ppu);
+        }
+    }
+
+    fn watch(&self, space: Space) -> Watch {
+        let mut watch = Watch::default();
+        for breakpoint in &self.list {
+            match &breakpoint.kind {
+                Kind::Watch { space: watched, range, accesses } if *watched == space => {
+                    for (set, access) in [(accesses.read, Access::Read), (accesses.write, Access::Write)] {
+                        if set {
+                            watch.ranges.push((range.clone(), access));
+                        }
+                    }
+                }
+                Kind::Interrupt(_) => watch.interrupts = space == Space::Cpu,
+                _ => {}
+            }
+        }
+        watch
+    }
+
+    /// Throws away anything the watches recorded outside of an instruction, like the debugger's own writes
+    pub fn forget_events<B: Bus>(&self, cpu: &mut Cpu<B>) {
+        cpu.watch.events.clear();
+        if let Some(watch) = cpu.bus.ppu_watch() {
+            watch.events.clear();
+        }
+    }
+
+    /// Checked with the CPU about to run the instruction at the PC. Returns why it should stop, if it should.
+    pub fn before<B: Bus>(&mut self, cpu: &Cpu<B>) -> Option<String> {
+        if self.list.is_empty() {
+            return None;
+        }
+        let address = cpu.registers.program_counter;
+        let opcode = cpu.bus.peek(address);
+        self.check(cpu, |kind| match kind {
+            Kind::Address(breakpoint) => (*breakpoint == address).then(|| format!("reached ${address:04X}")),
+            Kind::Watch { space: Space::Cpu, range, accesses } if accesses.execute && range.contains(&address) => {
+                Some(format!("executing ${address:04X}"))
+            }
+            Kind::Opcode { name, opcodes } if opcode.is_some_and(|opcode| opcodes.contains(&opcode)) => {
+                Some(format!("{name} at ${address:04X}"))
+            }
+            _ => None,
+        })
+    }
+
+    /// Checked once an instruction has run, with whatever the watches recorded during it
+    pub fn after<B: Bus>(&mut self, cpu: &mut Cpu<B>) -> Option<String> {
+        let mut events: Vec<(Space, Event)> = cpu.watch.events.drain(..).map(|event| (Space::Cpu, event)).collect();
+        if let Some(watch) = cpu.bus.ppu_watch() {
+            events.extend(watch.events.drain(..).map(|event| (Space::Ppu, event)));
+        }
+        let mut stop = None;
+        for (space, event) in events {
+            let found = self.check(cpu, |kind| match (kind, event) {
+                (
+                    Kind::Watch { space: watched, range, accesses },
+                    Event::Access { access, address, data },
+                ) if *watched == space && range.contains(&address) => {
+                    let (verb, preposition, set) = match access {
+                        Access::Read => ("read", "from", accesses.read),
+                        Access::Write => ("write", "to", accesses.write),
+                    };
+                    let space = if space == Space::Ppu { "PPU " } else { "" };
+                    set.then(|| format!("{space}{verb} ${data:02X} {preposition} ${address:04X}"))
+                }
+                (Kind::Interrupt(caught), Event::Interrupt(interrupt)) if *caught == interrupt => {
+                    Some(format!("{interrupt:?}").to_uppercase())
+                }
+                _ => None,
+            });
+            stop = stop.or(found);
+        }
+        stop
+    }
+
+    /// Counts a hit on every breakpoint `reached` describes a reason for, then returns the first of those whose
+    /// condition holds. A condition that can't be evaluated stops with the error.
+    fn check<B: Bus>(&mut self, cpu: &Cpu<B>, reached: impl Fn(&Kind) -> Option<String>) -> Option<String> {
+        let mut stop = None;
+        for breakpoint in &mut self.list {
+            let Some(reason) = reached(&breakpoint.kind) else {
+                continue;
+            };
+            breakpoint.hits += 1;
+            let reason = match &breakpoint.condition {
+                None => Some(reason),
+                Some(condition) => match condition.evaluate(cpu, breakpoint.hits) {
+                    Ok(value) => (value != 0).then_some(reason),
+                    Err(error) => Some(format!("{reason}, but the condition failed: {error}")),
+                },
+            };
+            if stop.is_none() {
+                stop = reason.map(|reason| format!("breakpoint {}: {reason}", breakpoint.id));
+            }
+        }
+        stop
+    }
+}
@@ -0,0 +1,141 @@
+//! Conditions for breakpoints, like `[$00FE] == 3 && A > $10`, in the assembler's expression language.
+//!
+//! The names are the registers `a`, `x`, `y`, `sp`, `pc` (also `*`) and `p`, the flags `n`, `v`, `d`, `i`, `z`
+//! and `c` as 0 or 1, `hits` for how many times the breakpoint has been reached and `cycles`, in either case.
+
+use std::fmt::{self, Display};
+
+use crate::{
+    bus::Bus,
+    cpu::Cpu,
+    expr::{Context, Expr, Parser},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Name {
+    A,
+    X,
+    Y,
+    StackPointer,
+    ProgramCounter,
+    Status,
+    /// A bit of the status register
+    Flag(u8),
+    Hits,
+    Cycles,
+}
+
+impl Name {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "a" => Name::A,
+            "x" => Name::X,
+            "y" => Name::Y,
+            "sp" => Name::StackPointer,
+            "pc" | "*" => Name::ProgramCounter,
+            "p" => Name::Status,
+            "n" => Name::Flag(7),
+            "v" => Name::Flag(6),
+            "d" => Name::Flag(3),
+            "i" => Name::Flag(2),
+            "z" => Name::Flag(1),
+            "c" => Name::Flag(0),
+            "hits" => Name::Hits,
+            "cycles" => Name::Cycles,
+            _ => return None,
+        })
+    }
+}
+
+/// A parsed expression, which keeps its source text for showing back
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    text: String,
+    root: Expr,
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser::new(text)?;
+        let root = parser.expression()?;
+        parser.finish()?;
+        if let Some(name) = root.names().into_iter().find(|name| Name::parse(name).is_none()) {
+            return Err(format!("unknown name {name}"));
+        }
+        Ok(Self { text: text.trim().to_string(), root })
+    }
+
+    /// Fails on overflow, division by zero and memory that can't be read without side effects
+    pub fn evaluate<B: Bus>(&self, cpu: &Cpu<B>, hits: u32) -> Result<i64, String> {
+        self.root.evaluate(&Machine { cpu, hits })
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// The registers and memory of `cpu`, and the hit count of the breakpoint being checked
+struct Machine<'a, B: Bus> {
+    cpu: &'a Cpu<B>,
+    hits: u32,
+}
+
+impl<B: Bus> Context for Machine<'_, B> {
+    fn name(&self, name: &str) -> Result<i64, String> {
+        let registers = &self.cpu.registers;
+        Ok(match Name::parse(name).ok_or_else(|| format!("unknown name {name}"))? {
+            Name::A => registers.a as i64,
+            Name::X => registers.x as i64,
+            Name::Y => registers.y as i64,
+            Name::StackPointer => registers.stack_pointer as i64,
+            Name::ProgramCounter => registers.program_counter as i64,
+            Name::Status => registers.status.bits() as i64,
+            Name::Flag(bit) => (registers.status.bits() >> bit & 1) as i64,
+            Name::Hits => self.hits as i64,
+            Name::Cycles => self.cpu.cycles as i64,
+        })
+    }
+
+    fn memory(&self, address: u16) -> Result<i64, String> {
+        let data = self.cpu.bus.peek(address);
+        Ok(data.ok_or_else(|| format!("${address:04X} can't be read without side effects"))? as i64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn evaluate(text: &str, cpu: &Cpu) -> Result<i64, String> {
+        Expression::parse(text)?.evaluate(cpu, 2)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut cpu = Cpu::new();
+        cpu.registers.a = 0x20;
+        cpu.registers.status.carry = true;
+        cpu.bus.load(0x00FE, &[3]);
+
+        assert_eq!(evaluate("[$00FE] == 3 && A > $10", &cpu), Ok(1));
+        assert_eq!(evaluate("[$FE] == 3 && a > $20", &cpu), Ok(0));
+        assert_eq!(evaluate("1 + 2 * 3 == 7 || [$FFFF] / 0", &cpu), Ok(1));
+        assert_eq!(evaluate("(1 + 2) * 3 << 1", &cpu), Ok(18));
+        assert_eq!(evaluate("C + Z + HITS + %101 - -1", &cpu), Ok(9));
+        assert_eq!(evaluate("!c | ~0 & $F0 ^ 1", &cpu), Ok(0xF1));
+        assert_eq!(evaluate("[pc + 1] != 0 && 5 / 3 >= 1", &cpu), Ok(0));
+        assert_eq!(evaluate(">$1234 + <$1234 == * + $46 - pc", &cpu), Ok(1));
+
+        assert_eq!(evaluate("a / [$FE - $FE]", &cpu), Err("division by zero".to_string()));
+        assert_eq!(evaluate("q == 1", &cpu), Err("unknown name q".to_string()));
+        assert_eq!(evaluate("(a == 1", &cpu), Err("expected ')'".to_string()));
+        assert_eq!(evaluate("a = 1", &cpu), Err("unexpected '='".to_string()));
+        assert!(evaluate("a 1", &cpu).is_err());
+        assert_eq!(evaluate("$7FFFFFFFFFFFFFFF + 1", &cpu), Err("overflow".to_string()));
+        assert_eq!(evaluate("1 << 64", &cpu), Err("can't shift by 64".to_string()));
+        assert_eq!(evaluate("[-1]", &cpu), Err("-1 isn't an address".to_string()));
+    }
+}
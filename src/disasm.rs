@@ -0,0 +1,160 @@
+//! Turning machine code back into assembly, in ca65 syntax
+
+use std::fmt::{self, Display};
+
+use crate::{
+    cpu::CpuVariant,
+    opcodes::{AddressingMode, Opcode, OpcodeName},
+};
+
+/// One decoded instruction
+#[derive(Clone)]
+pub struct Instruction {
+    pub address: u16,
+    pub opcode: Opcode,
+    /// The opcode byte followed by the operand, `opcode.length()` of them
+    pub bytes: Vec<u8>,
+}
+
+impl Instruction {
+    /// Decodes the instruction at the start of `memory`, which is at `address`. None if `memory` ends before the
+    /// instruction does.
+    pub fn decode(memory: &[u8], address: u16, variant: CpuVariant) -> Option<Self> {
+        let opcode = variant.opcodes()[*memory.first()? as usize];
+        let bytes = memory.get(..opcode.length() as usize)?.to_vec();
+        Some(Self { address, opcode, bytes })
+    }
+
+    pub fn mnemonic(&self) -> OpcodeName {
+        self.opcode.name()
+    }
+
+    pub fn length(&self) -> u16 {
+        self.bytes.len() as u16
+    }
+
+    /// Where the next instruction in memory starts
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.length())
+    }
+
+    /// The operand as a little endian number, 0 if there is none
+    pub fn operand(&self) -> u16 {
+        match self.bytes[1..] {
+            [low] => low as u16,
+            [low, high] => u16::from_le_bytes([low, high]),
+            _ => 0,
+        }
+    }
+
+    /// Where a branch goes if taken
+    pub fn branch_target(&self) -> Option<u16> {
+        let offset = match self.opcode.addressing_mode() {
+            AddressingMode::Relative => self.bytes[1],
+            AddressingMode::ZeroPageRelative => self.bytes[2],
+            _ => return None,
+        };
+        Some(self.next_address().wrapping_add(offset as i8 as u16))
+    }
+
+    /// The operand the way ca65 spells it for this addressing mode, empty for implied instructions
+    pub fn formatted_operand(&self) -> String {
+        let operand = self.operand();
+        // ca65 picks zero page for any address that fits, `a:` keeps it absolute so the bytes come out the same
+        let absolute = if operand < 0x100 { format!("a:${operand:04X}") } else { format!("${operand:04X}") };
+        let target = self.branch_target().unwrap_or_default();
+        match self.opcode.addressing_mode() {
+            AddressingMode::Implied => String::new(),
+            AddressingMode::Accumulator => "A".to_string(),
+            AddressingMode::Immediate => format!("#${operand:02X}"),
+            AddressingMode::ZeroPage => format!("${operand:02X}"),
+            AddressingMode::ZeroPageX => format!("${operand:02X},X"),
+            AddressingMode::ZeroPageY => format!("${operand:02X},Y"),
+            AddressingMode::Relative => format!("${target:04X}"),
+            AddressingMode::Absolute => absolute,
+            AddressingMode::AbsoluteX => format!("{absolute},X"),
+            AddressingMode::AbsoluteY => format!("{absolute},Y"),
+            AddressingMode::Indirect => format!("(${operand:04X})"),
+            AddressingMode::IndexedIndirectX => format!("(${operand:02X},X)"),
+            AddressingMode::IndirectIndexedY => format!("(${operand:02X}),Y"),
+            AddressingMode::ZeroPageIndirect => format!("(${operand:02X})"),
+            AddressingMode::AbsoluteIndexedIndirect => format!("(${operand:04X},X)"),
+            AddressingMode::ZeroPageRelative => format!("${:02X},${target:04X}", self.bytes[1]),
+        }
+    }
+}
+
+/// The instruction as ca65 would assemble it, `LDA ($10),Y`
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = self.formatted_operand();
+        if operand.is_empty() {
+            write!(f, "{}", self.mnemonic())
+        } else {
+            write!(f, "{} {operand}", self.mnemonic())
+        }
+    }
+}
+
+/// Decodes all of `memory`, which starts at `address`, one instruction after another. Stops early if the last
+/// instruction would run past the end.
+pub fn disassemble(memory: &[u8], address: u16, variant: CpuVariant) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while let Some(instruction) = Instruction::decode(&memory[offset..], address.wrapping_add(offset as u16), variant) {
+        offset += instruction.bytes.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(memory: &[u8], address: u16, variant: CpuVariant) -> Vec<String> {
+        disassemble(memory, address, variant).iter().map(|instruction| instruction.to_string()).collect()
+    }
+
+    #[test]
+    fn test_operand_syntax() {
+        #[rustfmt::skip]
+        let program = [
+            0xA9, 0x10,       // LDA #$10
+            0xB5, 0x80,       // LDA $80,X
+            0xB1, 0x10,       // LDA ($10),Y
+            0xA1, 0x10,       // LDA ($10,X)
+            0xBD, 0x00, 0x10, // LDA $1000,X
+            0xAD, 0x12, 0x00, // LDA a:$0012
+            0x6C, 0xFC, 0xFF, // JMP ($FFFC)
+            0x0A,             // ASL A
+            0xD0, 0xFE,       // BNE to itself
+            0xF0, 0x80,       // BEQ back 128
+            0xEA,             // NOP
+            0xA2,             // LDX cut short
+        ];
+        let instructions = disassemble(&program, 0xC000, CpuVariant::Ricoh2A03);
+        assert_eq!(
+            instructions.iter().map(|instruction| instruction.to_string()).collect::<Vec<_>>(),
+            [
+                "LDA #$10", "LDA $80,X", "LDA ($10),Y", "LDA ($10,X)", "LDA $1000,X", "LDA a:$0012", "JMP ($FFFC)",
+                "ASL A", "BNE $C012", "BEQ $BF96", "NOP",
+            ]
+        );
+        let bne = &instructions[8];
+        assert_eq!((bne.address, bne.length(), &bne.bytes[..]), (0xC012, 2, &[0xD0, 0xFE][..]));
+        assert_eq!(bne.branch_target(), Some(0xC012));
+        assert_eq!(instructions[4].operand(), 0x1000);
+    }
+
+    #[test]
+    fn test_variants() {
+        let program = [0x07, 0x10, 0x0F, 0x10, 0x03, 0x12, 0x10, 0x7C, 0x00, 0x20];
+        // The JSR at the end is cut short
+        assert_eq!(text(&program, 0x0200, CpuVariant::Nmos6502), ["SLO $10", "SLO $0310", "JAM", "BPL $0284", "BRK"]);
+        assert_eq!(
+            text(&program, 0x0200, CpuVariant::Wdc65C02),
+            ["RMB0 $10", "BBR0 $10,$0208", "ORA ($10)", "JMP ($2000,X)"]
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! The expression language shared by the assembler's operands and the debugger's conditions, so the two read
+//! numbers and operators the same way.
+//!
+//! Numbers are decimal, `$` hexadecimal, `%` binary or a character in single quotes. Names are looked up by
+//! whoever evaluates the expression, as is `*`, the assembler's current address. `[address]` is the byte in
+//! memory there. The binary operators are C's except for `%`, which starts binary numbers. The unary ones are
+//! `- ~ !` and `<`/`>` for the low and high byte. Comparisons and `!`, `&&` and `||` give 0 or 1. Arithmetic
+//! that overflows is an error rather than wrapping.
+
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Number(i64),
+    Name(String),
+    /// Only the assembler has a use for these, in `.byte`
+    String(String),
+    Operator(&'static str),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{value}"),
+            Token::Name(name) => write!(f, "{name}"),
+            Token::String(text) => write!(f, "\"{text}\""),
+            Token::Operator(operator) => write!(f, "{operator}"),
+        }
+    }
+}
+
+/// Longest first, so `<=` isn't read as `<` then `=`. The last few are punctuation for the assembler's syntax.
+const OPERATORS: [&str; 27] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<<", ">>", "<", ">", "|", "^", "&", "+", "-", "*", "/", "!", "~", "(", ")",
+    "[", "]", "#", ",", ":", "=",
+];
+
+/// Splits `text` after the characters at its start that match `predicate`
+fn split_while(text: &str, predicate: fn(char) -> bool) -> (&str, &str) {
+    text.split_at(text.find(|c| !predicate(c)).unwrap_or(text.len()))
+}
+
+/// Splits a line into tokens, stopping at a `;` comment
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let token;
+        (token, rest) = match c {
+            ';' => break,
+            '$' | '%' => {
+                let (radix, (digits, after)) = if c == '$' {
+                    (16, split_while(&rest[1..], |c| c.is_ascii_hexdigit()))
+                } else {
+                    (2, split_while(&rest[1..], |c| c == '0' || c == '1'))
+                };
+                let value = i64::from_str_radix(digits, radix).map_err(|_| format!("bad number after '{c}'"))?;
+                (Token::Number(value), after)
+            }
+            '0'..='9' => {
+                let (digits, after) = split_while(rest, |c| c.is_ascii_digit());
+                (Token::Number(digits.parse().map_err(|_| format!("bad number {digits}"))?), after)
+            }
+            'a'..='z' | 'A'..='Z' | '_' | '.' | '@' => {
+                let (name, after) = split_while(rest, |c| c.is_ascii_alphanumeric() || "_.@".contains(c));
+                (Token::Name(name.to_string()), after)
+            }
+            '\'' => {
+                let mut chars = rest[1..].chars();
+                let (Some(value), Some('\'')) = (chars.next(), chars.next()) else {
+                    return Err("bad character literal".to_string());
+                };
+                (Token::Number(value as i64), chars.as_str())
+            }
+            '"' => {
+                let (text, after) = rest[1..].split_once('"').ok_or("unterminated string")?;
+                (Token::String(text.to_string()), after)
+            }
+            _ => {
+                let operator = OPERATORS
+                    .into_iter()
+                    .find(|operator| rest.starts_with(operator))
+                    .ok_or_else(|| format!("unexpected '{c}'"))?;
+                (Token::Operator(operator), &rest[operator.len()..])
+            }
+        };
+        tokens.push(token);
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Number(i64),
+    /// A name or `*`, for the `Context` to look up
+    Name(String),
+    /// `[address]`
+    Memory(Box<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+/// What the names and memory in an expression stand for
+pub trait Context {
+    fn name(&self, name: &str) -> Result<i64, String>;
+    fn memory(&self, _address: u16) -> Result<i64, String> {
+        Err("memory can't be read here".to_string())
+    }
+}
+
+impl Expr {
+    pub fn evaluate(&self, context: &impl Context) -> Result<i64, String> {
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Name(name) => context.name(name)?,
+            Expr::Memory(address) => {
+                let address = address.evaluate(context)?;
+                context.memory(u16::try_from(address).map_err(|_| format!("{address} isn't an address"))?)?
+            }
+            Expr::Unary(operator, operand) => {
+                let value = operand.evaluate(context)?;
+                match *operator {
+                    "-" => value.checked_neg().ok_or("overflow")?,
+                    "~" => !value,
+                    "!" => (value == 0) as i64,
+                    "<" => value & 0xFF,
+                    _ => (value >> 8) & 0xFF,
+                }
+            }
+            // Only evaluated when the left side doesn't decide, so `[$2002]` behind a false condition is fine
+            Expr::Binary("&&", left, right) => (left.evaluate(context)? != 0 && right.evaluate(context)? != 0) as i64,
+            Expr::Binary("||", left, right) => (left.evaluate(context)? != 0 || right.evaluate(context)? != 0) as i64,
+            Expr::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(context)?, right.evaluate(context)?);
+                match *operator {
+                    "==" => (left == right) as i64,
+                    "!=" => (left != right) as i64,
+                    "<" => (left < right) as i64,
+                    "<=" => (left <= right) as i64,
+                    ">" => (left > right) as i64,
+                    ">=" => (left >= right) as i64,
+                    "|" => left | right,
+                    "^" => left ^ right,
+                    "&" => left & right,
+                    "<<" => left << shift_count(right)?,
+                    ">>" => left >> shift_count(right)?,
+                    "+" => left.checked_add(right).ok_or("overflow")?,
+                    "-" => left.checked_sub(right).ok_or("overflow")?,
+                    "*" => left.checked_mul(right).ok_or("overflow")?,
+                    _ => left.checked_div(right).ok_or("division by zero")?,
+                }
+            }
+        })
+    }
+
+    /// Every name the expression refers to, so a caller can reject unknown ones before evaluating it
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Expr::Number(_) => vec![],
+            Expr::Name(name) => vec![name.as_str()],
+            Expr::Memory(operand) | Expr::Unary(_, operand) => operand.names(),
+            Expr::Binary(_, left, right) => [left.names(), right.names()].concat(),
+        }
+    }
+}
+
+/// Shifts by 64 or more bits, or by a negative count, are errors rather than wrapping
+fn shift_count(count: i64) -> Result<u32, String> {
+    u32::try_from(count).ok().filter(|&count| count < 64).ok_or_else(|| format!("can't shift by {count}"))
+}
+
+/// Binary operators from loosest to tightest
+const PRECEDENCE: [&[&str]; 10] = [
+    &["||"],
+    &["&&"],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["<<", ">>"],
+    &["+", "-"],
+    &["*", "/"],
+];
+
+/// Recursive descent over a line's tokens. Expressions can be parsed from anywhere in the line, the assembler
+/// picks its own syntax around them.
+pub struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    pub fn new(text: &str) -> Result<Self, String> {
+        Ok(Self { tokens: tokenize(text)?, position: 0 })
+    }
+
+    pub fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    pub fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.position + offset)
+    }
+
+    pub fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    /// True and consumed if the next token is `operator`
+    pub fn eat(&mut self, operator: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Operator(found)) if *found == operator) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect(&mut self, operator: &str) -> Result<(), String> {
+        if self.eat(operator) {
+            Ok(())
+        } else {
+            Err(format!("expected '{operator}'"))
+        }
+    }
+
+    /// Complains about anything left on the line
+    pub fn finish(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(format!("unexpected '{token}'")),
+        }
+    }
+
+    pub fn expression(&mut self) -> Result<Expr, String> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(&Token::Operator(operator)) = self.peek() {
+            if !PRECEDENCE[level].contains(&operator) {
+                break;
+            }
+            self.position += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(operator, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Operator(operator @ ("-" | "~" | "!" | "<" | ">"))) => {
+                Ok(Expr::Unary(operator, Box::new(self.unary()?)))
+            }
+            Some(Token::Operator("(")) => {
+                let inner = self.expression()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some(Token::Operator("[")) => {
+                let address = self.expression()?;
+                self.expect("]")?;
+                Ok(Expr::Memory(Box::new(address)))
+            }
+            Some(Token::Operator("*")) => Ok(Expr::Name("*".to_string())),
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Name(name)) => Ok(Expr::Name(name)),
+            Some(token) => Err(format!("expected a value, got '{token}'")),
+            None => Err("expected a value".to_string()),
+        }
+    }
+}
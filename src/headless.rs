@@ -0,0 +1,370 @@
+//! Running a ROM without a window or audio device, for regression testing and rendering music to files
+
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    apu::Channel,
+    bus::NesBus,
+    cartridge::{Rom, RomError},
+    console::Console,
+    cpu::Jam,
+    controller::{multitap::MultitapKind, Button},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// 1-based
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Controller input to play back, one change per line:
+///
+/// ```text
+/// # frame player buttons
+/// 60  1 START
+/// 62  1 -
+/// 120 1 RIGHT+A
+/// ```
+///
+/// Buttons stay held until the next line for the same player, `-` releases everything. Players 3 and 4 need a
+/// four player adapter, see `HeadlessOptions::multitap`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputScript {
+    /// (frame, player, buttons), sorted by frame
+    changes: Vec<(u64, usize, u8)>,
+}
+
+impl InputScript {
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut changes = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ScriptError { line: index + 1, message };
+
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [frame, player, buttons] = fields[..] else {
+                return Err(error(format!("expected 'frame player buttons', got '{line}'")));
+            };
+
+            let frame = frame.parse().map_err(|_| error(format!("bad frame number '{frame}'")))?;
+            let player = match player.parse::<usize>() {
+                Ok(number @ 1..=4) => number - 1,
+                _ => return Err(error(format!("player must be 1 to 4, got '{player}'"))),
+            };
+            let mut state = 0;
+            if buttons != "-" {
+                for button in buttons.split('+') {
+                    let button =
+                        Button::from_name(button).ok_or_else(|| error(format!("unknown button '{button}'")))?;
+                    state |= button.bit();
+                }
+            }
+            changes.push((frame, player, state));
+        }
+        // Stable, so later lines for the same frame still win
+        changes.sort_by_key(|&(frame, _, _)| frame);
+        Ok(Self { changes })
+    }
+
+    fn apply(&self, frame: u64, bus: &mut NesBus) {
+        for &(_, player, buttons) in self.changes.iter().filter(|&&(at, _, _)| at == frame) {
+            if let Some(joypad) = bus.player_mut(player) {
+                joypad.set_buttons(buttons);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    pub frames: u64,
+    pub sample_rate: u32,
+    /// Run the output through the console's high and low pass filters
+    pub filters: bool,
+    /// Also record every channel on its own
+    pub stems: bool,
+    pub input: InputScript,
+    /// A four player adapter to plug in, for games that support more than two players
+    pub multitap: Option<MultitapKind>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            frames: 60,
+            sample_rate: crate::apu::DEFAULT_SAMPLE_RATE,
+            filters: true,
+            stems: false,
+            input: InputScript::default(),
+            multitap: None,
+        }
+    }
+}
+
+/// The audio of a headless run
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub sample_rate: u32,
+    pub mix: Vec<f32>,
+    /// Empty unless stems were asked for
+    pub stems: Vec<(Channel, Vec<f32>)>,
+    /// Set if the game crashed the CPU, the recording still runs to the end
+    pub jam: Option<Jam>,
+}
+
+impl Recording {
+    /// Writes the mix to `path` and each stem next to it, "music.wav" gets "music.pulse1.wav" and so on
+    pub fn write_wavs(&self, path: &Path) -> io::Result<()> {
+        write_wav(io::BufWriter::new(std::fs::File::create(path)?), self.sample_rate, &self.mix)?;
+        for (channel, samples) in &self.stems {
+            let file = std::fs::File::create(stem_path(path, *channel))?;
+            write_wav(io::BufWriter::new(file), self.sample_rate, samples)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn stem_path(path: &Path, channel: Channel) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.{}.wav", channel.name()))
+}
+
+/// Powers on a console with `rom` inserted and runs it for `options.frames` frames
+pub fn run(rom: Rom, options: &HeadlessOptions) -> Result<Recording, RomError> {
+    let mut console = Console::new(rom)?;
+    let bus = &mut console.cpu.bus;
+    bus.apu.set_sample_rate(options.sample_rate);
+    bus.apu.set_filters_enabled(options.filters);
+    bus.apu.set_stems_enabled(options.stems);
+    if let Some(kind) = options.multitap {
+        bus.connect_multitap(kind);
+    }
+
+    let mut recording = Recording {
+        sample_rate: options.sample_rate,
+        mix: Vec::new(),
+        stems: Vec::new(),
+        jam: None,
+    };
+    if options.stems {
+        recording.stems = Channel::ALL.iter().map(|&channel| (channel, Vec::new())).collect();
+    }
+
+    let mut buffer = vec![0.0; 4096];
+    for frame in 0..options.frames {
+        options.input.apply(frame, &mut console.cpu.bus);
+        if let Err(jam) = console.run_frame() {
+            recording.jam.get_or_insert(jam);
+        }
+
+        // Every resampler is at the same clock, so they all have the same number of samples ready
+        loop {
+            let count = console.drain_samples(&mut buffer);
+            if count == 0 {
+                break;
+            }
+            recording.mix.extend_from_slice(&buffer[..count]);
+            for (channel, samples) in &mut recording.stems {
+                let count = console.cpu.bus.apu.drain_stem(*channel, &mut buffer);
+                samples.extend_from_slice(&buffer[..count]);
+            }
+        }
+    }
+
+    Ok(recording)
+}
+
+/// $6001-$6003 once a test ROM has started reporting through $6000
+const TEST_SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const TEST_RUNNING: u8 = 0x80;
+const TEST_NEEDS_RESET: u8 = 0x81;
+/// The ROMs ask for at least 100ms between requesting the reset and getting it
+const TEST_RESET_DELAY_FRAMES: u64 = 7;
+
+/// How a test ROM reporting through $6000 finished
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    /// The result code, usually the number of the first test that failed
+    Failed(u8),
+    /// No result within the frame limit
+    TimedOut,
+    Jammed(Jam),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestReport {
+    pub outcome: TestOutcome,
+    /// The text the ROM left at $6004, what it would have printed on screen
+    pub message: String,
+}
+
+/// Runs a test ROM following blargg's $6000 protocol until it reports a result, for at most `max_frames`
+/// frames. $6001-$6003 hold the signature once $6000 is valid: $80 while running, $81 to ask for the reset
+/// button to be pressed and anything lower as the final result, 0 for a pass.
+pub fn run_test_rom(rom: Rom, max_frames: u64) -> Result<TestReport, RomError> {
+    let mut console = Console::new(rom)?;
+    let mut reset_at = None;
+
+    let mut outcome = TestOutcome::TimedOut;
+    for frame in 0..max_frames {
+        if let Err(jam) = console.run_frame() {
+            outcome = TestOutcome::Jammed(jam);
+            break;
+        }
+        let prg_ram = console.cpu.bus.prg_ram();
+        if prg_ram[1..4] != TEST_SIGNATURE {
+            continue;
+        }
+        match prg_ram[0] {
+            TEST_RUNNING => {}
+            TEST_NEEDS_RESET => {
+                let at = *reset_at.get_or_insert(frame + TEST_RESET_DELAY_FRAMES);
+                if frame >= at {
+                    reset_at = None;
+                    console.reset();
+                }
+            }
+            0 => {
+                outcome = TestOutcome::Passed;
+                break;
+            }
+            code if code < TEST_RUNNING => {
+                outcome = TestOutcome::Failed(code);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let text = &console.cpu.bus.prg_ram()[4..];
+    let text = &text[..text.iter().position(|&byte| byte == 0).unwrap_or(text.len())];
+    let message = String::from_utf8_lossy(text).trim_end().to_string();
+    Ok(TestReport { outcome, message })
+}
+
+/// Writes mono 16 bit PCM, clipping anything outside -1.0..=1.0
+pub fn write_wav<W: Write>(mut out: W, sample_rate: u32, samples: &[f32]) -> io::Result<()> {
+    const CHANNELS: u16 = 1;
+    const BITS_PER_SAMPLE: u16 = 16;
+    let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+    let data_size = samples.len() as u32 * block_align as u32;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_size).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    // PCM
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&CHANNELS.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+    out.write_all(&block_align.to_le_bytes())?;
+    out.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    out.write_all(b"data")?;
+    out.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        out.write_all(&sample.to_le_bytes())?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_rom::TestRom;
+
+    #[test]
+    fn test_parse_script() {
+        let script = InputScript::parse("# comment\n10 1 start\n\n12 2 A+RIGHT # jump\n5 1 -\n").unwrap();
+        assert_eq!(script.changes, vec![(5, 0, 0), (10, 0, 0b1000), (12, 1, 0b1000_0001)]);
+
+        let error = InputScript::parse("1 1 A\n2 5 A").unwrap_err();
+        assert_eq!(error.line, 2);
+        assert!(InputScript::parse("1 1 TURBO").is_err());
+    }
+
+    #[test]
+    fn test_rom_protocol_reset() {
+        // Asks for a reset on the first run and passes after it, PRG RAM survives the reset
+        #[rustfmt::skip]
+        let rom = TestRom::nrom(&[
+            0xAD, 0x00, 0x60,             // LDA $6000
+            0xC9, 0x81,                   // CMP #$81
+            0xF0, 0x17,                   // BEQ $C01E
+            0xA9, 0xDE, 0x8D, 0x01, 0x60, // LDA #$DE, STA $6001
+            0xA9, 0xB0, 0x8D, 0x02, 0x60, // LDA #$B0, STA $6002
+            0xA9, 0x61, 0x8D, 0x03, 0x60, // LDA #$61, STA $6003
+            0xA9, 0x81, 0x8D, 0x00, 0x60, // LDA #$81, STA $6000
+            0x4C, 0x1B, 0xC0,             // JMP $C01B
+            0xA9, 0x6F, 0x8D, 0x04, 0x60, // LDA #'o', STA $6004
+            0xA9, 0x6B, 0x8D, 0x05, 0x60, // LDA #'k', STA $6005
+            0xA9, 0x00, 0x8D, 0x06, 0x60, // LDA #0, STA $6006
+            0x8D, 0x00, 0x60,             // STA $6000
+            0x4C, 0x30, 0xC0,             // JMP $C030
+        ]).rom();
+        let report = run_test_rom(rom.clone(), 60).unwrap();
+        assert_eq!(report, TestReport { outcome: TestOutcome::Passed, message: "ok".to_string() });
+
+        assert_eq!(run_test_rom(rom, 3).unwrap().outcome, TestOutcome::TimedOut);
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let mut out = Vec::new();
+        write_wav(&mut out, 48_000, &[0.0, 1.0, -2.0]).unwrap();
+        assert_eq!(out.len(), 44 + 6);
+        assert_eq!(&out[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(out[4..8].try_into().unwrap()), 36 + 6);
+        assert_eq!(u32::from_le_bytes(out[24..28].try_into().unwrap()), 48_000);
+        assert_eq!(i16::from_le_bytes([out[46], out[47]]), i16::MAX);
+        assert_eq!(i16::from_le_bytes([out[48], out[49]]), -i16::MAX);
+    }
+
+    #[test]
+    fn test_stems_split_channels() {
+        #[rustfmt::skip]
+        let rom = TestRom::nrom(&[
+            0xA9, 0x01, 0x8D, 0x15, 0x40, // LDA #$01, STA $4015
+            0xA9, 0xBF, 0x8D, 0x00, 0x40, // LDA #$BF, STA $4000
+            0xA9, 0xFD, 0x8D, 0x02, 0x40, // LDA #$FD, STA $4002
+            0xA9, 0x08, 0x8D, 0x03, 0x40, // LDA #$08, STA $4003
+            0x4C, 0x14, 0xC0,             // JMP $C014
+        ]).rom();
+        let options = HeadlessOptions { frames: 10, stems: true, ..Default::default() };
+        let recording = run(rom, &options).unwrap();
+
+        let loudest = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!(recording.mix.len() > 7000);
+        assert!(loudest(&recording.mix) > 0.05);
+        for (channel, samples) in &recording.stems {
+            assert_eq!(samples.len(), recording.mix.len());
+            match channel {
+                Channel::Pulse1 => assert!(loudest(samples) > 0.05),
+                // The triangle idles at a constant level, which the filters take out over time
+                Channel::Triangle => {}
+                _ => assert!(loudest(samples) < 1e-6, "{} is not silent", channel.name()),
+            }
+        }
+    }
+}
@@ -1,6 +1,22 @@
+pub mod apu;
+pub mod asm;
+pub mod bus;
+pub mod cartridge;
+pub mod console;
+pub mod controller;
 pub mod cpu;
+pub mod debugger;
+pub mod disasm;
+mod expr;
+pub mod headless;
+pub mod loader;
+pub mod machine;
+pub mod ppu;
 pub mod registers;
+pub mod watch;
 pub mod opcodes;
+#[cfg(test)]
+mod test_rom;
 
 pub trait NegativeSet {
     fn negative_set(&self) -> bool;
@@ -22,19 +38,29 @@ impl NegativeSet for u8 {
 
 #[cfg(test)]
 mod test {
-    use crate::cpu::Cpu;
+    use std::collections::BTreeMap;
+
+    use crate::{
+        asm::assemble,
+        bus::{Bus, NesBus},
+        controller::{multitap::MultitapKind, Button},
+        cpu::{Cpu, CpuVariant},
+        loader::EntryPoint,
+        ppu::Mirroring,
+        test_rom::TestRom,
+    };
 
     #[test]
     fn test_zero() {
         let mut cpu = Cpu::new();
 
-        cpu.load_and_run(&vec![0xA9, 0x00, 0x00]);
+        cpu.load_and_run(&[0xA9, 0x00, 0x00]);
 
-        assert_eq!(cpu.registers.status.zero, true);
+        assert!(cpu.registers.status.zero);
 
-        cpu.load_and_run(&vec![0xA9, 0x01, 0x00]);
+        cpu.load_and_run(&[0xA9, 0x01, 0x00]);
 
-        assert_eq!(cpu.registers.status.zero, false);
+        assert!(!cpu.registers.status.zero);
     }
 
     #[test]
@@ -42,21 +68,21 @@ mod test {
         let mut cpu = Cpu::new();
 
         // 0x80 has bit 7 set so negative should be true
-        cpu.load_and_run(&vec![0xA9, 0x80, 0x00]);
+        cpu.load_and_run(&[0xA9, 0x80, 0x00]);
 
-        assert_eq!(cpu.registers.status.negative, true);
+        assert!(cpu.registers.status.negative);
 
         // 0x00 has bit 7 reset so negative should be false
-        cpu.load_and_run(&vec![0xA9, 0x00, 0x00]);
+        cpu.load_and_run(&[0xA9, 0x00, 0x00]);
 
-        assert_eq!(cpu.registers.status.negative, false);
+        assert!(!cpu.registers.status.negative);
     }
 
     #[test]
     fn test_lda_immediate() {
         let mut cpu = Cpu::new();
 
-        cpu.load_and_run(&vec![0xA9, 0x11, 0x00]);
+        cpu.load_and_run(&[0xA9, 0x11, 0x00]);
 
         assert_eq!(cpu.registers.a, 0x11);
     }
@@ -66,8 +92,573 @@ mod test {
         let mut cpu = Cpu::new();
 
         // Load A with 0xff, Store a at 0x01, Load A with 0x00, Load A with the data at 0x01
-        cpu.load_and_run(&vec![0xa9, 0xff, 0x85, 0x01, 0xa9, 0x00, 0xa5, 0x01, 0x00]);
+        cpu.load_and_run(&[0xa9, 0xff, 0x85, 0x01, 0xa9, 0x00, 0xa5, 0x01, 0x00]);
 
         assert_eq!(cpu.registers.a, 0xff);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_transfers_and_increments() {
+        let mut cpu = Cpu::new();
+
+        // LDA #$fe, TAX, INX, INX, TXA, TAY, DEY
+        cpu.load_and_run(&[0xa9, 0xfe, 0xaa, 0xe8, 0xe8, 0x8a, 0xa8, 0x88, 0x00]);
+
+        assert_eq!(cpu.registers.x, 0x00);
+        assert_eq!(cpu.registers.a, 0x00);
+        assert_eq!(cpu.registers.y, 0xff);
+        assert!(cpu.registers.status.negative);
+    }
+
+    #[test]
+    fn test_decimal_mode_variants() {
+        // SED, CLC, LDA #$19, ADC #$28
+        let add = [0xf8, 0x18, 0xa9, 0x19, 0x69, 0x28, 0x00];
+        // SED, SEC, LDA #$42, SBC #$13
+        let subtract = [0xf8, 0x38, 0xa9, 0x42, 0xe9, 0x13, 0x00];
+
+        let mut cpu = Cpu::new();
+        cpu.load_and_run(&add);
+        // The 2A03 has no decimal mode
+        assert_eq!(cpu.registers.a, 0x41);
+
+        for variant in [CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+            cpu.variant = variant;
+            cpu.load_and_run(&add);
+            assert_eq!(cpu.registers.a, 0x47);
+            assert!(!cpu.registers.status.carry);
+            cpu.load_and_run(&subtract);
+            assert_eq!(cpu.registers.a, 0x29);
+            assert!(cpu.registers.status.carry);
+        }
+
+        // SED, CLC, LDA #$99, ADC #$01: the NMOS chip takes Z from the binary sum $9A, the 65C02 from $00
+        let wrap = [0xf8, 0x18, 0xa9, 0x99, 0x69, 0x01, 0x00];
+        cpu.load_and_run(&wrap);
+        assert_eq!(cpu.registers.a, 0x00);
+        assert!(cpu.registers.status.zero);
+        cpu.variant = CpuVariant::Nmos6502;
+        cpu.load_and_run(&wrap);
+        assert_eq!(cpu.registers.a, 0x00);
+        assert!(!cpu.registers.status.zero);
+    }
+
+    #[test]
+    fn test_65c02_instructions() {
+        let mut cpu = Cpu::new();
+        cpu.variant = CpuVariant::Wdc65C02;
+
+        #[rustfmt::skip]
+        cpu.load_and_run(&[
+            0xa2, 0x42,       // LDX #$42
+            0xda,             // PHX
+            0x7a,             // PLY
+            0xa9, 0x34,       // LDA #$34
+            0x85, 0x10,       // STA $10
+            0xa9, 0x12,       // LDA #$12
+            0x85, 0x11,       // STA $11
+            0xa9, 0x0f,       // LDA #$0f
+            0x92, 0x10,       // STA ($10)
+            0x64, 0x10,       // STZ $10
+            0x04, 0x12,       // TSB $12
+            0xf7, 0x12,       // SMB7 $12
+            0xff, 0x12, 0x02, // BBS7 $12, +2
+            0xa9, 0xee,       // LDA #$ee
+            0x80, 0x02,       // BRA +2
+            0xa9, 0xdd,       // LDA #$dd
+            0x00,
+        ]);
+
+        assert_eq!(cpu.registers.a, 0x0f);
+        assert_eq!(cpu.registers.y, 0x42);
+        assert_eq!(cpu.mem_read(0x1234), 0x0f);
+        assert_eq!(cpu.mem_read(0x10), 0x00);
+        assert_eq!(cpu.mem_read(0x12), 0x8f);
+    }
+
+    #[test]
+    fn test_65c02_fixes_jmp_indirect() {
+        let mut cpu = Cpu::new();
+        cpu.bus.load(0x10ff, &[0x00, 0x90]);
+        cpu.bus.load(0x1000, &[0xa0]);
+        // LDA #$01 at $9000, LDA #$02 at $a000
+        cpu.bus.load(0x9000, &[0xa9, 0x01, 0x00]);
+        cpu.bus.load(0xa000, &[0xa9, 0x02, 0x00]);
+
+        // JMP ($10ff)
+        cpu.load_and_run(&[0x6c, 0xff, 0x10]);
+        assert_eq!(cpu.registers.a, 0x02);
+
+        cpu.variant = CpuVariant::Wdc65C02;
+        cpu.load_and_run(&[0x6c, 0xff, 0x10]);
+        assert_eq!(cpu.registers.a, 0x01);
+    }
+
+    #[test]
+    fn test_unofficial_combined_opcodes() {
+        let mut cpu = Cpu::new();
+
+        // LDA #$5a, STA $10, LAX $10, LDA #$0f, SAX $11, DCP $10, SLO $11
+        cpu.load_and_run(&[
+            0xa9, 0x5a, 0x85, 0x10, 0xa7, 0x10, 0xa9, 0x0f, 0x87, 0x11, 0xc7, 0x10, 0x07, 0x11, 0x00,
+        ]);
+
+        assert_eq!(cpu.registers.x, 0x5a);
+        assert_eq!(cpu.mem_read(0x10), 0x59);
+        assert_eq!(cpu.mem_read(0x11), 0x14);
+        assert_eq!(cpu.registers.a, 0x1f);
+        assert!(!cpu.registers.status.carry);
+    }
+
+    #[test]
+    fn test_unofficial_immediate_opcodes() {
+        let mut cpu = Cpu::new();
+
+        // LDA #$f0, LDX #$3c, SBX #$10
+        cpu.load_and_run(&[0xa9, 0xf0, 0xa2, 0x3c, 0xcb, 0x10, 0x00]);
+        assert_eq!(cpu.registers.x, 0x20);
+        assert!(cpu.registers.status.carry);
+
+        // SEC, LDA #$ff, ARR #$c0
+        cpu.load_and_run(&[0x38, 0xa9, 0xff, 0x6b, 0xc0, 0x00]);
+        assert_eq!(cpu.registers.a, 0xe0);
+        assert!(cpu.registers.status.carry);
+        assert!(!cpu.registers.status.overflow);
+        assert!(cpu.registers.status.negative);
+
+        // In decimal mode the NMOS chip fixes up each nibble of ARR's result as BCD, the 2A03 doesn't
+        let mut decimal = Cpu::new();
+        decimal.variant = CpuVariant::Nmos6502;
+        // SED, SEC, LDA #$ff, ARR #$ff
+        decimal.load_and_run(&[0xf8, 0x38, 0xa9, 0xff, 0x6b, 0xff, 0x00]);
+        assert_eq!(decimal.registers.a, 0x55);
+        assert!(decimal.registers.status.carry);
+        assert!(decimal.registers.status.negative);
+        assert!(!decimal.registers.status.overflow);
+        // SED, CLC, LDA #$65, ARR #$ff
+        decimal.load_and_run(&[0xf8, 0x18, 0xa9, 0x65, 0x6b, 0xff, 0x00]);
+        assert_eq!(decimal.registers.a, 0x98);
+        assert!(decimal.registers.status.carry);
+        assert!(decimal.registers.status.overflow);
+        decimal.variant = CpuVariant::Ricoh2A03;
+        decimal.load_and_run(&[0xf8, 0x18, 0xa9, 0x65, 0x6b, 0xff, 0x00]);
+        assert_eq!(decimal.registers.a, 0x32);
+
+        // LDA #$ff, LDX #$0f, XAA #$3c
+        cpu.magic.xaa = 0x00;
+        cpu.load_and_run(&[0xa9, 0xff, 0xa2, 0x0f, 0x8b, 0x3c, 0x00]);
+        assert_eq!(cpu.registers.a, 0x0c);
+    }
+
+    #[test]
+    fn test_shx_page_cross_corrupts_address() {
+        let mut cpu = Cpu::new();
+
+        // LDX #$03, LDY #$01, SHX $1200,Y, LDY #$10, SHX $12f8,Y
+        cpu.load_and_run(&[0xa2, 0x03, 0xa0, 0x01, 0x9e, 0x00, 0x12, 0xa0, 0x10, 0x9e, 0xf8, 0x12, 0x00]);
+
+        // X & ($12 + 1) = 3, and crossing the page puts that in the high byte of the address
+        assert_eq!(cpu.mem_read(0x1201), 0x03);
+        assert_eq!(cpu.mem_read(0x0308), 0x03);
+        assert_eq!(cpu.mem_read(0x1308), 0x00);
+    }
+
+    #[test]
+    fn test_adc_overflow_and_carry() {
+        let mut cpu = Cpu::new();
+
+        // CLC, LDA #$50, ADC #$50
+        cpu.load_and_run(&[0x18, 0xa9, 0x50, 0x69, 0x50, 0x00]);
+
+        assert_eq!(cpu.registers.a, 0xa0);
+        assert!(cpu.registers.status.overflow);
+        assert!(!cpu.registers.status.carry);
+
+        // SEC, LDA #$50, SBC #$f0
+        cpu.load_and_run(&[0x38, 0xa9, 0x50, 0xe9, 0xf0, 0x00]);
+
+        assert_eq!(cpu.registers.a, 0x60);
+        assert!(!cpu.registers.status.overflow);
+        assert!(!cpu.registers.status.carry);
+    }
+
+    #[test]
+    fn test_loop_with_branch() {
+        let mut cpu = Cpu::new();
+
+        let program = assemble(
+            "
+                    LDX #$05
+            loop:   INY
+                    DEX
+                    BNE loop
+                    BRK
+            ",
+            CpuVariant::Nmos6502,
+        )
+        .unwrap();
+        cpu.load_and_run(&program.bytes);
+
+        assert_eq!(cpu.registers.y, 0x05);
+        // 2 + 5 * (2 + 2) + 4 * 3 + 2 cycles for the loop, 7 for the BRK sequence
+        assert_eq!(cpu.cycles, 2 + 5 * 4 + 4 * 3 + 2 + 7);
+    }
+
+    #[test]
+    fn test_jsr_rts() {
+        let mut cpu = Cpu::new();
+
+        let program = assemble(
+            "
+                    .org $8000
+                    JSR sub
+                    LDX #$01
+                    BRK
+            sub:    LDA #$42
+                    RTS
+            ",
+            CpuVariant::Nmos6502,
+        )
+        .unwrap();
+        cpu.load_and_run(&program.bytes);
+
+        assert_eq!(cpu.registers.a, 0x42);
+        assert_eq!(cpu.registers.x, 0x01);
+    }
+
+    #[test]
+    fn test_jmp_indirect_page_wrap() {
+        let mut cpu = Cpu::new();
+
+        // JMP ($02ff) takes its high byte from $0200, not $0300
+        cpu.load_at(0x8000, &[0x6c, 0xff, 0x02], EntryPoint::Address(0x8000)).unwrap();
+        cpu.mem_write(0x02ff, 0x34);
+        cpu.mem_write(0x0200, 0x12);
+        cpu.mem_write(0x0300, 0x56);
+        cpu.step();
+
+        assert_eq!(cpu.registers.program_counter, 0x1234);
+    }
+
+    /// Flat memory that logs every access as (address, Some(data) for writes)
+    struct TracingBus {
+        memory: Vec<u8>,
+        accesses: Vec<(u16, Option<u8>)>,
+    }
+
+    impl Bus for TracingBus {
+        fn mem_read(&mut self, address: u16) -> u8 {
+            self.accesses.push((address, None));
+            self.memory[address as usize]
+        }
+        fn mem_write(&mut self, address: u16, data: u8) {
+            self.accesses.push((address, Some(data)));
+            self.memory[address as usize] = data;
+        }
+    }
+
+    /// The bus accesses of the first instruction of `program`, loaded at $0600 with X = $10
+    fn trace(program: &[u8]) -> Vec<(u16, Option<u8>)> {
+        let mut memory = vec![0; 0x10000];
+        memory[0x0600..0x0600 + program.len()].copy_from_slice(program);
+        let mut cpu = Cpu::with_bus(TracingBus { memory, accesses: vec![] });
+        cpu.registers.program_counter = 0x0600;
+        cpu.registers.x = 0x10;
+        cpu.step();
+        cpu.bus.accesses
+    }
+
+    #[test]
+    fn test_dummy_accesses() {
+        // LDA $12F8,X reads $1208 before fixing the page
+        assert_eq!(
+            trace(&[0xbd, 0xf8, 0x12]),
+            [(0x0600, None), (0x0601, None), (0x0602, None), (0x1208, None), (0x1308, None)]
+        );
+        // STA $1200,X always spends the fix up cycle reading
+        assert_eq!(trace(&[0x9d, 0x00, 0x12])[3..], [(0x1210, None), (0x1210, Some(0))]);
+        // INC $40 writes the old value back before the new one
+        assert_eq!(trace(&[0xe6, 0x40])[2..], [(0x0040, None), (0x0040, Some(0)), (0x0040, Some(1))]);
+        // LDA $40,X reads the unindexed address while adding X
+        assert_eq!(trace(&[0xb5, 0x40])[2..], [(0x0040, None), (0x0050, None)]);
+        // PLA reads the stack before incrementing the pointer
+        assert_eq!(trace(&[0x68]), [(0x0600, None), (0x0601, None), (0x01FD, None), (0x01FE, None)]);
+    }
+
+    /// Builds a 32KiB PRG-ROM with `program` at $8000, `handler` at $9000 and the vectors pointing at them
+    fn nes_rom(program: &[u8], handler: &[u8]) -> Vec<u8> {
+        TestRom::new(2, 0).code(0x8000, program).code(0x9000, handler).vectors(0x9000, 0x8000, 0x9000).prg_rom
+    }
+
+    #[test]
+    fn test_vblank_nmi() {
+        // LDA #$80, STA $2000, loop: LDA $00, BEQ loop, BRK
+        let program = [0xa9, 0x80, 0x8d, 0x00, 0x20, 0xa5, 0x00, 0xf0, 0xfc, 0x00];
+        // INC $00, RTI
+        let nmi_handler = [0xe6, 0x00, 0x40];
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&program, &nmi_handler), vec![], Mirroring::Horizontal));
+        cpu.reset();
+        cpu.run();
+
+        // vblank starts on dot 1 of scanline 241, then it is the NMI, the handler, the rest of the loop and BRK
+        let vblank_cycle = (241 * 341 + 1) / 3;
+        assert!(cpu.cycles > vblank_cycle && cpu.cycles < vblank_cycle + 40);
+        assert_eq!(cpu.mem_read(0x0000), 1);
+    }
+
+    #[test]
+    fn test_status_read_clears_nmi() {
+        // LDA #$80, STA $2000, loop: BIT $2002, BPL loop, BRK
+        let program = [0xa9, 0x80, 0x8d, 0x00, 0x20, 0x2c, 0x02, 0x20, 0x10, 0xfb, 0x00];
+        // INC $00, RTI
+        let nmi_handler = [0xe6, 0x00, 0x40];
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&program, &nmi_handler), vec![], Mirroring::Horizontal));
+        cpu.reset();
+        cpu.run();
+
+        // The loop's reads miss the start of vblank, so the NMI is taken once and the first BIT after its RTI
+        // sees the flag and clears it, leaving the loop 103 dots into scanline 241
+        assert_eq!((cpu.bus.ppu.scanline, cpu.bus.ppu.dot), (241, 103));
+        assert_eq!(cpu.cycles, 27428);
+        assert!(!cpu.bus.ppu.status.vblank);
+        assert_eq!(cpu.mem_read(0x0000), 1);
+    }
+
+    /// Runs an endless loop at $8000 until the middle of vblank with NMI still disabled, then jumps to $8003
+    fn cpu_in_vblank(program: &[u8], nmi_handler: &[u8]) -> Cpu<NesBus> {
+        let mut rom = vec![0x4c, 0x00, 0x80];
+        rom.extend_from_slice(program);
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&rom, nmi_handler), vec![], Mirroring::Horizontal));
+        cpu.reset();
+        while cpu.bus.ppu.scanline != 245 {
+            cpu.step();
+        }
+        cpu.registers.program_counter = 0x8003;
+        cpu
+    }
+
+    #[test]
+    fn test_nmi_enabled_during_vblank() {
+        // LDA #$80, STA $2000, LDA #$11, LDA #$22, BRK
+        let program = [0xa9, 0x80, 0x8d, 0x00, 0x20, 0xa9, 0x11, 0xa9, 0x22, 0x00];
+        // STA $00, RTI
+        let nmi_handler = [0x85, 0x00, 0x40];
+        let mut cpu = cpu_in_vblank(&program, &nmi_handler);
+        cpu.run();
+
+        // The write lands on the last cycle of STA, too late for its interrupt poll, so one more
+        // instruction runs before the NMI
+        assert_eq!(cpu.mem_read(0x0000), 0x11);
+    }
+
+    #[test]
+    fn test_nmi_enable_toggled_during_vblank() {
+        // LDA #$80, STA $2000, LDA #$00, STA $2000, LDA #$80, STA $2000, NOP, BRK
+        let program = [
+            0xa9, 0x80, 0x8d, 0x00, 0x20, 0xa9, 0x00, 0x8d, 0x00, 0x20, 0xa9, 0x80, 0x8d, 0x00, 0x20, 0xea, 0x00,
+        ];
+        // INC $01, RTI
+        let nmi_handler = [0xe6, 0x01, 0x40];
+        let mut cpu = cpu_in_vblank(&program, &nmi_handler);
+        cpu.run();
+
+        assert_eq!(cpu.mem_read(0x0001), 2);
+    }
+
+    /// Lands `probe`, an absolute access to a PPU register with A = 0, on every dot around the start of vblank
+    /// with NMI enabled, the way blargg's `ppu_vbl_nmi` sweeps its reads and writes. Returns, by the dot the access
+    /// happens on relative to the one that sets the flag, what A was stored as afterwards and how many NMIs were
+    /// taken.
+    fn vblank_sweep(probe: [u8; 3]) -> BTreeMap<i64, (u8, u8)> {
+        // JMP $8000, a NOP sled from $8100 that runs into the probe at $8180, then STA $10 and a JMP to itself
+        let mut program = vec![0xea; 0x200];
+        program[..3].copy_from_slice(&[0x4c, 0x00, 0x80]);
+        program[0x180..0x188].copy_from_slice(&[probe[0], probe[1], probe[2], 0x85, 0x10, 0x4c, 0x85, 0x81]);
+        // INC $11, RTI
+        let nmi_handler = [0xe6, 0x11, 0x40];
+
+        let mut results = BTreeMap::new();
+        // A frame with rendering off is 2 dots short of a whole number of CPU cycles and one more pass through
+        // the JMP is 9 dots, so between them the accesses hit every dot
+        for frame in 1..=3 {
+            for extra in 0..2 {
+                for nops in 52..60 {
+                    let rom = nes_rom(&program, &nmi_handler);
+                    let mut cpu = Cpu::with_bus(NesBus::new(rom, vec![], Mirroring::Horizontal));
+                    cpu.reset();
+                    while cpu.bus.ppu.frame != frame || cpu.bus.ppu.scanline != 240 {
+                        cpu.step();
+                    }
+                    for _ in 0..extra {
+                        cpu.step();
+                    }
+                    cpu.bus.ppu.write_register(0x2000, 0x80);
+                    cpu.registers.a = 0;
+                    cpu.registers.program_counter = 0x8180 - nops;
+                    while cpu.registers.program_counter != 0x8180 {
+                        cpu.step();
+                    }
+                    // The access is on the 4th cycle, after two of its three dots
+                    let ppu = &cpu.bus.ppu;
+                    let dot = ppu.scanline as i64 * 341 + ppu.dot as i64 + 3 * 3 + 2 - (241 * 341 + 1);
+                    while cpu.bus.ppu.scanline != 245 {
+                        cpu.step();
+                    }
+                    let result = (cpu.bus.peek(0x0010).unwrap(), cpu.bus.peek(0x0011).unwrap());
+                    assert_eq!(*results.entry(dot).or_insert(result), result, "dot {dot}");
+                }
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn test_status_read_races_vblank() {
+        // LDA $2002
+        let results = vblank_sweep([0xad, 0x02, 0x20]);
+        assert!((-6..=6).all(|dot| results.contains_key(&dot)));
+        for (dot, (status, nmis)) in results {
+            let expected = match dot {
+                ..=-1 => (0x00, 1),
+                // One dot early, the flag is never set this frame and there is no NMI
+                0 => (0x00, 0),
+                // The flag went up during the read's own cycle and is cleared before the CPU samples /NMI
+                1..=2 => (0x80, 0),
+                _ => (0x80, 1),
+            };
+            assert_eq!((status & 0x80, nmis), expected, "read on dot {dot}");
+        }
+    }
+
+    #[test]
+    fn test_nmi_disable_races_vblank() {
+        // STA $2000
+        let results = vblank_sweep([0x8d, 0x00, 0x20]);
+        assert!((-6..=6).all(|dot| results.contains_key(&dot)));
+        for (dot, (_, nmis)) in results {
+            // Only an edge the CPU saw at the end of an earlier cycle survives the write
+            assert_eq!(nmis, (dot >= 3) as u8, "write on dot {dot}");
+        }
+    }
+
+    #[test]
+    fn test_apu_frame_irq() {
+        // CLI, loop: LDA $00, BEQ loop, BRK
+        let program = [0x58, 0xa5, 0x00, 0xf0, 0xfc, 0x00];
+        // LDA $4015, STA $00, RTI
+        let irq_handler = [0xad, 0x15, 0x40, 0x85, 0x00, 0x40];
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&program, &irq_handler), vec![], Mirroring::Horizontal));
+        cpu.reset();
+        cpu.run();
+
+        // The handler saw the frame interrupt flag, and reading it acknowledged the interrupt
+        assert_eq!(cpu.mem_read(0x0000) & 0b0100_0000, 0b0100_0000);
+        assert!(!cpu.bus.apu.irq_line());
+        assert!(cpu.cycles > 29828 && cpu.cycles < 29828 + 50);
+    }
+
+    #[test]
+    fn test_dmc_dma_repeats_halted_read() {
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&[], &[]), vec![], Mirroring::Horizontal));
+
+        // Fill $2000-$200F with 0, 1, 2, ... then point back at $2000 and prime the read buffer
+        cpu.mem_write(0x2006, 0x20);
+        cpu.mem_write(0x2006, 0x00);
+        for i in 0..16 {
+            cpu.mem_write(0x2007, i);
+        }
+        cpu.mem_write(0x2006, 0x20);
+        cpu.mem_write(0x2006, 0x00);
+        cpu.mem_read(0x2007);
+
+        // Start a one byte sample, the DMC wants its first byte straight away
+        cpu.mem_write(0x4012, 0x00);
+        cpu.mem_write(0x4013, 0x00);
+        cpu.mem_write(0x4015, 0b0001_0000);
+
+        let cycles = cpu.cycles;
+        let data = cpu.mem_read(0x2007);
+        let stolen = cpu.cycles - cycles - 1;
+
+        assert!(stolen == 3 || stolen == 4);
+        // Every stolen cycle but the sample fetch read $2007 again, moving the PPU address along
+        assert_eq!(data as u64, stolen - 1);
+        assert!(!cpu.bus.apu.dmc.active());
+    }
+
+    #[test]
+    fn test_page_cross_dummy_read_clears_vblank() {
+        // LDX #$10, LDA $20F2,X, STA $00, BRK
+        let program = [0xa2, 0x10, 0xbd, 0xf2, 0x20, 0x85, 0x00, 0x00];
+        let mut cpu = cpu_in_vblank(&program, &[]);
+        assert!(cpu.bus.ppu.status.vblank);
+        cpu.run();
+
+        // The dummy read of $2002 saw vblank and cleared it before the real read of its mirror at $2102
+        assert_eq!(cpu.mem_read(0x0000) & 0x80, 0);
+    }
+
+    #[test]
+    fn test_open_bus_reads() {
+        let mut bus = NesBus::new(nes_rom(&[], &[]), vec![], Mirroring::Horizontal);
+        // Nothing answers unmapped reads, they see whatever was last on the bus
+        bus.mem_write(0x00, 0x12);
+        assert_eq!(bus.mem_read(0x4000), 0x12);
+        assert_eq!(bus.mem_read(0x5123), 0x12);
+        // Bit 5 of $4015 is open bus, and reading it leaves the bus alone
+        bus.mem_write(0x00, 0xFF);
+        assert_eq!(bus.mem_read(0x4015) & 0b0010_0000, 0b0010_0000);
+        assert_eq!(bus.mem_read(0x4018), 0xFF);
+    }
+
+    #[test]
+    fn test_joypad_read_keeps_open_bus_bits() {
+        // LDA #$01, STA $4016, LDA #$00, STA $4016, LDA $4016, STA $00, LDA $4016, STA $01, BRK
+        let program = [
+            0xa9, 0x01, 0x8d, 0x16, 0x40, 0xa9, 0x00, 0x8d, 0x16, 0x40, 0xad, 0x16, 0x40, 0x85, 0x00, 0xad, 0x16,
+            0x40, 0x85, 0x01, 0x00,
+        ];
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&program, &[]), vec![], Mirroring::Horizontal));
+        cpu.bus.joypad_mut(0).unwrap().set_pressed(Button::A, true);
+        cpu.reset();
+        cpu.run();
+
+        // The high byte of the address is the last thing on the bus before the controller answers
+        assert_eq!(cpu.mem_read(0x0000), 0x41);
+        assert_eq!(cpu.mem_read(0x0001), 0x40);
+    }
+
+    #[test]
+    fn test_four_score_players() {
+        let mut bus = NesBus::new(nes_rom(&[], &[]), vec![], Mirroring::Horizontal);
+        assert!(bus.player_mut(2).is_none());
+        bus.connect_multitap(MultitapKind::FourScore);
+        bus.player_mut(3).unwrap().set_pressed(Button::B, true);
+
+        bus.mem_write(0x4016, 1);
+        bus.mem_write(0x4016, 0);
+        // Player 4 is the second joypad on port 2
+        let bits: Vec<u8> = (0..24).map(|_| bus.mem_read(0x4017) & 1).collect();
+        assert_eq!(bits[8..16], [0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(bits[16..], [0, 0, 1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_oam_dma() {
+        // LDA #$02, STA $4014, BRK
+        let program = [0xa9, 0x02, 0x8d, 0x14, 0x40, 0x00];
+        let mut cpu = Cpu::with_bus(NesBus::new(nes_rom(&program, &[]), vec![], Mirroring::Horizontal));
+        for i in 0..=255u8 {
+            cpu.mem_write(0x0200 + i as u16, i);
+        }
+        cpu.reset();
+        cpu.step();
+        cpu.step();
+        let cycles = cpu.cycles;
+        cpu.step();
+
+        // 513 or 514 stolen cycles, then the 7 of BRK
+        assert!((cpu.cycles - cycles) == 513 + 7 || (cpu.cycles - cycles) == 514 + 7);
+        assert!(cpu.bus.ppu.oam_data.iter().enumerate().all(|(i, &byte)| byte == i as u8));
+    }
+}
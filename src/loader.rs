@@ -0,0 +1,236 @@
+//! Program images for bare 6502 machines: raw binaries, Intel HEX and Motorola S-records
+
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// Data that would run past $FFFF, or a start address that doesn't fit in 16 bits
+    OutOfRange { address: u32, length: usize },
+    /// A malformed record in a HEX or S-record file
+    Syntax {
+        /// 1-based
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::OutOfRange { address, length } => {
+                write!(f, "{length} bytes at ${address:X} don't fit in 64KiB")
+            }
+            LoadError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Where to start running once an image is loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPoint {
+    Address(u16),
+    /// Run the reset sequence, which jumps through $FFFC
+    ResetVector,
+    /// The start address recorded in the file, or the reset vector if there is none
+    FromImage,
+}
+
+/// Bytes to place in memory, possibly in several pieces
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Image {
+    /// (address, data) in the order the file has them
+    pub segments: Vec<(u16, Vec<u8>)>,
+    /// The start address the file gives, if any
+    pub entry: Option<u16>,
+}
+
+impl Image {
+    /// A raw binary belonging at `origin`
+    pub fn raw(origin: u16, data: &[u8]) -> Self {
+        Self { segments: vec![(origin, data.to_vec())], entry: None }
+    }
+
+    /// Parses Intel HEX, with the 16 bit data and EOF records plus the segment and linear address records as long
+    /// as they keep everything below $10000
+    pub fn from_intel_hex(text: &str) -> Result<Self, LoadError> {
+        let mut image = Self::default();
+        let mut base = 0;
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: &str| LoadError::Syntax { line: index + 1, message: message.to_string() };
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let record = line.strip_prefix(':').ok_or_else(|| error("records start with ':'"))?;
+            let bytes = hex_bytes(record).ok_or_else(|| error("not hexadecimal"))?;
+            let [length, high, low, kind, ..] = bytes[..] else {
+                return Err(error("record too short"));
+            };
+            if bytes.len() != length as usize + 5 {
+                return Err(error("length doesn't match the record"));
+            }
+            if bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0 {
+                return Err(error("bad checksum"));
+            }
+            let data = &bytes[4..bytes.len() - 1];
+            let number = || data.iter().fold(0u32, |value, &byte| value << 8 | byte as u32);
+            match (kind, data.len()) {
+                (0x00, _) => image.add(base + u16::from_be_bytes([high, low]) as u32, data)?,
+                (0x01, 0) => break,
+                (0x02, 2) => base = number() << 4,
+                (0x03, 4) => image.set_entry((number() >> 16 << 4) + (number() & 0xFFFF))?,
+                (0x04, 2) => base = number() << 16,
+                (0x05, 4) => image.set_entry(number())?,
+                (0x01..=0x05, _) => return Err(error("wrong length for the record type")),
+                _ => return Err(error(&format!("unknown record type {kind:02X}"))),
+            }
+        }
+        Ok(image)
+    }
+
+    /// Parses Motorola S-records. S1, S2 and S3 carry data, S7, S8 and S9 the start address, the header and
+    /// count records are skipped.
+    pub fn from_srecord(text: &str) -> Result<Self, LoadError> {
+        let mut image = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: &str| LoadError::Syntax { line: index + 1, message: message.to_string() };
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut chars = line.chars();
+            let (Some('S'), Some(kind)) = (chars.next(), chars.next()) else {
+                return Err(error("records start with 'S' and a type"));
+            };
+            let bytes = hex_bytes(chars.as_str()).ok_or_else(|| error("not hexadecimal"))?;
+            let Some((&count, rest)) = bytes.split_first() else {
+                return Err(error("record too short"));
+            };
+            if rest.len() != count as usize {
+                return Err(error("count doesn't match the record"));
+            }
+            if bytes.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) != 0xFF {
+                return Err(error("bad checksum"));
+            }
+            let address_length = match kind {
+                '0' | '1' | '5' | '9' => 2,
+                '2' | '6' | '8' => 3,
+                '3' | '7' => 4,
+                _ => return Err(error(&format!("unknown record type S{kind}"))),
+            };
+            if rest.len() < address_length + 1 {
+                return Err(error("record too short"));
+            }
+            let (address, data) = rest[..rest.len() - 1].split_at(address_length);
+            let address = address.iter().fold(0u32, |value, &byte| value << 8 | byte as u32);
+            match kind {
+                '1' | '2' | '3' => image.add(address, data)?,
+                '7' | '8' | '9' => image.set_entry(address)?,
+                _ => {}
+            }
+        }
+        Ok(image)
+    }
+
+    /// Fails if any segment runs past $FFFF
+    pub fn check(&self) -> Result<(), LoadError> {
+        for (address, data) in &self.segments {
+            if *address as usize + data.len() > 0x10000 {
+                return Err(LoadError::OutOfRange { address: *address as u32, length: data.len() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends `data`, extending the last segment if it carries straight on from it
+    fn add(&mut self, address: u32, data: &[u8]) -> Result<(), LoadError> {
+        if address as usize + data.len() > 0x10000 {
+            return Err(LoadError::OutOfRange { address, length: data.len() });
+        }
+        match self.segments.last_mut() {
+            Some((start, segment)) if *start as usize + segment.len() == address as usize => {
+                segment.extend_from_slice(data)
+            }
+            _ => self.segments.push((address as u16, data.to_vec())),
+        }
+        Ok(())
+    }
+
+    fn set_entry(&mut self, address: u32) -> Result<(), LoadError> {
+        let address = u16::try_from(address).map_err(|_| LoadError::OutOfRange { address, length: 0 })?;
+        self.entry = Some(address);
+        Ok(())
+    }
+}
+
+/// Pairs of hex digits to bytes
+fn hex_bytes(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cpu::Cpu;
+
+    #[test]
+    fn test_intel_hex() {
+        let text = ":03060000A942000C\n:020000040000FA\n\n:02FFFC000006FD\n:0400000500000600F1\n:00000001FF\n";
+        let image = Image::from_intel_hex(text).unwrap();
+        assert_eq!(image.segments, [(0x0600, vec![0xA9, 0x42, 0x00]), (0xFFFC, vec![0x00, 0x06])]);
+        assert_eq!(image.entry, Some(0x0600));
+
+        // $FFFF plus two bytes, and a linear base of $10000
+        let error = Image::from_intel_hex(":02FFFF000102FD").unwrap_err();
+        assert_eq!(error, LoadError::OutOfRange { address: 0xFFFF, length: 2 });
+        assert!(matches!(
+            Image::from_intel_hex(":020000040001F9\n:03060000A942000C"),
+            Err(LoadError::OutOfRange { address: 0x10600, .. })
+        ));
+        assert_eq!(
+            Image::from_intel_hex(":03060000A942000D"),
+            Err(LoadError::Syntax { line: 1, message: "bad checksum".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_srecord() {
+        let text = "S0050000686929\nS1060600A9420008\nS205000603EA07\nS9030600F6\n";
+        let image = Image::from_srecord(text).unwrap();
+        assert_eq!(image.segments, [(0x0600, vec![0xA9, 0x42, 0x00, 0xEA])]);
+        assert_eq!(image.entry, Some(0x0600));
+
+        assert!(matches!(Image::from_srecord("S3060001000001F7"), Err(LoadError::OutOfRange { .. })));
+        assert!(matches!(Image::from_srecord("S1060600A9420009"), Err(LoadError::Syntax { line: 1, .. })));
+    }
+
+    #[test]
+    fn test_entry_points() {
+        let mut cpu = Cpu::new();
+        // LDA #$42, BRK at $0600 with the reset vector pointing there
+        let mut image = Image::raw(0x0600, &[0xA9, 0x42, 0x00]);
+        image.segments.push((0xFFFC, vec![0x00, 0x06]));
+        cpu.load_image(&image, EntryPoint::ResetVector).unwrap();
+        assert_eq!(cpu.registers.program_counter, 0x0600);
+        cpu.run();
+        assert_eq!(cpu.registers.a, 0x42);
+
+        cpu.load_at(0x1000, &[0xA9, 0x07, 0x00], EntryPoint::Address(0x1000)).unwrap();
+        cpu.run();
+        assert_eq!(cpu.registers.a, 0x07);
+
+        // Nothing is copied when part of the image doesn't fit
+        assert_eq!(
+            cpu.load_at(0xFFFE, &[1, 2, 3], EntryPoint::FromImage),
+            Err(LoadError::OutOfRange { address: 0xFFFE, length: 3 })
+        );
+        assert_eq!(cpu.mem_read(0xFFFE), 0x00);
+    }
+}
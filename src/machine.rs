@@ -0,0 +1,274 @@
+//! Bare 6502 computers put together from RAM, ROM and memory mapped devices, for single board designs rather
+//! than the NES.
+//!
+//! A machine in the style of Grant Searle's or Ben Eater's, with an ACIA for a terminal:
+//!
+//! ```no_run
+//! use rnes::machine::{uart::{StdioSerial, Uart}, Machine, MachineBus};
+//! # let basic_rom = vec![0; 0x4000];
+//!
+//! let mut bus = MachineBus::new();
+//! bus.map_ram(0x0000..=0x7FFF);
+//! bus.map_device(0xA000..=0xA001, Uart::new(StdioSerial::new()));
+//! bus.map_rom(0xC000, &basic_rom);
+//! let mut machine = Machine::new(bus);
+//! machine.run();
+//! ```
+
+pub mod uart;
+
+use std::{any::Any, ops::RangeInclusive};
+
+use crate::{
+    bus::Bus,
+    cpu::{Cpu, Jam},
+    opcodes::Opcode,
+};
+
+/// Something on the address bus other than memory. Offsets are from the start of the device's region.
+pub trait Device: Any {
+    fn read(&mut self, offset: u16) -> u8;
+    fn write(&mut self, offset: u16, data: u8);
+    /// Called once per CPU cycle, before the access of that cycle
+    fn tick(&mut self) {}
+    /// Level of the device's interrupt output, true when it is pulling /IRQ low
+    fn irq(&self) -> bool {
+        false
+    }
+    /// For getting at the concrete device through `MachineBus::device_mut`
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+enum Contents {
+    Ram(Vec<u8>),
+    Rom(Vec<u8>),
+    Device(Box<dyn Device>),
+}
+
+struct Region {
+    range: RangeInclusive<u16>,
+    contents: Contents,
+}
+
+/// An address space declared region by region. Regions mapped later sit on top of earlier ones, so RAM can
+/// cover everything and devices then take over a few addresses of it. Nothing answers outside the regions, reads
+/// there see whatever was last on the data bus.
+#[derive(Default)]
+pub struct MachineBus {
+    regions: Vec<Region>,
+    /// The last value driven onto the data bus
+    data_bus: u8,
+}
+
+impl MachineBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn map_ram(&mut self, range: RangeInclusive<u16>) {
+        let size = range.len();
+        self.regions.push(Region { range, contents: Contents::Ram(vec![0; size]) });
+    }
+
+    /// Maps `data` at `start`, writes to it are ignored. Panics if it runs past $FFFF.
+    pub fn map_rom(&mut self, start: u16, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let end = start as usize + data.len() - 1;
+        let end = u16::try_from(end).unwrap_or_else(|_| panic!("ROM at ${start:04X} runs past $FFFF"));
+        self.regions.push(Region { range: start..=end, contents: Contents::Rom(data.to_vec()) });
+    }
+
+    pub fn map_device(&mut self, range: RangeInclusive<u16>, device: impl Device) {
+        self.regions.push(Region { range, contents: Contents::Device(Box::new(device)) });
+    }
+
+    /// The device mapped at `address` if it is a `T`
+    pub fn device_mut<T: Device>(&mut self, address: u16) -> Option<&mut T> {
+        match &mut self.region_mut(address)?.contents {
+            Contents::Device(device) => device.as_any_mut().downcast_mut(),
+            _ => None,
+        }
+    }
+
+    /// Copies `data` into whatever RAM and ROM is at `address` onwards, skipping devices and unmapped addresses.
+    /// For putting programs in place before running them.
+    pub fn load(&mut self, address: u16, data: &[u8]) {
+        for (address, &byte) in (address..=0xFFFF).zip(data) {
+            if let Some(region) = self.region_mut(address) {
+                let offset = (address - region.range.start()) as usize;
+                if let Contents::Ram(memory) | Contents::Rom(memory) = &mut region.contents {
+                    memory[offset] = byte;
+                }
+            }
+        }
+    }
+
+    fn region_mut(&mut self, address: u16) -> Option<&mut Region> {
+        self.regions.iter_mut().rev().find(|region| region.range.contains(&address))
+    }
+
+    fn tick(&mut self) {
+        for region in &mut self.regions {
+            if let Contents::Device(device) = &mut region.contents {
+                device.tick();
+            }
+        }
+    }
+}
+
+impl Bus for MachineBus {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        self.tick();
+        let data_bus = self.data_bus;
+        let data = match self.region_mut(address) {
+            Some(region) => {
+                let offset = address - region.range.start();
+                match &mut region.contents {
+                    Contents::Ram(memory) | Contents::Rom(memory) => memory[offset as usize],
+                    Contents::Device(device) => device.read(offset),
+                }
+            }
+            None => data_bus,
+        };
+        self.data_bus = data;
+        data
+    }
+    fn mem_write(&mut self, address: u16, data: u8) {
+        self.tick();
+        self.data_bus = data;
+        if let Some(region) = self.region_mut(address) {
+            let offset = address - region.range.start();
+            match &mut region.contents {
+                Contents::Ram(memory) => memory[offset as usize] = data,
+                Contents::Rom(_) => {}
+                Contents::Device(device) => device.write(offset, data),
+            }
+        }
+    }
+    fn irq_line(&self) -> bool {
+        self.regions.iter().any(|region| matches!(&region.contents, Contents::Device(device) if device.irq()))
+    }
+    fn peek(&self, address: u16) -> Option<u8> {
+        let region = self.regions.iter().rev().find(|region| region.range.contains(&address))?;
+        match &region.contents {
+            Contents::Ram(memory) | Contents::Rom(memory) => Some(memory[(address - region.range.start()) as usize]),
+            Contents::Device(_) => None,
+        }
+    }
+    /// Also changes ROM
+    fn poke(&mut self, address: u16, data: u8) {
+        if let Some(region) = self.region_mut(address) {
+            let offset = address - region.range.start();
+            match &mut region.contents {
+                Contents::Ram(memory) | Contents::Rom(memory) => memory[offset as usize] = data,
+                Contents::Device(device) => device.write(offset, data),
+            }
+        }
+    }
+}
+
+/// A 6502 on a `MachineBus`
+pub struct Machine {
+    pub cpu: Cpu<MachineBus>,
+}
+
+impl Machine {
+    /// Powers on, starting from the reset vector. Set `cpu.variant` and call `reset` again for a 65C02 or NMOS
+    /// 6502 board, the default is the 2A03 which has no decimal mode.
+    pub fn new(bus: MachineBus) -> Self {
+        let mut cpu = Cpu::with_bus(bus);
+        cpu.reset();
+        Self { cpu }
+    }
+
+    pub fn reset(&mut self) {
+        self.cpu.reset();
+    }
+
+    pub fn step(&mut self) -> Opcode {
+        self.cpu.step()
+    }
+
+    /// Runs for at least `cycles` more CPU cycles, stopping early if the CPU jams
+    pub fn run_for(&mut self, cycles: u64) -> Result<(), Jam> {
+        let end = self.cpu.cycles + cycles;
+        while self.cpu.cycles < end {
+            self.cpu.step();
+            if let Some(jam) = self.cpu.jam() {
+                return Err(jam);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs until the CPU jams, which on a board with a terminal usually means forever
+    pub fn run(&mut self) -> Jam {
+        loop {
+            if let Err(jam) = self.run_for(u64::MAX - self.cpu.cycles) {
+                return jam;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        asm::assemble,
+        cpu::CpuVariant,
+        machine::uart::{BufferedSerial, Uart},
+    };
+
+    /// RAM at the bottom, a UART at $D000 and `source` assembled into ROM at $E000
+    fn machine(source: &str) -> Machine {
+        let program = assemble(&format!(".org $E000\n{source}"), CpuVariant::Wdc65C02).unwrap();
+        let mut rom = program.bytes;
+        rom.resize(0x2000, 0);
+        rom[0x1FFC..0x1FFE].copy_from_slice(&0xE000u16.to_le_bytes());
+
+        let mut bus = MachineBus::new();
+        bus.map_ram(0x0000..=0x7FFF);
+        bus.map_device(0xD000..=0xD001, Uart::new(BufferedSerial::default()));
+        bus.map_rom(0xE000, &rom);
+        let mut machine = Machine::new(bus);
+        machine.cpu.variant = CpuVariant::Wdc65C02;
+        machine
+    }
+
+    #[test]
+    fn test_echo_upper_case() {
+        let mut machine = machine(
+            "
+            loop:   LDA $D000
+                    AND #$01
+                    BEQ loop
+                    LDA $D001
+                    AND #$DF
+                    STA $D001
+                    BRA loop
+            ",
+        );
+        machine.cpu.bus.device_mut::<Uart<BufferedSerial>>(0xD000).unwrap().serial.input.extend(b"hello");
+        machine.run_for(1000).unwrap();
+        assert_eq!(machine.cpu.bus.device_mut::<Uart<BufferedSerial>>(0xD001).unwrap().serial.output, b"HELLO");
+    }
+
+    #[test]
+    fn test_regions() {
+        let mut machine = machine("STP");
+        let bus = &mut machine.cpu.bus;
+        // ROM ignores writes, unmapped space reads back the bus
+        bus.mem_write(0xE000, 0x12);
+        assert_eq!(bus.mem_read(0xE000), 0xDB);
+        assert_eq!(bus.mem_read(0x9000), 0xDB);
+        bus.map_ram(0x9000..=0x90FF);
+        bus.load(0x90FE, &[1, 2, 3]);
+        assert_eq!([bus.mem_read(0x90FF), bus.mem_read(0x9100)], [2, 2]);
+        assert!(bus.device_mut::<Uart<BufferedSerial>>(0x9000).is_none());
+
+        assert_eq!(machine.run(), Jam { address: 0xE000, opcode: 0xDB });
+    }
+}
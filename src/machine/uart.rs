@@ -0,0 +1,135 @@
+use std::{
+    any::Any,
+    collections::VecDeque,
+    io::{self, Read, Write},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use super::Device;
+
+/// Where a UART's bytes come from and go to
+pub trait Serial {
+    /// The next byte that has arrived, if there is one
+    fn receive(&mut self) -> Option<u8>;
+    fn transmit(&mut self, data: u8);
+}
+
+/// Serial through in-memory buffers, for tests and scripted sessions
+#[derive(Debug, Default)]
+pub struct BufferedSerial {
+    pub input: VecDeque<u8>,
+    pub output: Vec<u8>,
+}
+
+impl Serial for BufferedSerial {
+    fn receive(&mut self) -> Option<u8> {
+        self.input.pop_front()
+    }
+    fn transmit(&mut self, data: u8) {
+        self.output.push(data);
+    }
+}
+
+/// Serial to the terminal. Stdin is read on a thread of its own so the CPU never waits for a key, bytes are
+/// passed on untranslated.
+pub struct StdioSerial {
+    input: Receiver<u8>,
+}
+
+impl StdioSerial {
+    pub fn new() -> Self {
+        let (sender, input) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in io::stdin().lock().bytes() {
+                let Ok(byte) = byte else { break };
+                if sender.send(byte).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { input }
+    }
+}
+
+impl Default for StdioSerial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serial for StdioSerial {
+    fn receive(&mut self) -> Option<u8> {
+        self.input.try_recv().ok()
+    }
+    fn transmit(&mut self, data: u8) {
+        let mut stdout = io::stdout().lock();
+        // Nowhere to report a closed terminal to, the program carries on regardless
+        let _ = stdout.write_all(&[data]).and_then(|_| stdout.flush());
+    }
+}
+
+const RECEIVE_FULL: u8 = 0b0000_0001;
+const TRANSMIT_EMPTY: u8 = 0b0000_0010;
+const INTERRUPT_REQUEST: u8 = 0b1000_0000;
+const MASTER_RESET: u8 = 0b0000_0011;
+const RECEIVE_INTERRUPT_ENABLE: u8 = 0b1000_0000;
+/// Bits 5 and 6 of the control register, 01 enables the transmit interrupt
+const TRANSMIT_CONTROL: u8 = 0b0110_0000;
+const TRANSMIT_INTERRUPT_ENABLE: u8 = 0b0010_0000;
+
+/// A UART with the registers of a 6850 ACIA, two addresses wide. Even offsets are the status register when read
+/// and the control register when written, odd offsets receive and transmit data.
+///
+/// Transmitting takes no time, so the transmit register is always empty. The format and clock divider bits of
+/// the control register are ignored, only master reset and the interrupt enables do anything.
+pub struct Uart<S: Serial> {
+    pub serial: S,
+    /// The receive data register, RECEIVE_FULL says whether it holds a byte that hasn't been read
+    received: u8,
+    status: u8,
+    control: u8,
+}
+
+impl<S: Serial> Uart<S> {
+    pub fn new(serial: S) -> Self {
+        Self { serial, received: 0, status: TRANSMIT_EMPTY, control: 0 }
+    }
+}
+
+impl<S: Serial + 'static> Device for Uart<S> {
+    fn read(&mut self, offset: u16) -> u8 {
+        if offset & 1 == 0 {
+            self.status | if self.irq() { INTERRUPT_REQUEST } else { 0 }
+        } else {
+            self.status &= !RECEIVE_FULL;
+            self.received
+        }
+    }
+    fn write(&mut self, offset: u16, data: u8) {
+        if offset & 1 == 1 {
+            self.serial.transmit(data);
+        } else if data & MASTER_RESET == MASTER_RESET {
+            self.status = TRANSMIT_EMPTY;
+            self.control = 0;
+        } else {
+            self.control = data;
+        }
+    }
+    fn tick(&mut self) {
+        if self.status & RECEIVE_FULL == 0 {
+            if let Some(data) = self.serial.receive() {
+                self.received = data;
+                self.status |= RECEIVE_FULL;
+            }
+        }
+    }
+    fn irq(&self) -> bool {
+        let receive = self.control & RECEIVE_INTERRUPT_ENABLE != 0 && self.status & RECEIVE_FULL != 0;
+        let transmit = self.control & TRANSMIT_CONTROL == TRANSMIT_INTERRUPT_ENABLE;
+        receive || transmit
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -1,3 +1,97 @@
+use std::{env, fs, io, path::PathBuf, process, sync::atomic::Ordering};
+
+use rnes::{
+    cartridge::Rom,
+    console::Console,
+    controller::multitap::MultitapKind,
+    debugger::Debugger,
+    headless::{self, HeadlessOptions, InputScript},
+};
+
+const USAGE: &str = "usage: rnes --headless <rom.nes> --wav <out.wav> [--frames <n>] [--input <script>] \
+                     [--four-score | --hori] [--sample-rate <hz>] [--stems] [--no-filter]
+       rnes --debug <rom.nes>";
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = if args.iter().any(|arg| arg == "--headless") {
+        run_headless(&args)
+    } else if args.iter().any(|arg| arg == "--debug") {
+        run_debugger(&args)
+    } else {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    };
+    if let Err(message) = result {
+        eprintln!("{message}");
+        process::exit(1);
+    }
+}
+
+fn run_headless(args: &[String]) -> Result<(), String> {
+    let mut options = HeadlessOptions::default();
+    let mut rom_path = None;
+    let mut wav_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value\n{USAGE}"));
+        match arg.as_str() {
+            "--headless" => {}
+            "--wav" => wav_path = Some(PathBuf::from(value("--wav")?)),
+            "--frames" => {
+                options.frames = value("--frames")?.parse().map_err(|_| "--frames must be a number")?;
+            }
+            "--sample-rate" => {
+                options.sample_rate =
+                    value("--sample-rate")?.parse().map_err(|_| "--sample-rate must be a number")?;
+            }
+            "--input" => {
+                let path = value("--input")?;
+                let text = fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+                options.input = InputScript::parse(&text).map_err(|error| format!("{path}: {error}"))?;
+            }
+            "--four-score" => options.multitap = Some(MultitapKind::FourScore),
+            "--hori" => options.multitap = Some(MultitapKind::Hori),
+            "--stems" => options.stems = true,
+            "--no-filter" => options.filters = false,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}\n{USAGE}")),
+            _ => rom_path = Some(arg.clone()),
+        }
+    }
+
+    let rom_path = rom_path.ok_or(USAGE)?;
+    let wav_path = wav_path.ok_or(USAGE)?;
+
+    let raw = fs::read(&rom_path).map_err(|error| format!("{rom_path}: {error}"))?;
+    let rom = Rom::new(&raw).map_err(|error| format!("{rom_path}: {error}"))?;
+    let recording = headless::run(rom, &options).map_err(|error| format!("{rom_path}: {error}"))?;
+    if let Some(jam) = recording.jam {
+        eprintln!("{rom_path}: warning: {jam}");
+    }
+    recording
+        .write_wavs(&wav_path)
+        .map_err(|error| format!("{}: {error}", wav_path.display()))
+}
+
+/// Loads the ROM and hands the CPU to the debugger, stopped at the reset vector
+fn run_debugger(args: &[String]) -> Result<(), String> {
+    let mut rom_path = None;
+    for arg in args {
+        match arg.as_str() {
+            "--debug" => {}
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}\n{USAGE}")),
+            _ => rom_path = Some(arg.clone()),
+        }
+    }
+    let rom_path = rom_path.ok_or(USAGE)?;
+
+    let raw = fs::read(&rom_path).map_err(|error| format!("{rom_path}: {error}"))?;
+    let rom = Rom::new(&raw).map_err(|error| format!("{rom_path}: {error}"))?;
+    let mut console = Console::new(rom).map_err(|error| format!("{rom_path}: {error}"))?;
+    let mut debugger = Debugger::new();
+    // Ctrl-C stops a running command and goes back to the prompt instead of ending the program
+    let interrupt = debugger.interrupt_flag();
+    ctrlc::set_handler(move || interrupt.store(true, Ordering::Relaxed)).map_err(|error| error.to_string())?;
+    debugger.run(&mut console.cpu, io::stdin().lock(), io::stdout()).map_err(|error| error.to_string())
 }
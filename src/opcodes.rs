@@ -15,6 +15,10 @@ impl Opcode {
     pub fn addressing_mode(&self) -> AddressingMode {
         self.addressing_mode
     }
+    /// Size of the instruction in bytes, the opcode included
+    pub fn length(&self) -> u16 {
+        1 + self.addressing_mode.operand_length()
+    }
 }
 
 /// Names or acronyms of all the opcodes, copied from https://github.com/mre/mos6502/blob/master/src/instruction.rs
@@ -79,10 +83,76 @@ pub enum OpcodeName {
     TXA, // Transfer X to Accumulator..... | N. ...Z. A            = X
     TXS, // Transfer X to Stack pointer... | .. .....       S      = X
     TYA, // Transfer Y to Accumulator..... | N. ...Z. A            = Y
+
+    // Unofficial opcodes, names from "No More Secrets" (NMOS 6510 Unintended Opcodes)
+    ALR, // AND then LSR A................ | N. ...ZC A            = (A & M) / 2
+    ANC, // AND, Carry from bit 7......... | N. ...ZC A            = A & M
+    ARR, // AND then ROR A................ | NV ...ZC A            = C (A & M) rotated
+    DCP, // DEC then CMP.................. | N. ...ZC            M = M - 1
+    ISC, // INC then SBC.................. | NV ...ZC A          M = M + 1
+    LAS, // LDA/TSX with Stack pointer.... | N. ...Z. A X   S      = M & S
+    LAX, // LDA and LDX................... | N. ...Z. A X          = M
+    LXA, // LAX immediate, unstable....... | N. ...Z. A X          = (A | magic) & M
+    RLA, // ROL then AND.................. | N. ...ZC A          M = C M rotated
+    RRA, // ROR then ADC.................. | NV ...ZC A          M = C M rotated
+    SAX, // Store A AND X................. | .. .....            M = A & X
+    SBX, // Subtract from A AND X (AXS)... | N. ...ZC   X          = (A & X) - M
+    SHA, // Store A AND X AND High byte... | .. .....            M = A & X & (H + 1)
+    SHX, // Store X AND High byte......... | .. .....            M = X & (H + 1)
+    SHY, // Store Y AND High byte......... | .. .....            M = Y & (H + 1)
+    SLO, // ASL then ORA.................. | N. ...ZC A          M = M << 1
+    SRE, // LSR then EOR.................. | N. ...ZC A          M = M / 2
+    TAS, // Transfer A AND X to S, SHA.... | .. .....       S    M = A & X & (H + 1)
+    XAA, // TXA then AND, unstable........ | N. ...Z. A            = (A | magic) & X & M
+    JAM, // JAM the CPU (KIL)............. | .. .....              =   until reset
+
+    // 65C02 additions
+    BRA, // BRanch Always................. | .. .....         PC   =
+    PHX, // PusH X register............... | .. .....       S    M = X
+    PHY, // PusH Y register............... | .. .....       S    M = Y
+    PLX, // PuLl X register............... | N. ...Z.   X   S      = M (stack)
+    PLY, // PuLl Y register............... | N. ...Z.     Y S      = M (stack)
+    STP, // SToP the clock................ | .. .....              =   until reset
+    STZ, // STore Zero.................... | .. .....            M = 0
+    TRB, // Test and Reset Bits........... | .. ...Z.            M = M & !A
+    TSB, // Test and Set Bits............. | .. ...Z.            M = M | A
+    WAI, // WAit for Interrupt............ | .. .....              =
+    RMB0, // Reset Memory Bit 0........... | .. .....            M = M & !(1 << 0)
+    RMB1, // Reset Memory Bit 1........... | .. .....            M = M & !(1 << 1)
+    RMB2, // Reset Memory Bit 2........... | .. .....            M = M & !(1 << 2)
+    RMB3, // Reset Memory Bit 3........... | .. .....            M = M & !(1 << 3)
+    RMB4, // Reset Memory Bit 4........... | .. .....            M = M & !(1 << 4)
+    RMB5, // Reset Memory Bit 5........... | .. .....            M = M & !(1 << 5)
+    RMB6, // Reset Memory Bit 6........... | .. .....            M = M & !(1 << 6)
+    RMB7, // Reset Memory Bit 7........... | .. .....            M = M & !(1 << 7)
+    SMB0, // Set Memory Bit 0............. | .. .....            M = M | 1 << 0
+    SMB1, // Set Memory Bit 1............. | .. .....            M = M | 1 << 1
+    SMB2, // Set Memory Bit 2............. | .. .....            M = M | 1 << 2
+    SMB3, // Set Memory Bit 3............. | .. .....            M = M | 1 << 3
+    SMB4, // Set Memory Bit 4............. | .. .....            M = M | 1 << 4
+    SMB5, // Set Memory Bit 5............. | .. .....            M = M | 1 << 5
+    SMB6, // Set Memory Bit 6............. | .. .....            M = M | 1 << 6
+    SMB7, // Set Memory Bit 7............. | .. .....            M = M | 1 << 7
+    BBR0, // Branch if Bit 0 Reset........ | .. .....         PC   = !M0
+    BBR1, // Branch if Bit 1 Reset........ | .. .....         PC   = !M1
+    BBR2, // Branch if Bit 2 Reset........ | .. .....         PC   = !M2
+    BBR3, // Branch if Bit 3 Reset........ | .. .....         PC   = !M3
+    BBR4, // Branch if Bit 4 Reset........ | .. .....         PC   = !M4
+    BBR5, // Branch if Bit 5 Reset........ | .. .....         PC   = !M5
+    BBR6, // Branch if Bit 6 Reset........ | .. .....         PC   = !M6
+    BBR7, // Branch if Bit 7 Reset........ | .. .....         PC   = !M7
+    BBS0, // Branch if Bit 0 Set.......... | .. .....         PC   = M0
+    BBS1, // Branch if Bit 1 Set.......... | .. .....         PC   = M1
+    BBS2, // Branch if Bit 2 Set.......... | .. .....         PC   = M2
+    BBS3, // Branch if Bit 3 Set.......... | .. .....         PC   = M3
+    BBS4, // Branch if Bit 4 Set.......... | .. .....         PC   = M4
+    BBS5, // Branch if Bit 5 Set.......... | .. .....         PC   = M5
+    BBS6, // Branch if Bit 6 Set.......... | .. .....         PC   = M6
+    BBS7, // Branch if Bit 7 Set.......... | .. .....         PC   = M7
 }
 
 /// addressing modes, copied from https://github.com/mre/mos6502/blob/master/src/instruction.rs
-#[derive(Display, Clone, Copy)]
+#[derive(Display, Clone, Copy, PartialEq, Eq)]
 pub enum AddressingMode {
     Accumulator,      // 1    LSR A        work directly on accumulator
     Implied,          // 1    BRK
@@ -99,520 +169,1064 @@ pub enum AddressingMode {
     //                   zero page address plus X register)
     IndirectIndexedY, // 2    LDA ($10),Y  load from (address stored at constant
                       //                   zero page address) plus Y register
+    // 65C02 only
+    ZeroPageIndirect,        // 2    LDA ($10)      load from address stored at zero page address
+    AbsoluteIndexedIndirect, // 3    JMP ($1000,X)  jump to address stored at address plus X register
+    ZeroPageRelative,        // 3    BBR0 $10,LABEL test a zero page bit, then branch
+}
+
+impl AddressingMode {
+    /// Bytes following the opcode
+    pub fn operand_length(self) -> u16 {
+        match self {
+            AddressingMode::Accumulator | AddressingMode::Implied => 0,
+            AddressingMode::Immediate
+            | AddressingMode::ZeroPage
+            | AddressingMode::ZeroPageX
+            | AddressingMode::ZeroPageY
+            | AddressingMode::Relative
+            | AddressingMode::IndexedIndirectX
+            | AddressingMode::IndirectIndexedY
+            | AddressingMode::ZeroPageIndirect => 1,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect
+            | AddressingMode::AbsoluteIndexedIndirect
+            | AddressingMode::ZeroPageRelative => 2,
+        }
+    }
 }
 
 /// Lookup table for all the Opcodes
-pub static OPCODES: [Option<Opcode>; 256] = [
+pub static OPCODES: [Opcode; 256] = [
+    // 0x00
+    Opcode { name: OpcodeName::BRK, addressing_mode: AddressingMode::Implied },
+    // 0x01
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndexedIndirectX},
+    // 0x02
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x03
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x04
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPage },
+    // 0x05
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPage },
+    // 0x06
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPage },
+    // 0x07
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::ZeroPage },
+    // 0x08
+    Opcode { name: OpcodeName::PHP, addressing_mode: AddressingMode::Implied },
+    // 0x09
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Immediate },
+    // 0x0A
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Accumulator },
+    // 0x0B
+    Opcode { name: OpcodeName::ANC, addressing_mode: AddressingMode::Immediate },
+    // 0x0C
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Absolute },
+    // 0x0D
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Absolute },
+    // 0x0E
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Absolute },
+    // 0x0F
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::Absolute },
+    // 0x10
+    Opcode { name: OpcodeName::BPL, addressing_mode: AddressingMode::Relative },
+    // 0x11
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x12
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x13
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x14
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x15
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x16
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x17
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x18
+    Opcode { name: OpcodeName::CLC, addressing_mode: AddressingMode::Implied },
+    // 0x19
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x1A
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0x1B
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x1C
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x1D
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x1E
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x1F
+    Opcode { name: OpcodeName::SLO, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x20
+    Opcode { name: OpcodeName::JSR, addressing_mode: AddressingMode::Absolute },
+    // 0x21
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x22
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x23
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x24
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::ZeroPage },
+    // 0x25
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPage },
+    // 0x26
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPage },
+    // 0x27
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::ZeroPage },
+    // 0x28
+    Opcode { name: OpcodeName::PLP, addressing_mode: AddressingMode::Implied },
+    // 0x29
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Immediate },
+    // 0x2A
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Accumulator },
+    // 0x2B
+    Opcode { name: OpcodeName::ANC, addressing_mode: AddressingMode::Immediate },
+    // 0x2C
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::Absolute },
+    // 0x2D
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Absolute },
+    // 0x2E
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Absolute },
+    // 0x2F
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::Absolute },
+    // 0x30
+    Opcode { name: OpcodeName::BMI, addressing_mode: AddressingMode::Relative },
+    // 0x31
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x32
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x33
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x34
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x35
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x36
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x37
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x38
+    Opcode { name: OpcodeName::SEC, addressing_mode: AddressingMode::Implied },
+    // 0x39
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x3A
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0x3B
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x3C
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x3D
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x3E
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x3F
+    Opcode { name: OpcodeName::RLA, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x40
+    Opcode { name: OpcodeName::RTI, addressing_mode: AddressingMode::Implied },
+    // 0x41
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x42
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x43
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x44
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPage },
+    // 0x45
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPage },
+    // 0x46
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPage },
+    // 0x47
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::ZeroPage },
+    // 0x48
+    Opcode { name: OpcodeName::PHA, addressing_mode: AddressingMode::Implied },
+    // 0x49
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Immediate },
+    // 0x4A
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Accumulator },
+    // 0x4B
+    Opcode { name: OpcodeName::ALR, addressing_mode: AddressingMode::Immediate },
+    // 0x4C
+    Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Absolute },
+    // 0x4D
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Absolute },
+    // 0x4E
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Absolute },
+    // 0x4F
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::Absolute },
+    // 0x50
+    Opcode { name: OpcodeName::BVC, addressing_mode: AddressingMode::Relative },
+    // 0x51
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x52
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x53
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x54
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x55
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x56
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x57
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x58
+    Opcode { name: OpcodeName::CLI, addressing_mode: AddressingMode::Implied },
+    // 0x59
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x5A
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0x5B
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x5C
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x5D
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x5E
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x5F
+    Opcode { name: OpcodeName::SRE, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x60
+    Opcode { name: OpcodeName::RTS, addressing_mode: AddressingMode::Implied },
+    // 0x61
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x62
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x63
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x64
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPage },
+    // 0x65
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPage },
+    // 0x66
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPage },
+    // 0x67
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::ZeroPage },
+    // 0x68
+    Opcode { name: OpcodeName::PLA, addressing_mode: AddressingMode::Implied },
+    // 0x69
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Immediate },
+    // 0x6A
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Accumulator },
+    // 0x6B
+    Opcode { name: OpcodeName::ARR, addressing_mode: AddressingMode::Immediate },
+    // 0x6C
+    Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Indirect },
+    // 0x6D
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Absolute },
+    // 0x6E
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Absolute },
+    // 0x6F
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::Absolute },
+    // 0x70
+    Opcode { name: OpcodeName::BVS, addressing_mode: AddressingMode::Relative },
+    // 0x71
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x72
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x73
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x74
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x75
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x76
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x77
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x78
+    Opcode { name: OpcodeName::SEI, addressing_mode: AddressingMode::Implied },
+    // 0x79
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x7A
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0x7B
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x7C
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x7D
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x7E
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x7F
+    Opcode { name: OpcodeName::RRA, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x80
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
+    // 0x81
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x82
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
+    // 0x83
+    Opcode { name: OpcodeName::SAX, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0x84
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPage },
+    // 0x85
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPage },
+    // 0x86
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPage },
+    // 0x87
+    Opcode { name: OpcodeName::SAX, addressing_mode: AddressingMode::ZeroPage },
+    // 0x88
+    Opcode { name: OpcodeName::DEY, addressing_mode: AddressingMode::Implied },
+    // 0x89
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
+    // 0x8A
+    Opcode { name: OpcodeName::TXA, addressing_mode: AddressingMode::Implied },
+    // 0x8B
+    Opcode { name: OpcodeName::XAA, addressing_mode: AddressingMode::Immediate },
+    // 0x8C
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::Absolute },
+    // 0x8D
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::Absolute },
+    // 0x8E
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::Absolute },
+    // 0x8F
+    Opcode { name: OpcodeName::SAX, addressing_mode: AddressingMode::Absolute },
+    // 0x90
+    Opcode { name: OpcodeName::BCC, addressing_mode: AddressingMode::Relative },
+    // 0x91
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x92
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0x93
+    Opcode { name: OpcodeName::SHA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0x94
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x95
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPageX },
+    // 0x96
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPageY },
+    // 0x97
+    Opcode { name: OpcodeName::SAX, addressing_mode: AddressingMode::ZeroPageY },
+    // 0x98
+    Opcode { name: OpcodeName::TYA, addressing_mode: AddressingMode::Implied },
+    // 0x99
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x9A
+    Opcode { name: OpcodeName::TXS, addressing_mode: AddressingMode::Implied },
+    // 0x9B
+    Opcode { name: OpcodeName::TAS, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x9C
+    Opcode { name: OpcodeName::SHY, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x9D
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteX },
+    // 0x9E
+    Opcode { name: OpcodeName::SHX, addressing_mode: AddressingMode::AbsoluteY },
+    // 0x9F
+    Opcode { name: OpcodeName::SHA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xA0
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Immediate },
+    // 0xA1
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xA2
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Immediate },
+    // 0xA3
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xA4
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPage },
+    // 0xA5
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPage },
+    // 0xA6
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPage },
+    // 0xA7
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::ZeroPage },
+    // 0xA8
+    Opcode { name: OpcodeName::TAY, addressing_mode: AddressingMode::Implied },
+    // 0xA9
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Immediate },
+    // 0xAA
+    Opcode { name: OpcodeName::TAX, addressing_mode: AddressingMode::Implied },
+    // 0xAB
+    Opcode { name: OpcodeName::LXA, addressing_mode: AddressingMode::Immediate },
+    // 0xAC
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Absolute },
+    // 0xAD
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Absolute },
+    // 0xAE
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Absolute },
+    // 0xAF
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::Absolute },
+    // 0xB0
+    Opcode { name: OpcodeName::BCS, addressing_mode: AddressingMode::Relative },
+    // 0xB1
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xB2
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0xB3
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xB4
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xB5
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xB6
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPageY },
+    // 0xB7
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::ZeroPageY },
+    // 0xB8
+    Opcode { name: OpcodeName::CLV, addressing_mode: AddressingMode::Implied },
+    // 0xB9
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xBA
+    Opcode { name: OpcodeName::TSX, addressing_mode: AddressingMode::Implied },
+    // 0xBB
+    Opcode { name: OpcodeName::LAS, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xBC
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xBD
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xBE
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xBF
+    Opcode { name: OpcodeName::LAX, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xC0
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Immediate },
+    // 0xC1
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xC2
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
+    // 0xC3
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xC4
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::ZeroPage },
+    // 0xC5
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPage },
+    // 0xC6
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPage },
+    // 0xC7
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::ZeroPage },
+    // 0xC8
+    Opcode { name: OpcodeName::INY, addressing_mode: AddressingMode::Implied },
+    // 0xC9
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Immediate },
+    // 0xCA
+    Opcode { name: OpcodeName::DEX, addressing_mode: AddressingMode::Implied },
+    // 0xCB
+    Opcode { name: OpcodeName::SBX, addressing_mode: AddressingMode::Immediate },
+    // 0xCC
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Absolute },
+    // 0xCD
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Absolute },
+    // 0xCE
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::Absolute },
+    // 0xCF
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::Absolute },
+    // 0xD0
+    Opcode { name: OpcodeName::BNE, addressing_mode: AddressingMode::Relative },
+    // 0xD1
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xD2
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0xD3
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xD4
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xD5
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xD6
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xD7
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xD8
+    Opcode { name: OpcodeName::CLD, addressing_mode: AddressingMode::Implied },
+    // 0xD9
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xDA
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0xDB
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xDC
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xDD
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xDE
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xDF
+    Opcode { name: OpcodeName::DCP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xE0
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Immediate },
+    // 0xE1
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xE2
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
+    // 0xE3
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::IndexedIndirectX },
+    // 0xE4
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::ZeroPage },
+    // 0xE5
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPage },
+    // 0xE6
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPage },
+    // 0xE7
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::ZeroPage },
+    // 0xE8
+    Opcode { name: OpcodeName::INX, addressing_mode: AddressingMode::Implied },
+    // 0xE9
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Immediate },
+    // 0xEA
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0xEB
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Immediate },
+    // 0xEC
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Absolute },
+    // 0xED
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Absolute },
+    // 0xEE
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::Absolute },
+    // 0xEF
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::Absolute },
+    // 0xF0
+    Opcode { name: OpcodeName::BEQ, addressing_mode: AddressingMode::Relative },
+    // 0xF1
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xF2
+    Opcode { name: OpcodeName::JAM, addressing_mode: AddressingMode::Implied },
+    // 0xF3
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::IndirectIndexedY },
+    // 0xF4
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xF5
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xF6
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xF7
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::ZeroPageX },
+    // 0xF8
+    Opcode { name: OpcodeName::SED, addressing_mode: AddressingMode::Implied },
+    // 0xF9
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xFA
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
+    // 0xFB
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::AbsoluteY },
+    // 0xFC
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xFD
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xFE
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::AbsoluteX },
+    // 0xFF
+    Opcode { name: OpcodeName::ISC, addressing_mode: AddressingMode::AbsoluteX },
+];
+
+/// Lookup table for the WDC 65C02. The official NMOS opcodes are unchanged, the rest are new instructions or
+/// NOPs of various lengths.
+pub static OPCODES_65C02: [Opcode; 256] = [
     // 0x00
-    Some(Opcode { name: OpcodeName::BRK, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::BRK, addressing_mode: AddressingMode::Implied },
     // 0x01
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndexedIndirectX}),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0x02
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0x03
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x04
-    None,
+    Opcode { name: OpcodeName::TSB, addressing_mode: AddressingMode::ZeroPage },
     // 0x05
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPage },
     // 0x06
-    Some(Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPage },
     // 0x07
-    None,
+    Opcode { name: OpcodeName::RMB0, addressing_mode: AddressingMode::ZeroPage },
     // 0x08
-    Some(Opcode { name: OpcodeName::PHP, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::PHP, addressing_mode: AddressingMode::Implied },
     // 0x09
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Immediate },
     // 0x0A
-    Some(Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Accumulator }),
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Accumulator },
     // 0x0B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x0C
-    None,
+    Opcode { name: OpcodeName::TSB, addressing_mode: AddressingMode::Absolute },
     // 0x0D
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::Absolute },
     // 0x0E
-    Some(Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::Absolute },
     // 0x0F
-    None,
+    Opcode { name: OpcodeName::BBR0, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x10
-    Some(Opcode { name: OpcodeName::BPL, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BPL, addressing_mode: AddressingMode::Relative },
     // 0x11
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0x12
-    None,
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0x13
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x14
-    None,
+    Opcode { name: OpcodeName::TRB, addressing_mode: AddressingMode::ZeroPage },
     // 0x15
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::ZeroPageX },
     // 0x16
-    Some(Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::ZeroPageX },
     // 0x17
-    None,
+    Opcode { name: OpcodeName::RMB1, addressing_mode: AddressingMode::ZeroPage },
     // 0x18
-    Some(Opcode { name: OpcodeName::CLC, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::CLC, addressing_mode: AddressingMode::Implied },
     // 0x19
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteY },
     // 0x1A
-    None,
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::Accumulator },
     // 0x1B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x1C
-    None,
+    Opcode { name: OpcodeName::TRB, addressing_mode: AddressingMode::Absolute },
     // 0x1D
-    Some(Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::ORA, addressing_mode: AddressingMode::AbsoluteX },
     // 0x1E
-    Some(Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::ASL, addressing_mode: AddressingMode::AbsoluteX },
     // 0x1F
-    None,
+    Opcode { name: OpcodeName::BBR1, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x20
-    Some(Opcode { name: OpcodeName::JSR, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::JSR, addressing_mode: AddressingMode::Absolute },
     // 0x21
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0x22
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0x23
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x24
-    Some(Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::ZeroPage },
     // 0x25
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPage },
     // 0x26
-    Some(Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPage },
     // 0x27
-    None,
+    Opcode { name: OpcodeName::RMB2, addressing_mode: AddressingMode::ZeroPage },
     // 0x28
-    Some(Opcode { name: OpcodeName::PLP, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::PLP, addressing_mode: AddressingMode::Implied },
     // 0x29
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Immediate },
     // 0x2A
-    Some(Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Accumulator }),
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Accumulator },
     // 0x2B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x2C
-    Some(Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::Absolute },
     // 0x2D
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::Absolute },
     // 0x2E
-    Some(Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::Absolute },
     // 0x2F
-    None,
+    Opcode { name: OpcodeName::BBR2, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x30
-    Some(Opcode { name: OpcodeName::BMI, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BMI, addressing_mode: AddressingMode::Relative },
     // 0x31
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0x32
-    None,
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0x33
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x34
-    None,
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::ZeroPageX },
     // 0x35
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::ZeroPageX },
     // 0x36
-    Some(Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::ZeroPageX },
     // 0x37
-    None,
+    Opcode { name: OpcodeName::RMB3, addressing_mode: AddressingMode::ZeroPage },
     // 0x38
-    Some(Opcode { name: OpcodeName::SEC, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::SEC, addressing_mode: AddressingMode::Implied },
     // 0x39
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteY },
     // 0x3A
-    None,
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::Accumulator },
     // 0x3B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x3C
-    None,
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::AbsoluteX },
     // 0x3D
-    Some(Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::AND, addressing_mode: AddressingMode::AbsoluteX },
     // 0x3E
-    Some(Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::ROL, addressing_mode: AddressingMode::AbsoluteX },
     // 0x3F
-    None,
+    Opcode { name: OpcodeName::BBR3, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x40
-    Some(Opcode { name: OpcodeName::RTI, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::RTI, addressing_mode: AddressingMode::Implied },
     // 0x41
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0x42
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0x43
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x44
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPage },
     // 0x45
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPage },
     // 0x46
-    Some(Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPage },
     // 0x47
-    None,
+    Opcode { name: OpcodeName::RMB4, addressing_mode: AddressingMode::ZeroPage },
     // 0x48
-    Some(Opcode { name: OpcodeName::PHA, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::PHA, addressing_mode: AddressingMode::Implied },
     // 0x49
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Immediate },
     // 0x4A
-    Some(Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Accumulator }),
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Accumulator },
     // 0x4B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x4C
-    Some(Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Absolute },
     // 0x4D
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::Absolute },
     // 0x4E
-    Some(Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::Absolute },
     // 0x4F
-    None,
+    Opcode { name: OpcodeName::BBR4, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x50
-    Some(Opcode { name: OpcodeName::BVC, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BVC, addressing_mode: AddressingMode::Relative },
     // 0x51
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0x52
-    None,
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0x53
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x54
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
     // 0x55
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::ZeroPageX },
     // 0x56
-    Some(Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::ZeroPageX },
     // 0x57
-    None,
+    Opcode { name: OpcodeName::RMB5, addressing_mode: AddressingMode::ZeroPage },
     // 0x58
-    Some(Opcode { name: OpcodeName::CLI, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::CLI, addressing_mode: AddressingMode::Implied },
     // 0x59
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteY },
     // 0x5A
-    None,
+    Opcode { name: OpcodeName::PHY, addressing_mode: AddressingMode::Implied },
     // 0x5B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x5C
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Absolute },
     // 0x5D
-    Some(Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::EOR, addressing_mode: AddressingMode::AbsoluteX },
     // 0x5E
-    Some(Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::LSR, addressing_mode: AddressingMode::AbsoluteX },
     // 0x5F
-    None,
+    Opcode { name: OpcodeName::BBR5, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x60
-    Some(Opcode { name: OpcodeName::RTS, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::RTS, addressing_mode: AddressingMode::Implied },
     // 0x61
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0x62
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0x63
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x64
-    None,
+    Opcode { name: OpcodeName::STZ, addressing_mode: AddressingMode::ZeroPage },
     // 0x65
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPage },
     // 0x66
-    Some(Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPage },
     // 0x67
-    None,
+    Opcode { name: OpcodeName::RMB6, addressing_mode: AddressingMode::ZeroPage },
     // 0x68
-    Some(Opcode { name: OpcodeName::PLA, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::PLA, addressing_mode: AddressingMode::Implied },
     // 0x69
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Immediate },
     // 0x6A
-    Some(Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Accumulator }),
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Accumulator },
     // 0x6B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x6C
-    Some(Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Indirect }),
+    Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::Indirect },
     // 0x6D
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::Absolute },
     // 0x6E
-    Some(Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::Absolute },
     // 0x6F
-    None,
+    Opcode { name: OpcodeName::BBR6, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x70
-    Some(Opcode { name: OpcodeName::BVS, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BVS, addressing_mode: AddressingMode::Relative },
     // 0x71
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0x72
-    None,
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0x73
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x74
-    None,
+    Opcode { name: OpcodeName::STZ, addressing_mode: AddressingMode::ZeroPageX },
     // 0x75
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::ZeroPageX },
     // 0x76
-    Some(Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::ZeroPageX },
     // 0x77
-    None,
+    Opcode { name: OpcodeName::RMB7, addressing_mode: AddressingMode::ZeroPage },
     // 0x78
-    Some(Opcode { name: OpcodeName::SEI, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::SEI, addressing_mode: AddressingMode::Implied },
     // 0x79
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteY },
     // 0x7A
-    None,
+    Opcode { name: OpcodeName::PLY, addressing_mode: AddressingMode::Implied },
     // 0x7B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x7C
-    None,
+    Opcode { name: OpcodeName::JMP, addressing_mode: AddressingMode::AbsoluteIndexedIndirect },
     // 0x7D
-    Some(Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::ADC, addressing_mode: AddressingMode::AbsoluteX },
     // 0x7E
-    Some(Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::ROR, addressing_mode: AddressingMode::AbsoluteX },
     // 0x7F
-    None,
+    Opcode { name: OpcodeName::BBR7, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x80
-    None,
+    Opcode { name: OpcodeName::BRA, addressing_mode: AddressingMode::Relative },
     // 0x81
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0x82
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0x83
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x84
-    Some(Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPage },
     // 0x85
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPage },
     // 0x86
-    Some(Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPage },
     // 0x87
-    None,
+    Opcode { name: OpcodeName::SMB0, addressing_mode: AddressingMode::ZeroPage },
     // 0x88
-    Some(Opcode { name: OpcodeName::DEY, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::DEY, addressing_mode: AddressingMode::Implied },
     // 0x89
-    None,
+    Opcode { name: OpcodeName::BIT, addressing_mode: AddressingMode::Immediate },
     // 0x8A
-    Some(Opcode { name: OpcodeName::TXA, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TXA, addressing_mode: AddressingMode::Implied },
     // 0x8B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x8C
-    Some(Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::Absolute },
     // 0x8D
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::Absolute },
     // 0x8E
-    Some(Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::Absolute },
     // 0x8F
-    None,
+    Opcode { name: OpcodeName::BBS0, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0x90
-    Some(Opcode { name: OpcodeName::BCC, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BCC, addressing_mode: AddressingMode::Relative },
     // 0x91
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0x92
-    None,
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0x93
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x94
-    Some(Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::STY, addressing_mode: AddressingMode::ZeroPageX },
     // 0x95
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::ZeroPageX },
     // 0x96
-    Some(Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPageY }),
+    Opcode { name: OpcodeName::STX, addressing_mode: AddressingMode::ZeroPageY },
     // 0x97
-    None,
+    Opcode { name: OpcodeName::SMB1, addressing_mode: AddressingMode::ZeroPage },
     // 0x98
-    Some(Opcode { name: OpcodeName::TYA, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TYA, addressing_mode: AddressingMode::Implied },
     // 0x99
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteY },
     // 0x9A
-    Some(Opcode { name: OpcodeName::TXS, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TXS, addressing_mode: AddressingMode::Implied },
     // 0x9B
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0x9C
-    None,
+    Opcode { name: OpcodeName::STZ, addressing_mode: AddressingMode::Absolute },
     // 0x9D
-    Some(Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::STA, addressing_mode: AddressingMode::AbsoluteX },
     // 0x9E
-    None,
+    Opcode { name: OpcodeName::STZ, addressing_mode: AddressingMode::AbsoluteX },
     // 0x9F
-    None,
+    Opcode { name: OpcodeName::BBS1, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xA0
-    Some(Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Immediate },
     // 0xA1
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0xA2
-    Some(Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Immediate },
     // 0xA3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xA4
-    Some(Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPage },
     // 0xA5
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPage },
     // 0xA6
-    Some(Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPage },
     // 0xA7
-    None,
+    Opcode { name: OpcodeName::SMB2, addressing_mode: AddressingMode::ZeroPage },
     // 0xA8
-    Some(Opcode { name: OpcodeName::TAY, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TAY, addressing_mode: AddressingMode::Implied },
     // 0xA9
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Immediate },
     // 0xAA
-    Some(Opcode { name: OpcodeName::TAX, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TAX, addressing_mode: AddressingMode::Implied },
     // 0xAB
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xAC
-    Some(Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::Absolute },
     // 0xAD
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::Absolute },
     // 0xAE
-    Some(Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::Absolute },
     // 0xAF
-    None,
+    Opcode { name: OpcodeName::BBS2, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xB0
-    Some(Opcode { name: OpcodeName::BCS, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BCS, addressing_mode: AddressingMode::Relative },
     // 0xB1
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0xB2
-    None,
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0xB3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xB4
-    Some(Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::ZeroPageX },
     // 0xB5
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::ZeroPageX },
     // 0xB6
-    Some(Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPageY }),
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::ZeroPageY },
     // 0xB7
-    None,
+    Opcode { name: OpcodeName::SMB3, addressing_mode: AddressingMode::ZeroPage },
     // 0xB8
-    Some(Opcode { name: OpcodeName::CLV, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::CLV, addressing_mode: AddressingMode::Implied },
     // 0xB9
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteY },
     // 0xBA
-    Some(Opcode { name: OpcodeName::TSX, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::TSX, addressing_mode: AddressingMode::Implied },
     // 0xBB
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xBC
-    Some(Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::LDY, addressing_mode: AddressingMode::AbsoluteX },
     // 0xBD
-    Some(Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::LDA, addressing_mode: AddressingMode::AbsoluteX },
     // 0xBE
-    Some(Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::LDX, addressing_mode: AddressingMode::AbsoluteY },
     // 0xBF
-    None,
+    Opcode { name: OpcodeName::BBS3, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xC0
-    Some(Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Immediate },
     // 0xC1
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0xC2
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0xC3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xC4
-    Some(Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::ZeroPage },
     // 0xC5
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPage },
     // 0xC6
-    Some(Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPage },
     // 0xC7
-    None,
+    Opcode { name: OpcodeName::SMB4, addressing_mode: AddressingMode::ZeroPage },
     // 0xC8
-    Some(Opcode { name: OpcodeName::INY, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::INY, addressing_mode: AddressingMode::Implied },
     // 0xC9
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Immediate },
     // 0xCA
-    Some(Opcode { name: OpcodeName::DEX, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::DEX, addressing_mode: AddressingMode::Implied },
     // 0xCB
-    None,
+    Opcode { name: OpcodeName::WAI, addressing_mode: AddressingMode::Implied },
     // 0xCC
-    Some(Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::CPY, addressing_mode: AddressingMode::Absolute },
     // 0xCD
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::Absolute },
     // 0xCE
-    Some(Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::Absolute },
     // 0xCF
-    None,
+    Opcode { name: OpcodeName::BBS4, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xD0
-    Some(Opcode { name: OpcodeName::BNE, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BNE, addressing_mode: AddressingMode::Relative },
     // 0xD1
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0xD2
-    None,
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0xD3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xD4
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
     // 0xD5
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::ZeroPageX },
     // 0xD6
-    Some(Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::ZeroPageX },
     // 0xD7
-    None,
+    Opcode { name: OpcodeName::SMB5, addressing_mode: AddressingMode::ZeroPage },
     // 0xD8
-    Some(Opcode { name: OpcodeName::CLD, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::CLD, addressing_mode: AddressingMode::Implied },
     // 0xD9
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteY },
     // 0xDA
-    None,
+    Opcode { name: OpcodeName::PHX, addressing_mode: AddressingMode::Implied },
     // 0xDB
-    None,
+    Opcode { name: OpcodeName::STP, addressing_mode: AddressingMode::Implied },
     // 0xDC
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Absolute },
     // 0xDD
-    Some(Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::CMP, addressing_mode: AddressingMode::AbsoluteX },
     // 0xDE
-    Some(Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::DEC, addressing_mode: AddressingMode::AbsoluteX },
     // 0xDF
-    None,
+    Opcode { name: OpcodeName::BBS5, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xE0
-    Some(Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Immediate },
     // 0xE1
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndexedIndirectX }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndexedIndirectX },
     // 0xE2
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Immediate },
     // 0xE3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xE4
-    Some(Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::ZeroPage },
     // 0xE5
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPage },
     // 0xE6
-    Some(Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPage }),
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPage },
     // 0xE7
-    None,
+    Opcode { name: OpcodeName::SMB6, addressing_mode: AddressingMode::ZeroPage },
     // 0xE8
-    Some(Opcode { name: OpcodeName::INX, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::INX, addressing_mode: AddressingMode::Implied },
     // 0xE9
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Immediate }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Immediate },
     // 0xEA
-    Some(Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xEB
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xEC
-    Some(Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::CPX, addressing_mode: AddressingMode::Absolute },
     // 0xED
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::Absolute },
     // 0xEE
-    Some(Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::Absolute }),
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::Absolute },
     // 0xEF
-    None,
+    Opcode { name: OpcodeName::BBS6, addressing_mode: AddressingMode::ZeroPageRelative },
     // 0xF0
-    Some(Opcode { name: OpcodeName::BEQ, addressing_mode: AddressingMode::Relative }),
+    Opcode { name: OpcodeName::BEQ, addressing_mode: AddressingMode::Relative },
     // 0xF1
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndirectIndexedY }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::IndirectIndexedY },
     // 0xF2
-    None,
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPageIndirect },
     // 0xF3
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xF4
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::ZeroPageX },
     // 0xF5
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::ZeroPageX },
     // 0xF6
-    Some(Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPageX }),
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::ZeroPageX },
     // 0xF7
-    None,
+    Opcode { name: OpcodeName::SMB7, addressing_mode: AddressingMode::ZeroPage },
     // 0xF8
-    Some(Opcode { name: OpcodeName::SED, addressing_mode: AddressingMode::Implied }),
+    Opcode { name: OpcodeName::SED, addressing_mode: AddressingMode::Implied },
     // 0xF9
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteY }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteY },
     // 0xFA
-    None,
+    Opcode { name: OpcodeName::PLX, addressing_mode: AddressingMode::Implied },
     // 0xFB
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Implied },
     // 0xFC
-    None,
+    Opcode { name: OpcodeName::NOP, addressing_mode: AddressingMode::Absolute },
     // 0xFD
-    Some(Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::SBC, addressing_mode: AddressingMode::AbsoluteX },
     // 0xFE
-    Some(Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::AbsoluteX }),
+    Opcode { name: OpcodeName::INC, addressing_mode: AddressingMode::AbsoluteX },
     // 0xFF
-    None,
+    Opcode { name: OpcodeName::BBS7, addressing_mode: AddressingMode::ZeroPageRelative },
 ];
@@ -0,0 +1,809 @@
+pub mod palette;
+
+use self::palette::SYSTEM_PALETTE;
+use crate::watch::{Access, Watch};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirroring {
+    Vertical,
+    Horizontal,
+    FourScreen,
+}
+
+/// PPUCTRL ($2000)
+#[derive(Debug, Default)]
+pub struct Control {
+    /// NN, which of the four nametables is at the top left of the screen
+    pub base_nametable: u8,
+    /// I, add 32 to the VRAM address after a $2007 access instead of 1
+    pub vram_increment_32: bool,
+    /// S, 8x8 sprites come from $1000 instead of $0000
+    pub sprite_pattern_high: bool,
+    /// B, the background comes from $1000 instead of $0000
+    pub background_pattern_high: bool,
+    /// H, 8x16 sprites
+    pub tall_sprites: bool,
+    /// P
+    pub master_slave: bool,
+    /// V, pull /NMI low while the vblank flag is set
+    pub nmi_enable: bool,
+}
+
+impl Control {
+    pub fn update(&mut self, bits: u8) {
+        self.base_nametable = bits & 0b0000_0011;
+        self.vram_increment_32 = bits & 0b0000_0100 != 0;
+        self.sprite_pattern_high = bits & 0b0000_1000 != 0;
+        self.background_pattern_high = bits & 0b0001_0000 != 0;
+        self.tall_sprites = bits & 0b0010_0000 != 0;
+        self.master_slave = bits & 0b0100_0000 != 0;
+        self.nmi_enable = bits & 0b1000_0000 != 0;
+    }
+}
+
+/// PPUMASK ($2001)
+#[derive(Debug, Default)]
+pub struct Mask {
+    /// Gr
+    pub greyscale: bool,
+    /// m
+    pub show_background_left: bool,
+    /// M
+    pub show_sprites_left: bool,
+    /// b
+    pub show_background: bool,
+    /// s
+    pub show_sprites: bool,
+    /// R
+    pub emphasize_red: bool,
+    /// G
+    pub emphasize_green: bool,
+    /// B
+    pub emphasize_blue: bool,
+}
+
+impl Mask {
+    pub fn update(&mut self, bits: u8) {
+        self.greyscale = bits & 0b0000_0001 != 0;
+        self.show_background_left = bits & 0b0000_0010 != 0;
+        self.show_sprites_left = bits & 0b0000_0100 != 0;
+        self.show_background = bits & 0b0000_1000 != 0;
+        self.show_sprites = bits & 0b0001_0000 != 0;
+        self.emphasize_red = bits & 0b0010_0000 != 0;
+        self.emphasize_green = bits & 0b0100_0000 != 0;
+        self.emphasize_blue = bits & 0b1000_0000 != 0;
+    }
+    pub fn rendering_enabled(&self) -> bool {
+        self.show_background || self.show_sprites
+    }
+}
+
+/// PPUSTATUS ($2002), only the top three bits exist, the rest is open bus
+#[derive(Debug, Default)]
+pub struct PpuStatus {
+    /// O
+    pub sprite_overflow: bool,
+    /// S
+    pub sprite_zero_hit: bool,
+    /// V
+    pub vblank: bool,
+}
+
+impl PpuStatus {
+    pub fn bits(&self) -> u8 {
+        (self.vblank as u8) << 7 | (self.sprite_zero_hit as u8) << 6 | (self.sprite_overflow as u8) << 5
+    }
+}
+
+/// Scanline the vblank flag is raised on, dot 1
+pub const VBLANK_SCANLINE: u16 = 241;
+/// Last scanline of a frame, the vblank flag is cleared on its dot 1
+pub const PRE_RENDER_SCANLINE: u16 = 261;
+pub const DOTS_PER_SCANLINE: u16 = 341;
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+/// Scanlines the PPU runs through per frame, counting vblank and the pre-render line
+pub const SCANLINES_PER_FRAME: u16 = PRE_RENDER_SCANLINE + 1;
+/// Bits of the I/O latch are held by capacitance and fade to 0 around 600ms after they were last driven high
+const IO_LATCH_DECAY_FRAMES: u64 = 36;
+
+/// The 2C02 picture processing unit
+pub struct Ppu {
+    chr: Vec<u8>,
+    chr_is_ram: bool,
+    mirroring: Mirroring,
+    vram: [u8; 0x1000],
+    palette_table: [u8; 32],
+    pub oam_data: [u8; 256],
+    oam_address: u8,
+
+    pub control: Control,
+    pub mask: Mask,
+    pub status: PpuStatus,
+
+    /// Current VRAM address (loopy v)
+    v: u16,
+    /// Temporary VRAM address (loopy t)
+    t: u16,
+    /// Fine X scroll
+    fine_x: u8,
+    /// Shared first/second write toggle of $2005 and $2006
+    write_latch: bool,
+    /// $2007 reads lag one access behind, except for palette reads
+    read_buffer: u8,
+    /// Last value written to or read from any PPU register, what undriven bits of a read return
+    io_latch: u8,
+    /// Frame each bit of `io_latch` was last driven on
+    io_latch_refreshed: [u64; 8],
+
+    /// The dot that will be processed by the next call to `tick`
+    pub scanline: u16,
+    pub dot: u16,
+    pub frame: u64,
+    odd_frame: bool,
+    /// Set by a $2002 read on the dot just before vblank starts, which stops the flag from being raised at all
+    suppress_vblank: bool,
+
+    /// The background tile being fetched, loaded into the shift registers every 8 dots
+    next_tile_id: u8,
+    next_tile_attribute: u8,
+    next_tile_low: u8,
+    next_tile_high: u8,
+    pattern_shift_low: u16,
+    pattern_shift_high: u16,
+    attribute_shift_low: u16,
+    attribute_shift_high: u16,
+
+    /// Sprites found on the current scanline, up to 8, with their pattern rows already fetched and flipped
+    sprite_count: usize,
+    sprite_patterns: [(u8, u8); 8],
+    sprite_attributes: [u8; 8],
+    sprite_x: [u8; 8],
+    sprite_zero_on_line: bool,
+
+    /// Palette index (0-63) of every pixel, row by row
+    frame_buffer: Vec<u8>,
+
+    /// PPU memory accesses to record for a debugger. Only those made through PPUDATA are seen, not the fetches
+    /// of rendering.
+    pub watch: Watch,
+}
+
+impl Ppu {
+    pub fn new(chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        // Boards without CHR-ROM have 8KiB of CHR-RAM in its place
+        let chr_is_ram = chr_rom.is_empty();
+        let chr = if chr_is_ram { vec![0; 0x2000] } else { chr_rom };
+
+        Self {
+            chr,
+            chr_is_ram,
+            mirroring,
+            vram: [0; 0x1000],
+            palette_table: [0; 32],
+            oam_data: [0; 256],
+            oam_address: 0,
+            control: Control::default(),
+            mask: Mask::default(),
+            status: PpuStatus::default(),
+            v: 0,
+            t: 0,
+            fine_x: 0,
+            write_latch: false,
+            read_buffer: 0,
+            io_latch: 0,
+            io_latch_refreshed: [0; 8],
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            odd_frame: false,
+            suppress_vblank: false,
+            next_tile_id: 0,
+            next_tile_attribute: 0,
+            next_tile_low: 0,
+            next_tile_high: 0,
+            pattern_shift_low: 0,
+            pattern_shift_high: 0,
+            attribute_shift_low: 0,
+            attribute_shift_high: 0,
+            sprite_count: 0,
+            sprite_patterns: [(0, 0); 8],
+            sprite_attributes: [0; 8],
+            sprite_x: [0; 8],
+            sprite_zero_on_line: false,
+            frame_buffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            watch: Watch::default(),
+        }
+    }
+
+    /// What the reset button does: PPUCTRL and PPUMASK are cleared along with the write toggle and read buffer,
+    /// memory and the beam position are left alone
+    pub fn reset(&mut self) {
+        self.control = Control::default();
+        self.mask = Mask::default();
+        self.write_latch = false;
+        self.read_buffer = 0;
+        self.odd_frame = false;
+    }
+
+    /// Palette indices of the picture, `SCREEN_WIDTH` by `SCREEN_HEIGHT`. Rows below the current scanline still
+    /// hold the previous frame.
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame_buffer
+    }
+
+    pub fn pixel_rgb(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        SYSTEM_PALETTE[self.frame_buffer[y * SCREEN_WIDTH + x] as usize]
+    }
+
+    /// Level of the PPU's /NMI output
+    pub fn nmi_line(&self) -> bool {
+        self.status.vblank && self.control.nmi_enable
+    }
+
+    /// Runs the PPU for a single dot
+    pub fn tick(&mut self) {
+        if self.scanline < SCREEN_HEIGHT as u16 || self.scanline == PRE_RENDER_SCANLINE {
+            self.render_dot();
+        }
+
+        if self.dot == 1 {
+            if self.scanline == VBLANK_SCANLINE {
+                if !self.suppress_vblank {
+                    self.status.vblank = true;
+                }
+                self.suppress_vblank = false;
+            } else if self.scanline == PRE_RENDER_SCANLINE {
+                self.status.vblank = false;
+                self.status.sprite_zero_hit = false;
+                self.status.sprite_overflow = false;
+            }
+        }
+
+        self.dot += 1;
+        // With rendering on, odd frames skip the last dot of the pre-render line
+        if self.scanline == PRE_RENDER_SCANLINE
+            && self.dot == DOTS_PER_SCANLINE - 1
+            && self.odd_frame
+            && self.mask.rendering_enabled()
+        {
+            self.dot += 1;
+        }
+        if self.dot == DOTS_PER_SCANLINE {
+            self.dot = 0;
+            self.scanline += 1;
+            if self.scanline > PRE_RENDER_SCANLINE {
+                self.scanline = 0;
+                self.frame += 1;
+                self.odd_frame = !self.odd_frame;
+            }
+        }
+    }
+
+    /// The fetches, scrolling and output of one dot on a visible or the pre-render scanline
+    fn render_dot(&mut self) {
+        let visible = self.scanline < SCREEN_HEIGHT as u16;
+        if self.mask.rendering_enabled() {
+            if (2..=257).contains(&self.dot) || (322..=337).contains(&self.dot) {
+                self.shift_background();
+            }
+            if (1..=256).contains(&self.dot) || (321..=336).contains(&self.dot) {
+                match (self.dot - 1) % 8 {
+                    0 => {
+                        self.load_background_shifters();
+                        self.next_tile_id = self.vram_read(0x2000 | (self.v & 0x0FFF));
+                    }
+                    2 => self.fetch_attribute(),
+                    4 => self.next_tile_low = self.vram_read(self.background_pattern_address()),
+                    6 => self.next_tile_high = self.vram_read(self.background_pattern_address() + 8),
+                    7 => self.increment_coarse_x(),
+                    _ => {}
+                }
+            }
+            match self.dot {
+                256 => self.increment_y(),
+                257 => {
+                    self.load_background_shifters();
+                    // Copy the horizontal position from t
+                    self.v = (self.v & !0x041F) | (self.t & 0x041F);
+                    if visible {
+                        self.evaluate_sprites();
+                    } else {
+                        // Nothing is evaluated on the pre-render line, so line 0 never shows sprites
+                        self.sprite_count = 0;
+                    }
+                }
+                // Copy the vertical position from t, ready for the next frame
+                280..=304 if !visible => self.v = (self.v & !0x7BE0) | (self.t & 0x7BE0),
+                _ => {}
+            }
+        }
+
+        if visible && (1..=256).contains(&self.dot) {
+            self.output_pixel();
+        }
+    }
+
+    fn background_pattern_address(&self) -> u16 {
+        let table = if self.control.background_pattern_high { 0x1000 } else { 0 };
+        let fine_y = (self.v >> 12) & 0b111;
+        table + self.next_tile_id as u16 * 16 + fine_y
+    }
+
+    fn fetch_attribute(&mut self) {
+        let address = 0x23C0 | (self.v & 0x0C00) | ((self.v >> 4) & 0x38) | ((self.v >> 2) & 0x07);
+        let mut attribute = self.vram_read(address);
+        // Each byte covers 4x4 tiles, two bits for each 2x2 quadrant
+        if self.v & 0b100_0000 != 0 {
+            attribute >>= 4;
+        }
+        if self.v & 0b10 != 0 {
+            attribute >>= 2;
+        }
+        self.next_tile_attribute = attribute & 0b11;
+    }
+
+    fn load_background_shifters(&mut self) {
+        self.pattern_shift_low = (self.pattern_shift_low & 0xFF00) | self.next_tile_low as u16;
+        self.pattern_shift_high = (self.pattern_shift_high & 0xFF00) | self.next_tile_high as u16;
+        let fill = |bit: u8| if self.next_tile_attribute & bit != 0 { 0xFF } else { 0x00 };
+        self.attribute_shift_low = (self.attribute_shift_low & 0xFF00) | fill(0b01);
+        self.attribute_shift_high = (self.attribute_shift_high & 0xFF00) | fill(0b10);
+    }
+
+    fn shift_background(&mut self) {
+        self.pattern_shift_low <<= 1;
+        self.pattern_shift_high <<= 1;
+        self.attribute_shift_low <<= 1;
+        self.attribute_shift_high <<= 1;
+    }
+
+    fn increment_coarse_x(&mut self) {
+        if self.v & 0x001F == 31 {
+            self.v &= !0x001F;
+            // Into the horizontally adjacent nametable
+            self.v ^= 0x0400;
+        } else {
+            self.v += 1;
+        }
+    }
+
+    fn increment_y(&mut self) {
+        if self.v & 0x7000 != 0x7000 {
+            self.v += 0x1000;
+            return;
+        }
+        self.v &= !0x7000;
+        let mut coarse_y = (self.v & 0x03E0) >> 5;
+        if coarse_y == 29 {
+            coarse_y = 0;
+            // Into the vertically adjacent nametable
+            self.v ^= 0x0800;
+        } else if coarse_y == 31 {
+            // Rows 30 and 31 are the attribute table, scrolling into them wraps without switching nametables
+            coarse_y = 0;
+        } else {
+            coarse_y += 1;
+        }
+        self.v = (self.v & !0x03E0) | (coarse_y << 5);
+    }
+
+    /// Finds the sprites on the next scanline and fetches their patterns
+    fn evaluate_sprites(&mut self) {
+        let height = if self.control.tall_sprites { 16 } else { 8 };
+        self.sprite_count = 0;
+        self.sprite_zero_on_line = false;
+
+        for index in 0..64 {
+            let sprite = &self.oam_data[index * 4..index * 4 + 4];
+            // OAM holds the Y coordinate minus one, so a sprite found now is drawn from the next scanline on
+            let row = self.scanline.wrapping_sub(sprite[0] as u16);
+            if row >= height {
+                continue;
+            }
+            if self.sprite_count == 8 {
+                self.status.sprite_overflow = true;
+                break;
+            }
+
+            let (tile, attributes, x) = (sprite[1], sprite[2], sprite[3]);
+            let row = if attributes & 0b1000_0000 != 0 { height - 1 - row } else { row };
+            let address = if height == 16 {
+                // Bit 0 picks the pattern table, the top and bottom halves are consecutive tiles
+                let table = (tile as u16 & 1) * 0x1000;
+                table + ((tile & 0xFE) as u16 + row / 8) * 16 + row % 8
+            } else {
+                let table = if self.control.sprite_pattern_high { 0x1000 } else { 0 };
+                table + tile as u16 * 16 + row
+            };
+            let mut low = self.vram_read(address);
+            let mut high = self.vram_read(address + 8);
+            if attributes & 0b0100_0000 != 0 {
+                low = low.reverse_bits();
+                high = high.reverse_bits();
+            }
+
+            let slot = self.sprite_count;
+            self.sprite_patterns[slot] = (low, high);
+            self.sprite_attributes[slot] = attributes;
+            self.sprite_x[slot] = x;
+            self.sprite_zero_on_line |= index == 0;
+            self.sprite_count += 1;
+        }
+    }
+
+    fn output_pixel(&mut self) {
+        let x = self.dot - 1;
+
+        let (mut background_pixel, mut background_palette) = (0, 0);
+        if self.mask.show_background && (x >= 8 || self.mask.show_background_left) {
+            let bit = 0x8000 >> self.fine_x;
+            background_pixel = (self.pattern_shift_high & bit != 0) as u8 * 2 + (self.pattern_shift_low & bit != 0) as u8;
+            background_palette =
+                (self.attribute_shift_high & bit != 0) as u8 * 2 + (self.attribute_shift_low & bit != 0) as u8;
+        }
+
+        let mut sprite = None;
+        if self.mask.show_sprites && (x >= 8 || self.mask.show_sprites_left) {
+            for slot in 0..self.sprite_count {
+                let offset = x.wrapping_sub(self.sprite_x[slot] as u16);
+                if offset >= 8 {
+                    continue;
+                }
+                let (low, high) = self.sprite_patterns[slot];
+                let shift = 7 - offset;
+                let pixel = ((high >> shift) & 1) * 2 + ((low >> shift) & 1);
+                if pixel != 0 {
+                    sprite = Some((slot, pixel, self.sprite_attributes[slot]));
+                    break;
+                }
+            }
+        }
+
+        let address = match sprite {
+            Some((slot, pixel, attributes)) => {
+                if slot == 0 && self.sprite_zero_on_line && background_pixel != 0 && x != 255 {
+                    self.status.sprite_zero_hit = true;
+                }
+                let behind_background = attributes & 0b0010_0000 != 0;
+                if behind_background && background_pixel != 0 {
+                    0x3F00 + background_palette as u16 * 4 + background_pixel as u16
+                } else {
+                    0x3F10 + (attributes & 0b11) as u16 * 4 + pixel as u16
+                }
+            }
+            None if background_pixel != 0 => 0x3F00 + background_palette as u16 * 4 + background_pixel as u16,
+            // With rendering off and v pointing into the palette, the PPU shows that color instead of the backdrop
+            None if !self.mask.rendering_enabled() && self.v & 0x3F00 == 0x3F00 => self.v,
+            None => 0x3F00,
+        };
+
+        let mut color = self.palette_table[palette_index(address)];
+        if self.mask.greyscale {
+            color &= 0x30;
+        }
+        self.frame_buffer[self.scanline as usize * SCREEN_WIDTH + x as usize] = color;
+    }
+
+    pub fn read_register(&mut self, address: u16) -> u8 {
+        // The value and which of its bits the register actually drives
+        let (data, driven) = match address & 0x0007 {
+            // PPUSTATUS
+            2 => {
+                let data = self.status.bits() | (self.decayed_io_latch() & 0b0001_1111);
+                if self.scanline == VBLANK_SCANLINE && self.dot == 1 {
+                    // Reading one dot before the flag goes up reads it as clear and loses this frame's vblank
+                    self.suppress_vblank = true;
+                }
+                self.status.vblank = false;
+                self.write_latch = false;
+                (data, 0b1110_0000)
+            }
+            // OAMDATA
+            4 => (self.oam_data[self.oam_address as usize], 0xFF),
+            // PPUDATA
+            7 => {
+                let address = self.v & 0x3FFF;
+                let read = if address >= 0x3F00 {
+                    // Palette reads bypass the buffer, which is filled with the nametable "underneath" instead
+                    self.read_buffer = self.vram_read(address - 0x1000);
+                    let data = self.palette_table[palette_index(address)] | (self.decayed_io_latch() & 0b1100_0000);
+                    self.watch.access(Access::Read, address, self.palette_table[palette_index(address)]);
+                    (data, 0b0011_1111)
+                } else {
+                    let data = self.read_buffer;
+                    self.read_buffer = self.vram_read(address);
+                    self.watch.access(Access::Read, address, self.read_buffer);
+                    (data, 0xFF)
+                };
+                self.increment_v();
+                read
+            }
+            // Write only registers
+            _ => (self.decayed_io_latch(), 0),
+        };
+        self.drive_io_latch(data, driven);
+        data
+    }
+
+    /// The I/O latch with any bits that have faded since they were last driven cleared
+    fn decayed_io_latch(&mut self) -> u8 {
+        for bit in 0..8 {
+            if self.frame > self.io_latch_refreshed[bit] + IO_LATCH_DECAY_FRAMES {
+                self.io_latch &= !(1 << bit);
+            }
+        }
+        self.io_latch
+    }
+
+    /// Puts the `driven` bits of `data` into the I/O latch, leaving the others to keep fading
+    fn drive_io_latch(&mut self, data: u8, driven: u8) {
+        self.io_latch = (self.io_latch & !driven) | (data & driven);
+        for bit in 0..8 {
+            if driven & 1 << bit != 0 {
+                self.io_latch_refreshed[bit] = self.frame;
+            }
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, data: u8) {
+        self.drive_io_latch(data, 0xFF);
+        match address & 0x0007 {
+            // PPUCTRL
+            0 => {
+                self.control.update(data);
+                self.t = (self.t & !0x0C00) | ((data as u16 & 0b11) << 10);
+            }
+            // PPUMASK
+            1 => self.mask.update(data),
+            // OAMADDR
+            3 => self.oam_address = data,
+            // OAMDATA
+            4 => {
+                self.oam_data[self.oam_address as usize] = data;
+                self.oam_address = self.oam_address.wrapping_add(1);
+            }
+            // PPUSCROLL
+            5 => {
+                if !self.write_latch {
+                    self.t = (self.t & !0x001F) | (data as u16 >> 3);
+                    self.fine_x = data & 0b111;
+                } else {
+                    self.t = (self.t & !0x73E0) | ((data as u16 & 0b111) << 12) | ((data as u16 & 0b1111_1000) << 2);
+                }
+                self.write_latch = !self.write_latch;
+            }
+            // PPUADDR
+            6 => {
+                if !self.write_latch {
+                    self.t = (self.t & 0x00FF) | ((data as u16 & 0b0011_1111) << 8);
+                } else {
+                    self.t = (self.t & 0xFF00) | data as u16;
+                    self.v = self.t;
+                }
+                self.write_latch = !self.write_latch;
+            }
+            // PPUDATA
+            7 => {
+                self.vram_write(self.v & 0x3FFF, data);
+                self.watch.access(Access::Write, self.v & 0x3FFF, data);
+                self.increment_v();
+            }
+            // PPUSTATUS is read only
+            _ => {}
+        }
+    }
+
+    fn increment_v(&mut self) {
+        let step = if self.control.vram_increment_32 { 32 } else { 1 };
+        self.v = self.v.wrapping_add(step) & 0x7FFF;
+    }
+
+    fn vram_read(&self, address: u16) -> u8 {
+        match address {
+            0x0000..=0x1FFF => self.chr[address as usize % self.chr.len()],
+            0x2000..=0x3EFF => self.vram[self.nametable_index(address)],
+            _ => self.palette_table[palette_index(address)],
+        }
+    }
+
+    fn vram_write(&mut self, address: u16, data: u8) {
+        match address {
+            0x0000..=0x1FFF => {
+                if self.chr_is_ram {
+                    let len = self.chr.len();
+                    self.chr[address as usize % len] = data;
+                }
+            }
+            0x2000..=0x3EFF => {
+                let index = self.nametable_index(address);
+                self.vram[index] = data;
+            }
+            _ => self.palette_table[palette_index(address)] = data & 0b0011_1111,
+        }
+    }
+
+    /// Maps $2000-$3EFF onto the console's 2KiB of nametable RAM (or the cartridge's extra 2KiB for four screen)
+    fn nametable_index(&self, address: u16) -> usize {
+        let address = (address - 0x2000) & 0x0FFF;
+        let table = address / 0x400;
+        let offset = address % 0x400;
+        let table = match self.mirroring {
+            Mirroring::Vertical => table % 2,
+            Mirroring::Horizontal => table / 2,
+            Mirroring::FourScreen => table,
+        };
+        (table * 0x400 + offset) as usize
+    }
+}
+
+/// $3F10, $3F14, $3F18 and $3F1C are mirrors of the backdrop entries
+fn palette_index(address: u16) -> usize {
+    let index = (address & 0x1F) as usize;
+    match index {
+        0x10 | 0x14 | 0x18 | 0x1C => index - 0x10,
+        _ => index,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ppu_at(scanline: u16, dot: u16) -> Ppu {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        while ppu.scanline != scanline || ppu.dot != dot {
+            ppu.tick();
+        }
+        ppu
+    }
+
+    #[test]
+    fn test_vblank_sets_and_clears() {
+        let mut ppu = ppu_at(VBLANK_SCANLINE, 1);
+        assert!(!ppu.status.vblank);
+        ppu.tick();
+        assert!(ppu.status.vblank);
+
+        let mut ppu = ppu_at(PRE_RENDER_SCANLINE, 1);
+        assert!(ppu.status.vblank);
+        ppu.tick();
+        assert!(!ppu.status.vblank);
+    }
+
+    #[test]
+    fn test_io_latch_open_bus_and_decay() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        // PPUSTATUS can't be written, but the write still charges the latch
+        ppu.write_register(0x2002, 0xFF);
+        assert_eq!(ppu.read_register(0x2000), 0xFF);
+        // Only the flags are driven, the low bits come from the latch
+        assert_eq!(ppu.read_register(0x2002), 0x1F);
+        assert_eq!(ppu.read_register(0x2005), 0x1F);
+
+        ppu.frame += IO_LATCH_DECAY_FRAMES / 2;
+        ppu.write_register(0x2002, 0x01);
+        ppu.frame += IO_LATCH_DECAY_FRAMES / 2 + 1;
+        // Bit 0 was refreshed more recently than the rest
+        assert_eq!(ppu.read_register(0x2005), 0x01);
+    }
+
+    #[test]
+    fn test_status_read_just_before_vblank_suppresses_it() {
+        let mut ppu = ppu_at(VBLANK_SCANLINE, 1);
+        ppu.control.nmi_enable = true;
+
+        assert_eq!(ppu.read_register(0x2002) & 0x80, 0);
+        for _ in 0..10 {
+            ppu.tick();
+            assert!(!ppu.status.vblank);
+            assert!(!ppu.nmi_line());
+        }
+    }
+
+    #[test]
+    fn test_status_read_clears_vblank_and_nmi_line() {
+        let mut ppu = ppu_at(VBLANK_SCANLINE, 2);
+        ppu.control.nmi_enable = true;
+        assert!(ppu.nmi_line());
+
+        assert_eq!(ppu.read_register(0x2002) & 0x80, 0x80);
+        assert!(!ppu.nmi_line());
+        assert_eq!(ppu.read_register(0x2002) & 0x80, 0);
+    }
+
+    #[test]
+    fn test_nmi_enable_during_vblank_raises_line() {
+        let mut ppu = ppu_at(VBLANK_SCANLINE + 1, 0);
+        assert!(!ppu.nmi_line());
+        ppu.write_register(0x2000, 0x80);
+        assert!(ppu.nmi_line());
+        ppu.write_register(0x2000, 0x00);
+        assert!(!ppu.nmi_line());
+    }
+
+    #[test]
+    fn test_ppudata_read_is_buffered() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        ppu.write_register(0x2006, 0x23);
+        ppu.write_register(0x2006, 0x05);
+        ppu.write_register(0x2007, 0x66);
+        ppu.write_register(0x2007, 0x77);
+
+        ppu.write_register(0x2006, 0x23);
+        ppu.write_register(0x2006, 0x05);
+        ppu.read_register(0x2007);
+        assert_eq!(ppu.read_register(0x2007), 0x66);
+        assert_eq!(ppu.read_register(0x2007), 0x77);
+    }
+
+    #[test]
+    fn test_horizontal_mirroring() {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        ppu.write_register(0x2006, 0x24);
+        ppu.write_register(0x2006, 0x05);
+        ppu.write_register(0x2007, 0x66);
+
+        ppu.write_register(0x2006, 0x20);
+        ppu.write_register(0x2006, 0x05);
+        ppu.read_register(0x2007);
+        assert_eq!(ppu.read_register(0x2007), 0x66);
+    }
+
+    /// Tile 1 solid in color 1, placed at the top left of the first nametable, with white as color 1 on black
+    fn ppu_with_tile() -> Ppu {
+        let mut ppu = Ppu::new(vec![], Mirroring::Horizontal);
+        let mut write = |address: u16, data: &[u8]| {
+            ppu.write_register(0x2006, (address >> 8) as u8);
+            ppu.write_register(0x2006, address as u8);
+            for &byte in data {
+                ppu.write_register(0x2007, byte);
+            }
+        };
+        write(0x0010, &[0xFF; 8]);
+        write(0x2000, &[1]);
+        write(0x3F00, &[0x0F, 0x30]);
+        write(0x3F10, &[0x0F, 0x16]);
+        ppu.write_register(0x2006, 0);
+        ppu.write_register(0x2006, 0);
+        ppu
+    }
+
+    fn run_frames(ppu: &mut Ppu, frames: u64) {
+        let end = ppu.frame + frames;
+        while ppu.frame != end {
+            ppu.tick();
+        }
+    }
+
+    #[test]
+    fn test_background_renders() {
+        let mut ppu = ppu_with_tile();
+        ppu.mask.update(0b0000_1010);
+        // The first frame starts without the pre-render line's prefetch
+        run_frames(&mut ppu, 2);
+
+        assert_eq!(ppu.frame_buffer()[0], 0x30);
+        assert_eq!(ppu.frame_buffer()[7 * SCREEN_WIDTH + 7], 0x30);
+        assert_eq!(ppu.frame_buffer()[8], 0x0F);
+        assert_eq!(ppu.frame_buffer()[8 * SCREEN_WIDTH], 0x0F);
+        assert_eq!(ppu.pixel_rgb(0, 0), (0xFF, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn test_sprite_zero_hit() {
+        let mut ppu = ppu_with_tile();
+        // Sprite 0 at (4, 3) overlapping the tile
+        ppu.oam_data[..4].copy_from_slice(&[2, 1, 0, 4]);
+        ppu.mask.update(0b0001_1110);
+        run_frames(&mut ppu, 1);
+        while ppu.scanline != 20 {
+            ppu.tick();
+        }
+
+        assert!(ppu.status.sprite_zero_hit);
+        // In front of the background, from the sprite palette
+        assert_eq!(ppu.frame_buffer()[3 * SCREEN_WIDTH + 4], 0x16);
+        assert_eq!(ppu.frame_buffer()[3 * SCREEN_WIDTH + 3], 0x30);
+    }
+}
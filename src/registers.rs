@@ -1,8 +1,11 @@
 #[derive(Debug, Default)]
 pub struct Registers {
     pub a: u8,
+    pub x: u8,
+    pub y: u8,
     pub status: Status,
     pub program_counter: u16,
+    pub stack_pointer: u8,
 }
 
 #[derive(Debug, Default)]
@@ -23,4 +26,28 @@ pub struct Status {
     pub zero: bool,
     /// C
     pub carry: bool,
-}
\ No newline at end of file
+}
+
+impl Status {
+    /// Packs the flags into the byte layout used when pushing P to the stack: NV-BDIZC
+    pub fn bits(&self) -> u8 {
+        (self.negative as u8) << 7
+            | (self.overflow as u8) << 6
+            | (self.unused as u8) << 5
+            | (self.brk as u8) << 4
+            | (self.decimal as u8) << 3
+            | (self.interrupt_disable as u8) << 2
+            | (self.zero as u8) << 1
+            | self.carry as u8
+    }
+    /// Unpacks a byte pulled from the stack. B and bit 5 do not exist as real flip-flops in the 6502, so
+    /// they are left untouched.
+    pub fn set_bits(&mut self, bits: u8) {
+        self.negative = bits & 0b1000_0000 != 0;
+        self.overflow = bits & 0b0100_0000 != 0;
+        self.decimal = bits & 0b0000_1000 != 0;
+        self.interrupt_disable = bits & 0b0000_0100 != 0;
+        self.zero = bits & 0b0000_0010 != 0;
+        self.carry = bits & 0b0000_0001 != 0;
+    }
+}
@@ -0,0 +1,72 @@
+//! Cartridges for the unit tests to run programs from
+
+use crate::{cartridge::Rom, ppu::Mirroring};
+
+/// A cartridge that is all zeros apart from the code and vectors a test puts in it
+#[derive(Debug, Clone)]
+pub struct TestRom {
+    pub prg_rom: Vec<u8>,
+    pub chr_rom: Vec<u8>,
+    pub mapper: u8,
+    pub mirroring: Mirroring,
+}
+
+impl TestRom {
+    /// `prg_pages` 16KiB pages of PRG-ROM and `chr_pages` 8KiB pages of CHR-ROM on mapper 0
+    pub fn new(prg_pages: usize, chr_pages: usize) -> Self {
+        Self {
+            prg_rom: vec![0; prg_pages * 0x4000],
+            chr_rom: vec![0; chr_pages * 0x2000],
+            mapper: 0,
+            mirroring: Mirroring::Horizontal,
+        }
+    }
+
+    /// NROM-128 with `program` at $C000, which is also where it resets to
+    pub fn nrom(program: &[u8]) -> Self {
+        Self::new(1, 1).code(0xC000, program).vectors(0, 0xC000, 0)
+    }
+
+    pub fn mapper(mut self, mapper: u8) -> Self {
+        self.mapper = mapper;
+        self
+    }
+
+    pub fn mirroring(mut self, mirroring: Mirroring) -> Self {
+        self.mirroring = mirroring;
+        self
+    }
+
+    /// Puts `code` where the CPU sees it at `address`, with the PRG-ROM mirrored across $8000-$FFFF
+    pub fn code(mut self, address: u16, code: &[u8]) -> Self {
+        let offset = (address as usize - 0x8000) % self.prg_rom.len();
+        self.prg_rom[offset..offset + code.len()].copy_from_slice(code);
+        self
+    }
+
+    pub fn vectors(self, nmi: u16, reset: u16, irq: u16) -> Self {
+        let vectors: Vec<u8> = [nmi, reset, irq].iter().flat_map(|vector| vector.to_le_bytes()).collect();
+        self.code(0xFFFA, &vectors)
+    }
+
+    /// The image as a .nes file would have it
+    pub fn ines(&self) -> Vec<u8> {
+        let flags_6 = (self.mapper << 4)
+            | match self.mirroring {
+                Mirroring::Horizontal => 0,
+                Mirroring::Vertical => 0b1,
+                _ => 0b1000,
+            };
+        let mut image = vec![b'N', b'E', b'S', 0x1A];
+        image.extend_from_slice(&[(self.prg_rom.len() / 0x4000) as u8, (self.chr_rom.len() / 0x2000) as u8]);
+        image.extend_from_slice(&[flags_6, self.mapper & 0xF0]);
+        image.resize(16, 0);
+        image.extend_from_slice(&self.prg_rom);
+        image.extend_from_slice(&self.chr_rom);
+        image
+    }
+
+    pub fn rom(&self) -> Rom {
+        Rom::new(&self.ines()).unwrap()
+    }
+}
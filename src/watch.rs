@@ -0,0 +1,53 @@
+//! Recording of memory accesses and interrupts for debugger watchpoints
+
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    Nmi,
+    Irq,
+    Brk,
+}
+
+/// Something a `Watch` saw happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Access { access: Access, address: u16, data: u8 },
+    /// The interrupt sequence ran and the handler is about to start
+    Interrupt(Interrupt),
+}
+
+/// The accesses and interrupts a chip should record. With no ranges and `interrupts` off, which is how it starts,
+/// nothing is recorded and checking costs one comparison per access.
+#[derive(Debug, Default)]
+pub struct Watch {
+    pub ranges: Vec<(RangeInclusive<u16>, Access)>,
+    pub interrupts: bool,
+    /// Everything recorded since the owner of the watch last took them
+    pub events: Vec<Event>,
+}
+
+impl Watch {
+    #[inline]
+    pub fn access(&mut self, access: Access, address: u16, data: u8) {
+        if self.ranges.is_empty() {
+            return;
+        }
+        if self.ranges.iter().any(|(range, kind)| *kind == access && range.contains(&address)) {
+            self.events.push(Event::Access { access, address, data });
+        }
+    }
+
+    #[inline]
+    pub fn interrupt(&mut self, interrupt: Interrupt) {
+        if self.interrupts {
+            self.events.push(Event::Interrupt(interrupt));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! blargg's accuracy test ROMs, the ones that report through $6000, https://github.com/christopherpow/nes-test-roms
+//!
+//! The ROMs aren't checked in. Put any number of them in `tests/blargg`, in subdirectories if you like, or point
+//! `BLARGG_ROMS` at where they are. ROMs for mappers that aren't supported yet are skipped.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use rnes::{
+    cartridge::{Rom, RomError},
+    headless::{self, TestOutcome},
+};
+
+/// A minute of emulated time, the longest suites take about half that
+const MAX_FRAMES: u64 = 60 * 60;
+
+fn find_roms(directory: &Path, roms: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        if path.is_dir() {
+            find_roms(&path, roms);
+        } else if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("nes")) {
+            roms.push(path);
+        }
+    }
+}
+
+#[test]
+fn blargg_test_roms() {
+    let directory = env::var_os("BLARGG_ROMS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/blargg"));
+    let mut roms = vec![];
+    find_roms(&directory, &mut roms);
+    if roms.is_empty() {
+        eprintln!("skipping, no ROMs in {}", directory.display());
+        return;
+    }
+    roms.sort();
+
+    let mut failures = vec![];
+    for path in &roms {
+        let name = path.strip_prefix(&directory).unwrap_or(path).display();
+        let rom = Rom::new(&fs::read(path).unwrap()).unwrap_or_else(|error| panic!("{name}: {error}"));
+        let report = match headless::run_test_rom(rom, MAX_FRAMES) {
+            Ok(report) => report,
+            Err(error @ RomError::UnsupportedMapper(_)) => {
+                eprintln!("skipping {name}: {error}");
+                continue;
+            }
+            Err(error) => panic!("{name}: {error}"),
+        };
+        if report.outcome != TestOutcome::Passed {
+            failures.push(format!("{name}: {:?}\n{}", report.outcome, report.message));
+        }
+    }
+    assert!(failures.is_empty(), "{} of {} failed:\n{}", failures.len(), roms.len(), failures.join("\n\n"));
+}
@@ -0,0 +1,80 @@
+//! Klaus Dormann's 6502 functional and decimal tests, https://github.com/Klaus2m5/6502_65C02_functional_tests
+//!
+//! The binaries aren't checked in. Put `6502_functional_test.bin` and `6502_decimal_test.bin` from the
+//! repository's `bin_files` in `tests/roms`, or point `DORMANN_ROMS` at the directory they are in. Each test
+//! passes without doing anything when its binary is missing.
+
+use std::{env, fs, path::PathBuf};
+
+use rnes::cpu::{Cpu, CpuVariant};
+
+/// Where the prebuilt functional test loops forever once every test has passed
+const FUNCTIONAL_SUCCESS: u16 = 0x3469;
+/// The decimal test leaves 0 here on success and 1 on failure
+const DECIMAL_ERROR: u16 = 0x000B;
+/// Far more than either test needs, so a core that wanders off still stops
+const MAX_INSTRUCTIONS: u64 = 200_000_000;
+
+fn load(name: &str) -> Option<Vec<u8>> {
+    let directory = env::var_os("DORMANN_ROMS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/roms"));
+    match fs::read(directory.join(name)) {
+        Ok(image) => Some(image),
+        Err(_) => {
+            eprintln!("skipping, {name} not found in {}", directory.display());
+            None
+        }
+    }
+}
+
+/// Runs until the program traps, either on an instruction that jumps to itself or a jam. Returns where it
+/// stopped. BRK isn't a trap, the functional test runs one in its interrupt tests and expects it to return.
+fn run_until_trap(cpu: &mut Cpu) -> u16 {
+    for _ in 0..MAX_INSTRUCTIONS {
+        let address = cpu.registers.program_counter;
+        cpu.step();
+        if cpu.registers.program_counter == address || cpu.jam().is_some() {
+            return address;
+        }
+    }
+    panic!("no trap after {MAX_INSTRUCTIONS} instructions, at ${:04X}", cpu.registers.program_counter);
+}
+
+fn cpu_with(image: &[u8], load_at: u16, start: u16, variant: CpuVariant) -> Cpu {
+    let mut cpu = Cpu::new();
+    cpu.variant = variant;
+    cpu.bus.load(load_at, image);
+    cpu.registers.program_counter = start;
+    cpu
+}
+
+#[test]
+fn brk_is_not_a_trap() {
+    // BRK, its padding byte, then a jump to self at $0402. The handler at $0500 just returns.
+    let mut cpu = cpu_with(&[0x00, 0xEA, 0x4C, 0x02, 0x04], 0x0400, 0x0400, CpuVariant::Nmos6502);
+    cpu.bus.load(0x0500, &[0x40]);
+    cpu.bus.load(0xFFFE, &[0x00, 0x05]);
+    assert_eq!(run_until_trap(&mut cpu), 0x0402);
+}
+
+#[test]
+fn functional_test() {
+    let Some(image) = load("6502_functional_test.bin") else {
+        return;
+    };
+    // It covers decimal mode, so it needs a chip that has one
+    let mut cpu = cpu_with(&image, 0x0000, 0x0400, CpuVariant::Nmos6502);
+    let trap = run_until_trap(&mut cpu);
+    assert_eq!(trap, FUNCTIONAL_SUCCESS, "trapped at ${trap:04X}, look it up in the test's listing");
+}
+
+#[test]
+fn decimal_test() {
+    let Some(image) = load("6502_decimal_test.bin") else {
+        return;
+    };
+    let mut cpu = cpu_with(&image, 0x0200, 0x0200, CpuVariant::Nmos6502);
+    let trap = run_until_trap(&mut cpu);
+    assert_eq!(cpu.mem_read(DECIMAL_ERROR), 0, "decimal test failed, stopped at ${trap:04X}");
+}
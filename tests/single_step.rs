@@ -0,0 +1,182 @@
+//! Tom Harte's per-opcode single step tests, https://github.com/SingleStepTests/65x02
+//!
+//! Each opcode has a JSON file of a few thousand vectors, every one an instruction's starting registers and RAM,
+//! the registers and RAM after it ran and the bus activity of each of its cycles. The files aren't checked in.
+//! Copy the `v1` directories of `nes6502`, `6502` and `wdc65c02` to `tests/single_step/<name>`, or point
+//! `SINGLE_STEP_TESTS` at a directory laid out that way. A variant passes without doing anything when its
+//! directory is missing, and so does any single opcode whose file is.
+
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use rnes::{
+    bus::Bus,
+    cpu::{Cpu, CpuVariant},
+    opcodes::OpcodeName,
+};
+use serde_json::Value;
+
+/// Sparse RAM that logs every cycle as (address, data, is write)
+struct RecordingBus {
+    memory: Box<[u8; 0x10000]>,
+    cycles: Vec<(u16, u8, bool)>,
+}
+
+impl Bus for RecordingBus {
+    fn mem_read(&mut self, address: u16) -> u8 {
+        let data = self.memory[address as usize];
+        self.cycles.push((address, data, false));
+        data
+    }
+    fn mem_write(&mut self, address: u16, data: u8) {
+        self.cycles.push((address, data, true));
+        self.memory[address as usize] = data;
+    }
+}
+
+/// The registers and RAM of one side of a vector
+struct State {
+    pc: u16,
+    s: u8,
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+    ram: Vec<(u16, u8)>,
+}
+
+impl State {
+    fn parse(json: &Value) -> Self {
+        let number = |key: &str| json[key].as_u64().unwrap_or_else(|| panic!("{key} is missing"));
+        Self {
+            pc: number("pc") as u16,
+            s: number("s") as u8,
+            a: number("a") as u8,
+            x: number("x") as u8,
+            y: number("y") as u8,
+            p: number("p") as u8,
+            ram: json["ram"]
+                .as_array()
+                .expect("ram is missing")
+                .iter()
+                .map(|cell| (cell[0].as_u64().unwrap() as u16, cell[1].as_u64().unwrap() as u8))
+                .collect(),
+        }
+    }
+}
+
+/// Runs one vector, returning what differed
+fn run_vector(cpu: &mut Cpu<RecordingBus>, vector: &Value) -> Result<(), String> {
+    let initial = State::parse(&vector["initial"]);
+    let expected = State::parse(&vector["final"]);
+    let expected_cycles: Vec<(u16, u8, bool)> = vector["cycles"]
+        .as_array()
+        .expect("cycles are missing")
+        .iter()
+        .map(|cycle| (cycle[0].as_u64().unwrap() as u16, cycle[1].as_u64().unwrap() as u8, cycle[2] == "write"))
+        .collect();
+
+    for &(address, _) in &expected.ram {
+        cpu.bus.memory[address as usize] = 0;
+    }
+    for &(address, data) in &initial.ram {
+        cpu.bus.memory[address as usize] = data;
+    }
+    cpu.bus.cycles.clear();
+    let registers = &mut cpu.registers;
+    registers.program_counter = initial.pc;
+    registers.stack_pointer = initial.s;
+    registers.a = initial.a;
+    registers.x = initial.x;
+    registers.y = initial.y;
+    registers.status.set_bits(initial.p);
+
+    cpu.step();
+
+    let mut errors = String::new();
+    let registers = &cpu.registers;
+    let mut compare = |what: &str, expected: u16, actual: u16| {
+        if expected != actual {
+            let _ = write!(errors, " {what}: expected ${expected:02X}, got ${actual:02X};");
+        }
+    };
+    compare("pc", expected.pc, registers.program_counter);
+    compare("s", expected.s.into(), registers.stack_pointer.into());
+    compare("a", expected.a.into(), registers.a.into());
+    compare("x", expected.x.into(), registers.x.into());
+    compare("y", expected.y.into(), registers.y.into());
+    // B and bit 5 only exist on the stack, the vectors always have both set
+    compare("p", (expected.p | 0x30).into(), (registers.status.bits() | 0x30).into());
+    for &(address, data) in &expected.ram {
+        compare(&format!("${address:04X}"), data.into(), cpu.bus.memory[address as usize].into());
+    }
+    if cpu.bus.cycles != expected_cycles {
+        let _ = write!(errors, " cycles: expected {expected_cycles:02X?}, got {:02X?};", cpu.bus.cycles);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn run_variant(name: &str, variant: CpuVariant) {
+    let directory = env::var_os("SINGLE_STEP_TESTS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/single_step"))
+        .join(name);
+    if !directory.is_dir() {
+        eprintln!("skipping, {} not found", directory.display());
+        return;
+    }
+
+    let mut cpu = Cpu::with_bus(RecordingBus { memory: Box::new([0; 0x10000]), cycles: vec![] });
+    cpu.variant = variant;
+    // What the vectors were generated with
+    cpu.magic.lxa = 0xEE;
+
+    let mut failures = vec![];
+    for (byte, opcode) in variant.opcodes().iter().enumerate() {
+        // After these the CPU is stopped, the vectors record whatever the chip left on the bus meanwhile
+        if let OpcodeName::JAM | OpcodeName::STP | OpcodeName::WAI = opcode.name() {
+            continue;
+        }
+        let Ok(file) = fs::read_to_string(directory.join(format!("{byte:02x}.json"))) else {
+            continue;
+        };
+        let vectors: Value = serde_json::from_str(&file).unwrap_or_else(|error| panic!("{byte:02x}.json: {error}"));
+        let vectors = vectors.as_array().expect("a file is an array of vectors");
+
+        let mut failed = 0;
+        let mut first = None;
+        for vector in vectors {
+            if let Err(errors) = run_vector(&mut cpu, vector) {
+                failed += 1;
+                first.get_or_insert_with(|| format!("\"{}\"{errors}", vector["name"].as_str().unwrap_or("?")));
+            }
+        }
+        if let Some(first) = first {
+            failures.push(format!(
+                "${byte:02X} {} {}: {failed} of {} failed, first {first}",
+                opcode.name(),
+                opcode.addressing_mode(),
+                vectors.len()
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{name}:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn ricoh_2a03() {
+    run_variant("nes6502", CpuVariant::Ricoh2A03);
+}
+
+#[test]
+fn nmos_6502() {
+    run_variant("6502", CpuVariant::Nmos6502);
+}
+
+#[test]
+fn wdc_65c02() {
+    run_variant("wdc65c02", CpuVariant::Wdc65C02);
+}